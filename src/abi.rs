@@ -0,0 +1,454 @@
+use crypto::{sha3::Sha3, digest::Digest};
+use serde::{Deserialize, Serialize};
+use crate::common::{hex_to_decimal, topic_to_address};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbiParam {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub indexed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbiItem {
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<AbiParam>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventAbi {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+}
+
+impl EventAbi {
+    // accepts `Deposit(address indexed user, uint256 amount)` as well as the bare `Deposit(address,uint256)`.
+    pub fn parse(signature: &str) -> Option<Self> {
+        let signature = signature.trim();
+        let open = signature.find('(')?;
+
+        if !signature.ends_with(')') {
+            return None;
+        }
+
+        let name = signature[..open].trim().to_string();
+        let params = &signature[open + 1..signature.len() - 1];
+
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+
+        let mut inputs = vec![];
+
+        for (i, param) in params.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()).enumerate() {
+            let parts = param.split_whitespace().collect::<Vec<&str>>();
+            let kind = canonical_type(parts.get(0)?);
+
+            if !is_supported_type(kind.as_str()) {
+                return None;
+            }
+
+            inputs.push(AbiParam {
+                indexed: parts.contains(&"indexed"),
+                name: parts.iter().skip(1).find(|p| **p != "indexed").map(|p| p.to_string())
+                    .unwrap_or(format!("arg{}", i)),
+                kind,
+            });
+        }
+
+        Some(EventAbi {
+            name,
+            inputs,
+        })
+    }
+
+    // picks the event from an uploaded abi json document.
+    pub fn from_abi_json(abi: &str, event_name: &str) -> Option<Self> {
+        let items = serde_json::from_str::<Vec<AbiItem>>(abi).ok()?;
+
+        let item = items.into_iter()
+            .find(|i| i.kind == "event" && i.name == event_name)?;
+
+        if !item.inputs.iter().all(|i| is_supported_type(i.kind.as_str())) {
+            return None;
+        }
+
+        Some(EventAbi {
+            name: item.name,
+            inputs: item.inputs.into_iter()
+                .map(|i| AbiParam { kind: canonical_type(i.kind.as_str()), ..i })
+                .collect(),
+        })
+    }
+
+    pub fn canonical(&self) -> String {
+        let types = self.inputs.iter().map(|i| canonical_type(i.kind.as_str())).collect::<Vec<String>>();
+
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    // the form stored in db, parse() reads it back with the indexed flags and names.
+    pub fn to_string(&self) -> String {
+        let params = self.inputs.iter()
+            .map(|i| if i.indexed { format!("{} indexed {}", i.kind, i.name) } else { format!("{} {}", i.kind, i.name) })
+            .collect::<Vec<String>>();
+
+        format!("{}({})", self.name, params.join(", "))
+    }
+
+    pub fn topic(&self) -> String {
+        let mut hasher = Sha3::keccak256();
+        hasher.input_str(self.canonical().as_str());
+
+        format!("0x{}", hasher.result_str())
+    }
+
+    // decoded (name, value) pairs, indexed params come from the topics and the rest from data.
+    pub fn decode(&self, topics: &[String], data: &str) -> Vec<(String, String)> {
+        let mut indexed = self.inputs.iter().map(|i| i.indexed).collect::<Vec<bool>>();
+
+        // a bare signature has no indexed markers, in that case the leading params are assumed to be indexed.
+        if !indexed.iter().any(|i| *i) {
+            for flag in indexed.iter_mut().take(topics.len().saturating_sub(1)) {
+                *flag = true;
+            }
+        }
+
+        let data = data.trim_start_matches("0x");
+        let words = (0..data.len() / 64).map(|i| &data[i * 64..(i + 1) * 64]).collect::<Vec<&str>>();
+
+        let mut res = vec![];
+        let mut topic_index = 1;
+        let mut word_index = 0;
+
+        for (input, is_indexed) in self.inputs.iter().zip(indexed) {
+            let value = if is_indexed {
+                let topic = topics.get(topic_index).map(|t| t.trim_start_matches("0x")).unwrap_or("");
+                topic_index += 1;
+
+                // dynamic indexed values are only available as their keccak hash.
+                if is_dynamic(input.kind.as_str()) {
+                    format!("0x{}", topic)
+                } else {
+                    decode_word(input.kind.as_str(), topic)
+                }
+            } else {
+                let head = words.get(word_index).copied().unwrap_or("");
+                word_index += 1;
+
+                if is_dynamic(input.kind.as_str()) {
+                    decode_dynamic(input.kind.as_str(), &words, head)
+                } else {
+                    decode_word(input.kind.as_str(), head)
+                }
+            };
+
+            res.push((input.name.clone(), value));
+        }
+
+        res
+    }
+}
+
+// a single return value of an eth_call.
+pub fn decode_output(kind: &str, data: &str) -> String {
+    let data = data.trim_start_matches("0x");
+    let words = (0..data.len() / 64).map(|i| &data[i * 64..(i + 1) * 64]).collect::<Vec<&str>>();
+    let head = words.first().copied().unwrap_or("");
+
+    if is_dynamic(kind) {
+        decode_dynamic(kind, &words, head)
+    } else {
+        decode_word(kind, head)
+    }
+}
+
+// the full type name the topic hash is computed over: `uint` and `int` are aliases of uint256 and
+// int256, also as array elements and tuple members.
+pub fn canonical_type(kind: &str) -> String {
+    let kind = kind.trim();
+
+    if kind.starts_with('(') {
+        let mut depth = 0;
+
+        for (i, c) in kind.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+
+            if depth == 0 {
+                let members = split_members(&kind[1..i]).into_iter().map(canonical_type).collect::<Vec<String>>();
+                return format!("({}){}", members.join(","), &kind[i + 1..]);
+            }
+        }
+
+        return kind.to_string();
+    }
+
+    let (base, suffix) = kind.split_at(kind.find('[').unwrap_or(kind.len()));
+
+    let base = match base {
+        "uint" => "uint256",
+        "int" => "int256",
+        "fixed" => "fixed128x18",
+        "ufixed" => "ufixed128x18",
+        b => b,
+    };
+
+    format!("{}{}", base, suffix)
+}
+
+// commas of the tuple itself, not those of nested tuples.
+fn split_members(members: &str) -> Vec<&str> {
+    let mut res = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in members.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                res.push(&members[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if !members.trim().is_empty() {
+        res.push(&members[start..]);
+    }
+
+    res
+}
+
+fn is_static_type(kind: &str) -> bool {
+    if kind == "address" || kind == "bool" {
+        return true;
+    }
+
+    for prefix in ["uint", "int", "bytes"] {
+        if let Some(size) = kind.strip_prefix(prefix) {
+            return size.is_empty() && prefix != "bytes" || size.parse::<u32>().is_ok();
+        }
+    }
+
+    false
+}
+
+fn is_dynamic(kind: &str) -> bool {
+    kind == "string" || kind == "bytes" || kind.ends_with("[]")
+}
+
+// tuples and fixed size arrays are not supported.
+fn is_supported_type(kind: &str) -> bool {
+    kind == "string" || kind == "bytes" || is_static_type(kind)
+        || kind.strip_suffix("[]").map(is_static_type).unwrap_or(false)
+}
+
+fn decode_word(kind: &str, word: &str) -> String {
+    if word.len() != 64 {
+        return "?".to_string();
+    }
+
+    if kind == "address" {
+        return topic_to_address(word);
+    }
+
+    if kind == "bool" {
+        return (word.trim_start_matches('0') != "").to_string();
+    }
+
+    if kind.starts_with("uint") {
+        return hex_to_decimal(word).unwrap_or("?".to_string());
+    }
+
+    if kind.starts_with("int") {
+        // two's complement, negative values have the top bit set.
+        if u8::from_str_radix(&word[..1], 16).unwrap_or(0) < 8 {
+            return hex_to_decimal(word).unwrap_or("?".to_string());
+        }
+
+        let inverted = word.chars()
+            .map(|c| std::char::from_digit(15 - c.to_digit(16).unwrap_or(0), 16).unwrap())
+            .collect::<String>();
+
+        return match hex_to_decimal(inverted.as_str()) {
+            Some(v) => format!("-{}", add_one(v.as_str())),
+            None => "?".to_string(),
+        };
+    }
+
+    // bytesN is left aligned.
+    let size = kind.trim_start_matches("bytes").parse::<usize>().unwrap_or(32).min(32);
+    format!("0x{}", &word[..size * 2])
+}
+
+fn decode_dynamic(kind: &str, words: &[&str], head: &str) -> String {
+    let offset = match usize::from_str_radix(head.trim_start_matches('0'), 16) {
+        Ok(o) => o / 32,
+        Err(_) if head.len() == 64 => 0,
+        Err(_) => return "?".to_string(),
+    };
+
+    // the length comes from the log, it is never trusted beyond the words actually present.
+    let length = match words.get(offset).map(|w| usize::from_str_radix(w.trim_start_matches('0'), 16)) {
+        Some(Ok(l)) => l,
+        Some(Err(_)) if words[offset].trim_start_matches('0').is_empty() => 0,
+        _ => return "?".to_string(),
+    };
+
+    let tail = &words[offset + 1..];
+
+    if let Some(element) = kind.strip_suffix("[]") {
+        let values = tail.iter()
+            .take(length.min(tail.len()))
+            .map(|w| decode_word(element, w))
+            .collect::<Vec<String>>();
+
+        return format!("[{}]", values.join(", "));
+    }
+
+    let content = tail.concat();
+    let content = match length.checked_mul(2) {
+        Some(l) => &content[..l.min(content.len())],
+        None => return "?".to_string(),
+    };
+
+    if kind == "string" {
+        let bytes = (0..content.len() / 2)
+            .filter_map(|i| u8::from_str_radix(&content[i * 2..i * 2 + 2], 16).ok())
+            .collect::<Vec<u8>>();
+
+        return String::from_utf8_lossy(&bytes).to_string();
+    }
+
+    format!("0x{}", content)
+}
+
+fn add_one(decimal: &str) -> String {
+    let mut digits = decimal.chars().rev().map(|c| c.to_digit(10).unwrap_or(0)).collect::<Vec<u32>>();
+    let mut carry = 1;
+
+    for d in digits.iter_mut() {
+        let sum = *d + carry;
+        *d = sum % 10;
+        carry = sum / 10;
+    }
+
+    if carry > 0 {
+        digits.push(carry);
+    }
+
+    digits.iter().rev().map(|d| std::char::from_digit(*d, 10).unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    const APPROVAL_TOPIC: &str = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+    fn address_topic(address: &str) -> String {
+        format!("0x{:0>64}", address.trim_start_matches("0x"))
+    }
+
+    #[test]
+    fn aliases_are_expanded() {
+        assert_eq!(canonical_type("uint"), "uint256");
+        assert_eq!(canonical_type("int[]"), "int256[]");
+        assert_eq!(canonical_type("uint[2][]"), "uint256[2][]");
+        assert_eq!(canonical_type("(uint,address,(int,bytes)[])[]"), "(uint256,address,(int256,bytes)[])[]");
+        assert_eq!(canonical_type("uint8"), "uint8");
+        assert_eq!(canonical_type("bytes32"), "bytes32");
+    }
+
+    #[test]
+    fn topic_of_known_events() {
+        let transfer = EventAbi::parse("Transfer(address indexed from, address indexed to, uint value)").unwrap();
+        assert_eq!(transfer.canonical(), "Transfer(address,address,uint256)");
+        assert_eq!(transfer.topic(), TRANSFER_TOPIC);
+
+        assert_eq!(EventAbi::parse("Approval(address,address,uint256)").unwrap().topic(), APPROVAL_TOPIC);
+    }
+
+    #[test]
+    fn abi_json_uses_canonical_types() {
+        let abi = r#"[{"type":"event","name":"Transfer","inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"value","type":"uint","indexed":false}]}]"#;
+
+        assert_eq!(EventAbi::from_abi_json(abi, "Transfer").unwrap().topic(), TRANSFER_TOPIC);
+        assert!(EventAbi::from_abi_json(abi, "Approval").is_none());
+    }
+
+    #[test]
+    fn decodes_indexed_and_data_params() {
+        let event = EventAbi::parse("Transfer(address indexed from, address indexed to, uint256 value)").unwrap();
+        let topics = vec![
+            TRANSFER_TOPIC.to_string(),
+            address_topic("0x1111111111111111111111111111111111111111"),
+            address_topic("0x2222222222222222222222222222222222222222"),
+        ];
+        let data = format!("0x{:0>64}", "3e8");
+
+        assert_eq!(event.decode(&topics, data.as_str()), vec![
+            ("from".to_string(), "0x1111111111111111111111111111111111111111".to_string()),
+            ("to".to_string(), "0x2222222222222222222222222222222222222222".to_string()),
+            ("value".to_string(), "1000".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn bare_signature_takes_indexed_params_from_topics() {
+        let event = EventAbi::parse("Transfer(address,address,uint256)").unwrap();
+        let topics = vec![
+            TRANSFER_TOPIC.to_string(),
+            address_topic("0x1111111111111111111111111111111111111111"),
+            address_topic("0x2222222222222222222222222222222222222222"),
+        ];
+
+        let values = event.decode(&topics, format!("0x{:0>64}", "1").as_str());
+        assert_eq!(values[1], ("arg1".to_string(), "0x2222222222222222222222222222222222222222".to_string()));
+        assert_eq!(values[2], ("arg2".to_string(), "1".to_string()));
+    }
+
+    #[test]
+    fn decodes_negative_ints_and_strings() {
+        let event = EventAbi::parse("Note(int amount, string memo)").unwrap();
+        let data = format!("0x{}{:0>64}{:0>64}{:0<64}", "f".repeat(64), "40", "5", "68656c6c6f");
+
+        assert_eq!(event.decode(&[event.topic()], data.as_str()), vec![
+            ("amount".to_string(), "-1".to_string()),
+            ("memo".to_string(), "hello".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn malformed_lengths_are_capped_to_the_data() {
+        let array = format!("0x{:0>64}{:0>64}{:0>64}", "20", "ffffffffffff", "1");
+        assert_eq!(decode_output("uint256[]", array.as_str()), "[1]");
+
+        let string = format!("0x{:0>64}{:0>64}{:0<64}", "20", "ffffffffffff", "6869");
+        assert_eq!(decode_output("string", string.as_str()).trim_end_matches('\0'), "hi");
+
+        let overflow = format!("0x{:0>64}{:0>64}{:0<64}", "20", "8000000000000000", "6869");
+        assert_eq!(decode_output("bytes", overflow.as_str()), "?");
+
+        let unparsable = format!("0x{:0>64}{}{:0<64}", "20", "f".repeat(64), "6869");
+        assert_eq!(decode_output("bytes", unparsable.as_str()), "?");
+        assert_eq!(decode_output("uint256[]", format!("0x{:0>64}", "20").as_str()), "?");
+    }
+}
@@ -3,12 +3,104 @@ use structopt::StructOpt;
 
 #[derive(StructOpt, Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
-    #[structopt(short = "bt", long = "bot-token", env = "BOT_TOKEN")]
+    /// required to run the bot, the export subcommand works offline without it.
+    #[structopt(short = "bt", long = "bot-token", env = "BOT_TOKEN", default_value = "", hide_default_value = true)]
     pub bot_token: String,
 
-    #[structopt(short = "ea", long = "ether-api", env = "ETHER_API")]
+    /// required to run the bot, the export subcommand works offline without it.
+    #[structopt(short = "ea", long = "ether-api", env = "ETHER_API", default_value = "", hide_default_value = true)]
     pub ether_api: String,
 
     #[structopt(short = "db", long = "db", env = "DB_PATH")]
     pub db_path: String,
-}
\ No newline at end of file
+
+    #[structopt(long = "price-api", env = "PRICE_API", default_value = "https://min-api.cryptocompare.com")]
+    pub price_api: String,
+
+    #[structopt(long = "price-file", env = "PRICE_FILE")]
+    pub price_file: Option<String>,
+
+    #[structopt(long = "parse-mode", env = "PARSE_MODE", default_value = "html", possible_values = &["html", "markdownv2"])]
+    pub parse_mode: String,
+
+    #[structopt(long = "rpc-url", env = "RPC_URL", default_value = "https://cloudflare-eth.com")]
+    pub rpc_url: String,
+
+    /// who may change the watchlist of a group: admins, owner or members.
+    #[structopt(long = "manage-role", env = "MANAGE_ROLE", default_value = "admins", possible_values = &["admins", "owner", "members"])]
+    pub manage_role: String,
+
+    /// csv of `address,name,category` rows extending the bundled known address dataset.
+    #[structopt(long = "entities-file", env = "ENTITIES_FILE")]
+    pub entities_file: Option<String>,
+
+    /// every notification is also POSTed here as a json event.
+    #[structopt(long = "webhook-url", env = "WEBHOOK_URL")]
+    pub webhook_url: Option<String>,
+
+    /// key of the hmac-sha256 signature sent in the X-Signature-256 header, required with --webhook-url.
+    #[structopt(long = "webhook-secret", env = "WEBHOOK_SECRET")]
+    pub webhook_secret: Option<String>,
+
+    /// smtp server used for email alerts and /email verification codes.
+    #[structopt(long = "smtp-host", env = "SMTP_HOST")]
+    pub smtp_host: Option<String>,
+
+    /// defaults to 465 with --smtp-tls tls, 587 with starttls and 25 with none.
+    #[structopt(long = "smtp-port", env = "SMTP_PORT")]
+    pub smtp_port: Option<u16>,
+
+    #[structopt(long = "smtp-username", env = "SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+
+    #[structopt(long = "smtp-password", env = "SMTP_PASSWORD")]
+    pub smtp_password: Option<String>,
+
+    /// none is meant for a local smtp catcher.
+    #[structopt(long = "smtp-tls", env = "SMTP_TLS", default_value = "starttls", possible_values = &["none", "starttls", "tls"])]
+    pub smtp_tls: String,
+
+    #[structopt(long = "smtp-from", env = "SMTP_FROM", default_value = "EthWalletTrackerBot <noreply@localhost>")]
+    pub smtp_from: String,
+
+    /// telegram user ids allowed to use the admin commands, comma separated.
+    #[structopt(long = "admins", env = "ADMINS", use_delimiter = true)]
+    pub admins: Vec<u64>,
+
+    #[structopt(subcommand)]
+    pub cmd: Option<CliCommand>,
+}
+
+#[derive(StructOpt, Debug, Clone, Serialize, Deserialize)]
+pub enum CliCommand {
+    /// Writes the stored transactions of an address, or of every tracked wallet with `all`, to a file.
+    Export {
+        address: String,
+
+        #[structopt(long = "format", default_value = "csv", possible_values = &["csv", "json"])]
+        format: String,
+
+        /// YYYY-MM-DD
+        #[structopt(long = "from")]
+        from: Option<String>,
+
+        /// YYYY-MM-DD
+        #[structopt(long = "to")]
+        to: Option<String>,
+
+        #[structopt(short = "o", long = "output")]
+        output: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_runs_without_bot_credentials() {
+        let config = AppConfig::from_iter_safe(&["bot", "--db", "bot.db", "export", "all", "-o", "out.csv"]).unwrap();
+
+        assert!(matches!(config.cmd, Some(CliCommand::Export { .. })));
+    }
+}
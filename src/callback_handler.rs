@@ -0,0 +1,198 @@
+use teloxide::{prelude::*};
+use std::error::Error;
+use crate::{AppConfig, DataRepository, SqliteDb};
+use crate::common::{has_manage_role, is_banned, now};
+use crate::models::mute::Mute;
+use crate::models::transaction::TransactionFilter;
+use crate::renderer::{Markup, render_transaction_page};
+use crate::throttle;
+use structopt::StructOpt;
+
+pub const MUTE_TOKEN: &str = "mute_token";
+pub const MUTE_WALLET: &str = "mute_wallet";
+// the wallet mute button offers these durations, anything else in the callback data is forged.
+pub const MUTE_WALLET_SECONDS: [i64; 1] = [3600];
+pub const TX_PAGE: &str = "tx";
+// telegram rejects buttons with more callback data than this many bytes.
+pub const CALLBACK_DATA_LIMIT: usize = 64;
+pub const NOT_ALLOWED: &str = "You are not allowed to change what this chat tracks.";
+pub const BANNED: &str = "This chat is banned from using the bot.";
+
+pub async fn callback_handler(bot: AutoSend<Bot>, query: CallbackQuery)
+                              -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    let answer = match (&query.data, &query.message) {
+        // buttons of messages sent before the ban stop working too.
+        (Some(_), Some(message)) if is_banned(message.chat.id)
+            && !AppConfig::from_args().admins.contains(&query.from.id.0) => BANNED,
+        (Some(data), Some(message)) if data.starts_with(format!("{}:", TX_PAGE).as_str()) => handle_page_callback(&bot, data.as_str(), message).await,
+        (Some(data), Some(message)) => {
+            if has_manage_role(&bot, &message.chat, query.from.id).await {
+                handle_callback(data.as_str(), message)
+            } else {
+                NOT_ALLOWED
+            }
+        }
+        _ => "Unknown action.",
+    };
+
+    bot.answer_callback_query(query.id).text(answer).await?;
+
+    Ok(())
+}
+
+// tx:<wallet id>:<page>:<filter id>, the filter itself is kept in db as it does not fit
+// telegram's 64 bytes. 0 is no filter.
+pub fn page_callback(wallet_id: i64, page: i64, filter_id: i64) -> String {
+    format!("{}:{}:{}:{}", TX_PAGE, wallet_id, page, filter_id)
+}
+
+fn parse_page_callback(data: &str) -> Option<(i64, i64, i64)> {
+    match data.split(':').collect::<Vec<&str>>().as_slice() {
+        [TX_PAGE, wallet_id, page, filter_id] => Some((wallet_id.parse::<i64>().ok()?, page.parse::<i64>().ok()?,
+                                                       filter_id.parse::<i64>().ok()?)),
+        _ => None,
+    }
+}
+
+async fn handle_page_callback(bot: &AutoSend<Bot>, data: &str, message: &Message) -> &'static str {
+    let (wallet_id, page, filter_id) = match parse_page_callback(data) {
+        Some(p) => p,
+        None => return "Unknown action.",
+    };
+
+    let mut db = SqliteDb::get_connection();
+
+    let user = db.get_user(message.chat.id.0);
+
+    if user.is_none() {
+        return "Please send /start command.";
+    }
+
+    let user_id = user.unwrap().id.unwrap();
+
+    let wallet = match db.get_user_wallets(user_id).into_iter().find(|w| w.id == Some(wallet_id)) {
+        Some(w) => w,
+        None => return "This wallet address is not tracked by you.",
+    };
+
+    let filter = if filter_id == 0 {
+        TransactionFilter::default()
+    } else {
+        match db.get_page_filter(user_id, filter_id) {
+            Some(f) => f,
+            None => return "This list has expired, please send /txlist again.",
+        }
+    };
+
+    let total = db.count_transactions(wallet_id, wallet.address.clone(), &filter);
+    let txs = db.get_transactions(wallet_id, wallet.address.clone(), &filter, TransactionFilter::PAGE_SIZE,
+                                  (page - 1) * TransactionFilter::PAGE_SIZE);
+    db.drop();
+
+    let notification = render_transaction_page(&txs, &wallet, filter_id, page, total, Markup::from_config(&AppConfig::from_args()));
+
+    throttle::acquire(message.chat.id).await;
+
+    let mut request = bot.edit_message_text(message.chat.id, message.id, notification.text.as_str())
+        .parse_mode(notification.parse_mode)
+        .disable_web_page_preview(true);
+
+    if let Some(keyboard) = notification.keyboard {
+        request = request.reply_markup(keyboard);
+    }
+
+    if request.await.is_err() {
+        return "Could not load the page.";
+    }
+
+    ""
+}
+
+fn handle_callback<'a>(data: &str, message: &Message) -> &'a str {
+    let mut db = SqliteDb::get_connection();
+
+    let user = db.get_user(message.chat.id.0);
+
+    if user.is_none() {
+        return "Please send /start command.";
+    }
+
+    let user_id = user.unwrap().id.unwrap();
+    let parts = data.splitn(3, ':').collect::<Vec<&str>>();
+
+    let wallet_id = match parts.get(1).map(|id| id.parse::<i64>()) {
+        Some(Ok(id)) => id,
+        _ => return "Unknown action.",
+    };
+
+    if !db.get_user_wallets(user_id).iter().any(|w| w.id == Some(wallet_id)) {
+        return "This wallet address is not tracked by you.";
+    }
+
+    let answer = match parts.as_slice() {
+        [MUTE_TOKEN, _, token] => {
+            // a token name cut to fit the callback data is completed from the stored transfers.
+            let token = if data.len() < CALLBACK_DATA_LIMIT {
+                token.to_string()
+            } else {
+                db.get_all_transactions(wallet_id).into_iter()
+                    .map(|t| t.token)
+                    .find(|t| t.starts_with(token))
+                    .unwrap_or(token.to_string())
+            };
+
+            db.add_mute(Mute::new(user_id, wallet_id, token, 0, None));
+            "Token muted, send /unmute <address> to undo."
+        }
+        [MUTE_WALLET, _, seconds] => match mute_until(seconds, now()) {
+            Some(until) => {
+                db.add_mute(Mute::new(user_id, wallet_id, "".to_string(), until, None));
+                "Wallet muted."
+            }
+            None => "Unknown action.",
+        },
+        _ => "Unknown action.",
+    };
+
+    db.drop();
+
+    answer
+}
+
+fn mute_until(seconds: &str, now: i64) -> Option<i64> {
+    let seconds = seconds.parse::<i64>().ok().filter(|s| MUTE_WALLET_SECONDS.contains(s))?;
+
+    now.checked_add(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_callbacks_round_trip_within_the_limit() {
+        let data = page_callback(i64::MAX, i64::MAX, i64::MAX);
+
+        assert!(data.len() <= CALLBACK_DATA_LIMIT);
+        assert_eq!(parse_page_callback(data.as_str()), Some((i64::MAX, i64::MAX, i64::MAX)));
+        assert_eq!(parse_page_callback(page_callback(7, 2, 0).as_str()), Some((7, 2, 0)));
+    }
+
+    #[test]
+    fn malformed_page_callbacks_are_rejected() {
+        assert_eq!(parse_page_callback("tx:1:2"), None);
+        assert_eq!(parse_page_callback("tx:1:two:0"), None);
+        assert_eq!(parse_page_callback("tx:1:2:0:9"), None);
+        assert_eq!(parse_page_callback("mute_wallet:1:3600:0"), None);
+    }
+
+    #[test]
+    fn only_offered_mute_durations_are_accepted() {
+        assert_eq!(mute_until("3600", 1000), Some(4600));
+        assert_eq!(mute_until("-3600", 1000), None);
+        assert_eq!(mute_until("9223372036854775807", 1000), None);
+        assert_eq!(mute_until("60", 1000), None);
+        assert_eq!(mute_until("", 1000), None);
+        assert_eq!(mute_until("3600", i64::MAX), None);
+    }
+}
@@ -1,18 +1,75 @@
-use teloxide::{prelude::*};
+use teloxide::{prelude::*, types::{ChatId, Recipient}};
 use std::error::Error;
-use crate::{Command};
+use crate::{Command, DataRepository, SqliteDb};
 use crate::commands::CommandHandler;
 use crate::commands::{start::StartCommand, add_wallet::AddWalletCommand, remove_wallet::RemoveWalletCommand};
 use crate::commands::get_transaction::GetTransactionCommand;
 use crate::commands::get_wallets::GetWalletsCommand;
+use crate::commands::get_balance::GetBalanceCommand;
+use crate::commands::get_portfolio::GetPortfolioCommand;
+use crate::commands::set_currency::SetCurrencyCommand;
+use crate::commands::alert::AlertCommand;
+use crate::commands::watch_token::WatchTokenCommand;
+use crate::commands::unwatch_token::UnwatchTokenCommand;
+use crate::commands::watch_event::WatchEventCommand;
+use crate::commands::unwatch_event::UnwatchEventCommand;
+use crate::commands::unmute::UnmuteCommand;
+use crate::commands::set_format::SetFormatCommand;
+use crate::commands::export::ExportCommand;
+use crate::commands::tax_report::TaxReportCommand;
+use crate::commands::import::ImportCommand;
+use crate::commands::contact::ContactCommand;
+use crate::commands::route::RouteCommand;
+use crate::commands::set_timezone::SetTimezoneCommand;
+use crate::commands::quiet_hours::QuietHoursCommand;
+use crate::commands::mute::MuteCommand;
+use crate::commands::snooze::SnoozeCommand;
+use crate::commands::set_delivery::SetDeliveryCommand;
+use crate::commands::sink::SinkCommand;
+use crate::commands::email::EmailCommand;
+use crate::commands::announcements::AnnouncementsCommand;
+use crate::commands::broadcast::BroadcastCommand;
+use crate::commands::stats::StatsCommand;
+use crate::commands::users::UsersCommand;
+use crate::commands::ban::BanCommand;
+use crate::commands::quota::QuotaCommand;
+use crate::commands::workers::WorkersCommand;
+use crate::common::{download_reply_document, has_manage_role, is_admin, is_banned, valid_eth_address};
+use crate::models::chat::Chat;
+use crate::ens::resolve_arg;
+use crate::callback_handler::{BANNED, NOT_ALLOWED};
+use crate::sinks::{self, SinkError};
+use crate::throttle;
+
 
 pub async fn handler(bot: AutoSend<Bot>, message: Message, command: Command)
                      -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    if is_banned(message.chat.id) && !is_admin(&message) {
+        throttle::send_message(&bot, message.chat.id, BANNED).await?;
+        return Ok(());
+    }
+
+    let command = match resolve_names(command).await {
+        Ok(c) => c,
+        Err(e) => {
+            throttle::send_message(&bot, message.chat.id, e).await?;
+            return Ok(());
+        }
+    };
+
+    if is_management(&command) && !is_privileged(&bot, &message).await {
+        throttle::send_message(&bot, message.chat.id, NOT_ALLOWED).await?;
+        return Ok(());
+    }
+
     match command {
         Command::Start => {
             let mut start_command = StartCommand::new();
 
-            bot.send_message(message.chat.id, start_command.handle(message)).await?;
+            throttle::send_message(&bot, message.chat.id, start_command.handle(message)).await?;
+        }
+        Command::Help => {
+            throttle::send_message(&bot, message.chat.id, Command::descriptions().to_string()).await?;
         }
         Command::Add { address } => {
             let mut wallet_command = AddWalletCommand {
@@ -20,7 +77,7 @@ pub async fn handler(bot: AutoSend<Bot>, message: Message, command: Command)
                 bot: &bot,
             };
 
-            bot.send_message(message.chat.id, wallet_command.handle(message)).await?;
+            throttle::send_message(&bot, message.chat.id, wallet_command.handle(message)).await?;
         }
         Command::Remove { address } => {
             let mut remove_wallet_command = RemoveWalletCommand {
@@ -28,24 +85,355 @@ pub async fn handler(bot: AutoSend<Bot>, message: Message, command: Command)
                 bot: &bot,
             };
 
-            bot.send_message(message.chat.id, remove_wallet_command.handle(message)).await?;
+            throttle::send_message(&bot, message.chat.id, remove_wallet_command.handle(message)).await?;
         }
-        Command::TxList { address } => {
+        Command::TxList { args } => {
             let mut get_transaction = GetTransactionCommand {
-                address: address.trim().to_string(),
+                args: args.trim().to_string(),
                 bot: &bot,
             };
 
-            bot.send_message(message.chat.id, get_transaction.handle(message)).await?;
+            throttle::send_message(&bot, message.chat.id, get_transaction.handle(message)).await?;
         }
         Command::List => {
             let mut get_wallets = GetWalletsCommand {
                 bot: &bot,
             };
 
-            bot.send_message(message.chat.id, get_wallets.handle(message)).await?;
+            throttle::send_message(&bot, message.chat.id, get_wallets.handle(message)).await?;
+        }
+        Command::Balance { address } => {
+            let mut get_balance = GetBalanceCommand {
+                address: address.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, get_balance.handle(message)).await?;
+        }
+        Command::Portfolio => {
+            let mut get_portfolio = GetPortfolioCommand {
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, get_portfolio.handle(message)).await?;
+        }
+        Command::Currency { currency } => {
+            let mut set_currency = SetCurrencyCommand {
+                currency: currency.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, set_currency.handle(message)).await?;
+        }
+        Command::Alert { args } => {
+            let mut alert = AlertCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, alert.handle(message)).await?;
+        }
+        Command::WatchToken { args } => {
+            let mut watch_token = WatchTokenCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, watch_token.handle(message)).await?;
+        }
+        Command::UnwatchToken { contract } => {
+            let mut unwatch_token = UnwatchTokenCommand {
+                contract: contract.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, unwatch_token.handle(message)).await?;
+        }
+        Command::WatchEvent { args } => {
+            let mut watch_event = WatchEventCommand {
+                args: args.trim().to_string(),
+                abi: download_reply_document(&bot, &message).await,
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, watch_event.handle(message)).await?;
+        }
+        Command::UnwatchEvent { id } => {
+            let mut unwatch_event = UnwatchEventCommand {
+                id: id.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, unwatch_event.handle(message)).await?;
+        }
+        Command::Unmute { address } => {
+            let mut unmute = UnmuteCommand {
+                address: address.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, unmute.handle(message)).await?;
+        }
+        Command::Format { template } => {
+            let mut set_format = SetFormatCommand {
+                template: template.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, set_format.handle(message)).await?;
+        }
+        Command::Export { args } => {
+            let mut export = ExportCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, export.handle(message)).await?;
+        }
+        Command::TaxReport { args } => {
+            let mut tax_report = TaxReportCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, tax_report.handle(message)).await?;
+        }
+        Command::Import { args } => {
+            let mut import = ImportCommand {
+                args: args.trim().to_string(),
+                csv: download_reply_document(&bot, &message).await,
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, import.handle(message)).await?;
+        }
+        Command::Contact { args } => {
+            let mut contact = ContactCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, contact.handle(message)).await?;
+        }
+        Command::Route { args } => {
+            let mut route = RouteCommand {
+                target: route_target(&bot, &message, args.as_str()).await,
+                args: args.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, route.handle(message)).await?;
+        }
+        Command::Timezone { timezone } => {
+            let mut set_timezone = SetTimezoneCommand {
+                timezone: timezone.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, set_timezone.handle(message)).await?;
+        }
+        Command::Quiet { args } => {
+            let mut quiet_hours = QuietHoursCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, quiet_hours.handle(message)).await?;
+        }
+        Command::Mute { args } => {
+            let mut mute = MuteCommand {
+                args: args.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, mute.handle(message)).await?;
+        }
+        Command::Snooze { duration } => {
+            let mut snooze = SnoozeCommand {
+                duration: duration.trim().to_ascii_lowercase(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, snooze.handle(message)).await?;
+        }
+        Command::Delivery { args } => {
+            let mut set_delivery = SetDeliveryCommand {
+                args: args.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, set_delivery.handle(message)).await?;
+        }
+        Command::Sink { args } => {
+            let mut sink = SinkCommand {
+                reachable: sink_url_check(args.as_str()).await,
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, sink.handle(message)).await?;
+        }
+        Command::Email { args } => {
+            let mut email = EmailCommand {
+                args: args.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, email.handle(message)).await?;
+        }
+        Command::Announcements { setting } => {
+            let mut announcements = AnnouncementsCommand {
+                setting: setting.trim().to_ascii_lowercase(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, announcements.handle(message)).await?;
+        }
+        Command::Broadcast { text } => {
+            let mut broadcast = BroadcastCommand {
+                text: text.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, broadcast.handle(message)).await?;
+        }
+        Command::Stats => {
+            let mut stats = StatsCommand {
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, stats.handle(message)).await?;
+        }
+        Command::Users { page } => {
+            let mut users = UsersCommand {
+                page: page.trim().to_string(),
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, users.handle(message)).await?;
+        }
+        Command::Ban { chat } => {
+            let mut ban = BanCommand {
+                chat: chat.trim().to_string(),
+                banned: true,
+            };
+
+            throttle::send_message(&bot, message.chat.id, ban.handle(message)).await?;
+        }
+        Command::Unban { chat } => {
+            let mut unban = BanCommand {
+                chat: chat.trim().to_string(),
+                banned: false,
+            };
+
+            throttle::send_message(&bot, message.chat.id, unban.handle(message)).await?;
+        }
+        Command::Quota { args } => {
+            let mut quota = QuotaCommand {
+                args: args.trim().to_string(),
+            };
+
+            throttle::send_message(&bot, message.chat.id, quota.handle(message)).await?;
+        }
+        Command::Workers => {
+            let mut workers = WorkersCommand {
+                bot: &bot,
+            };
+
+            throttle::send_message(&bot, message.chat.id, workers.handle(message)).await?;
         }
     };
 
     Ok(())
 }
+
+// `/add vitalik.eth` and the like, names are resolved before the commands validate addresses.
+async fn resolve_names(command: Command) -> Result<Command, String> {
+    let mut repo = SqliteDb::get_connection();
+
+    let command = match command {
+        Command::Add { address } => Command::Add { address: resolve_arg(&address, 0, &mut repo).await? },
+        Command::Remove { address } => Command::Remove { address: resolve_arg(&address, 0, &mut repo).await? },
+        Command::TxList { args } => Command::TxList { args: resolve_arg(&args, 0, &mut repo).await? },
+        Command::Balance { address } => Command::Balance { address: resolve_arg(&address, 0, &mut repo).await? },
+        Command::Alert { args } => Command::Alert { args: resolve_arg(&args, 0, &mut repo).await? },
+        Command::Unmute { address } => Command::Unmute { address: resolve_arg(&address, 0, &mut repo).await? },
+        Command::Export { args } => Command::Export { args: resolve_arg(&args, 0, &mut repo).await? },
+        Command::Contact { args } => Command::Contact { args: resolve_arg(&args, 1, &mut repo).await? },
+        Command::Route { args } => Command::Route { args: resolve_arg(&args, 0, &mut repo).await? },
+        Command::Mute { args } => Command::Mute { args: resolve_arg(&args, 0, &mut repo).await? },
+        Command::Delivery { args } => Command::Delivery { args: resolve_arg(&args, 0, &mut repo).await? },
+        c => c,
+    };
+
+    repo.drop();
+
+    Ok(command)
+}
+
+// commands changing what a chat tracks or how it is notified. Everything else, /start and /help
+// included, stays open to every member of a group; admin commands check the sender against
+// --admins themselves.
+fn is_management(command: &Command) -> bool {
+    matches!(command, Command::Add { .. } | Command::Remove { .. } | Command::Currency { .. } | Command::Alert { .. }
+        | Command::WatchToken { .. } | Command::UnwatchToken { .. } | Command::WatchEvent { .. } | Command::UnwatchEvent { .. }
+        | Command::Unmute { .. } | Command::Format { .. } | Command::Import { .. } | Command::Contact { .. }
+        | Command::Route { .. } | Command::Timezone { .. } | Command::Quiet { .. } | Command::Mute { .. }
+        | Command::Snooze { .. } | Command::Delivery { .. } | Command::Sink { .. } | Command::Email { .. }
+        | Command::Announcements { .. })
+}
+
+async fn is_privileged(bot: &AutoSend<Bot>, message: &Message) -> bool {
+    // anonymous group admins post on behalf of the group itself.
+    if message.sender_chat.as_ref().map(|c| c.id == message.chat.id).unwrap_or(false) {
+        return true;
+    }
+
+    match message.from() {
+        Some(user) => has_manage_role(bot, &message.chat, user.id).await,
+        None => false,
+    }
+}
+
+// the requester has to be an admin of the target chat and the bot has to be in it, Ok(None) resets the route.
+// the address is checked first so telegram is only asked about well formed requests.
+async fn route_target(bot: &AutoSend<Bot>, message: &Message, args: &str) -> Result<Option<Chat>, &'static str> {
+    match args.split_whitespace().next() {
+        Some(address) if valid_eth_address(address) => {}
+        Some(_) => return Err("Invalid eth address"),
+        None => return Err("Usage: /route <address> <chat id|@channel>"),
+    }
+
+    let target = match args.split_whitespace().nth(1) {
+        Some("here") | None => return Ok(None),
+        Some(t) => t,
+    };
+
+    let recipient = if target.starts_with('@') {
+        Recipient::ChannelUsername(target.to_string())
+    } else {
+        match target.parse::<i64>() {
+            Ok(id) => Recipient::Id(ChatId(id)),
+            Err(_) => return Err("Usage: /route <address> <chat id|@channel>"),
+        }
+    };
+
+    let chat = bot.get_chat(recipient).await.map_err(|_| "The bot could not find that chat, add it there first.")?;
+    let user = message.from().ok_or(NOT_ALLOWED)?;
+    let me = bot.get_me().await.map_err(|_| "The bot could not find that chat, add it there first.")?;
+
+    match bot.get_chat_member(chat.id, me.id).await {
+        Ok(member) if member.kind.is_present() => {}
+        _ => return Err("The bot could not find that chat, add it there first."),
+    }
+
+    if !chat.is_private() || chat.id.0 != user.id.0 as i64 {
+        match bot.get_chat_member(chat.id, user.id).await {
+            Ok(member) if member.kind.is_privileged() => {}
+            _ => return Err("You have to be an admin of the target chat."),
+        }
+    }
+
+    Ok(Some(Chat::from_telegram(&chat, user.id.0 as i64)))
+}
+
+// sinks have to be https urls of public hosts, resolving the host is async so it is checked here.
+async fn sink_url_check(args: &str) -> Result<(), &'static str> {
+    match args.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        ["add", _, url, ..] => match sinks::ensure_public(url).await {
+            Ok(_) => Ok(()),
+            Err(SinkError::Transient(_)) => Err("The host of the url could not be resolved."),
+            Err(_) => Err("The url has to be https and point to a public host."),
+        },
+        _ => Ok(()),
+    }
+}
@@ -3,6 +3,35 @@ pub mod add_wallet;
 pub mod remove_wallet;
 pub mod get_transaction;
 pub mod get_wallets;
+pub mod get_balance;
+pub mod get_portfolio;
+pub mod set_currency;
+pub mod alert;
+pub mod watch_token;
+pub mod unwatch_token;
+pub mod watch_event;
+pub mod unwatch_event;
+pub mod unmute;
+pub mod set_format;
+pub mod export;
+pub mod tax_report;
+pub mod import;
+pub mod contact;
+pub mod route;
+pub mod set_timezone;
+pub mod quiet_hours;
+pub mod mute;
+pub mod snooze;
+pub mod set_delivery;
+pub mod sink;
+pub mod email;
+pub mod announcements;
+pub mod broadcast;
+pub mod stats;
+pub mod users;
+pub mod ban;
+pub mod quota;
+pub mod workers;
 
 use teloxide::{prelude::*, utils::command::BotCommands};
 use strum_macros::AsRefStr;
@@ -13,13 +42,75 @@ pub enum Command {
     #[command()]
     Start,
     #[command()]
+    Help,
+    #[command()]
     Add { address: String },
     #[command()]
     Remove { address: String },
     #[command()]
-    TxList { address: String },
+    TxList { args: String },
     #[command()]
     List,
+    #[command()]
+    Balance { address: String },
+    #[command()]
+    Portfolio,
+    #[command()]
+    Currency { currency: String },
+    #[command()]
+    Alert { args: String },
+    #[command()]
+    WatchToken { args: String },
+    #[command()]
+    UnwatchToken { contract: String },
+    #[command()]
+    WatchEvent { args: String },
+    #[command()]
+    UnwatchEvent { id: String },
+    #[command()]
+    Unmute { address: String },
+    #[command()]
+    Format { template: String },
+    #[command()]
+    Export { args: String },
+    #[command()]
+    TaxReport { args: String },
+    #[command()]
+    Import { args: String },
+    #[command()]
+    Contact { args: String },
+    #[command()]
+    Route { args: String },
+    #[command()]
+    Timezone { timezone: String },
+    #[command()]
+    Quiet { args: String },
+    #[command()]
+    Mute { args: String },
+    #[command()]
+    Snooze { duration: String },
+    #[command()]
+    Delivery { args: String },
+    #[command()]
+    Sink { args: String },
+    #[command()]
+    Email { args: String },
+    #[command()]
+    Announcements { setting: String },
+    #[command()]
+    Broadcast { text: String },
+    #[command()]
+    Stats,
+    #[command()]
+    Users { page: String },
+    #[command()]
+    Ban { chat: String },
+    #[command()]
+    Unban { chat: String },
+    #[command()]
+    Quota { args: String },
+    #[command()]
+    Workers,
 }
 
 pub trait CommandHandler {
@@ -1,7 +1,7 @@
 use tokio::{task};
 use crate::commands::CommandHandler;
 use crate::{DataRepository, Message, SqliteDb};
-use crate::common::{background_wallet_worker, valid_eth_address};
+use crate::common::{background_wallet_worker, to_checksum_address, valid_eth_address};
 use teloxide::{prelude::*};
 
 pub struct AddWalletCommand<'a> {
@@ -15,6 +15,8 @@ impl CommandHandler for AddWalletCommand<'_> {
             return "Invalid eth address";
         }
 
+        self.address = to_checksum_address(self.address.as_str());
+
         let mut db = SqliteDb::get_connection();
 
         let user = db.get_user(message.chat.id.0);
@@ -23,7 +25,8 @@ impl CommandHandler for AddWalletCommand<'_> {
             return "Please send /start command.";
         }
 
-        let user_id = user.unwrap().id.unwrap();
+        let user = user.unwrap();
+        let user_id = user.id.unwrap();
 
         let wallet = db.get_wallet(Some(user_id), self.address.to_string());
 
@@ -31,6 +34,10 @@ impl CommandHandler for AddWalletCommand<'_> {
             return "This wallet address is currently being tracked.";
         }
 
+        if !user.within_quota(db.get_user_wallets(user_id).len() + 1) {
+            return "You have reached your wallet quota.";
+        }
+
         db.add_wallet(user_id, self.address.to_string());
         db.drop();
 
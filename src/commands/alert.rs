@@ -0,0 +1,85 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{to_checksum_address, valid_eth_address};
+use crate::models::alert::Alert;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /alert <address> <token> below|above <amount>, /alert list, /alert remove <id>
+pub struct AlertCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for AlertCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        match args.as_slice() {
+            [] | ["list"] => {
+                let alerts = db.get_user_alerts(user_id);
+                db.drop();
+
+                if alerts.is_empty() {
+                    return "You have no alerts.";
+                }
+
+                let bot = self.bot.clone();
+                let text = alerts.iter().map(|a| a.to_string()).collect::<Vec<String>>().join("\n");
+
+                task::spawn(async move {
+                    throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+                });
+
+                "Here is your alerts:"
+            }
+            ["remove", id] => {
+                let id = match id.trim_start_matches('#').parse::<i64>() {
+                    Ok(id) => id,
+                    Err(_) => return "Invalid alert id.",
+                };
+
+                if !db.get_user_alerts(user_id).iter().any(|a| a.id == Some(id)) {
+                    return "This alert does not belong to you.";
+                }
+
+                db.remove_alert(user_id, id);
+                db.drop();
+
+                "The alert removed."
+            }
+            [address, token, direction, amount] => {
+                if !valid_eth_address(address) {
+                    return "Invalid eth address";
+                }
+
+                let direction = direction.to_ascii_lowercase();
+
+                if direction != Alert::BELOW && direction != Alert::ABOVE {
+                    return "Direction should be below or above.";
+                }
+
+                let threshold = match amount.replace(',', "").parse::<f64>() {
+                    Ok(v) if v >= 0f64 => v,
+                    _ => return "Invalid amount.",
+                };
+
+                db.add_alert(Alert::new(user_id, to_checksum_address(address), token.to_string(), direction, threshold, None));
+                db.drop();
+
+                "The alert added."
+            }
+            _ => "Usage: /alert <address> <token> below|above <amount>",
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+
+// /announcements on|off, covers release notes and /broadcast messages.
+pub struct AnnouncementsCommand {
+    pub setting: String,
+}
+
+impl CommandHandler for AnnouncementsCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let enabled = match self.setting.as_str() {
+            "on" => true,
+            "off" => false,
+            _ => return "Usage: /announcements on|off",
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        db.set_user_announcements(user.unwrap().id.unwrap(), enabled);
+        db.drop();
+
+        if enabled { "Announcements turned on." } else { "Announcements turned off." }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::is_admin;
+
+// /ban <chat> and /unban <chat>, admins only. Wallets of a banned user stay but are not polled.
+pub struct BanCommand {
+    pub chat: String,
+    pub banned: bool,
+}
+
+impl CommandHandler for BanCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !is_admin(&message) {
+            return "This command is only available to admins.";
+        }
+
+        let chat_id = match self.chat.parse::<i64>() {
+            Ok(id) => id,
+            Err(_) => return if self.banned { "Usage: /ban <chat id>" } else { "Usage: /unban <chat id>" },
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = match db.get_user(chat_id) {
+            Some(u) => u,
+            None => return "There is no user with this chat id.",
+        };
+
+        db.set_user_banned(user.id.unwrap(), self.banned);
+        db.drop();
+
+        if self.banned { "The user is banned." } else { "The user is unbanned." }
+    }
+}
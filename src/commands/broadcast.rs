@@ -0,0 +1,39 @@
+use structopt::StructOpt;
+use teloxide::types::ChatId;
+use crate::commands::CommandHandler;
+use crate::{AppConfig, DataRepository, Message, SqliteDb};
+use crate::common::is_admin;
+use crate::renderer::{Markup, Notification};
+use crate::sinks::telegram;
+
+// /broadcast <text>, admins only. Goes through the outbox to every user that did not turn announcements off.
+pub struct BroadcastCommand {
+    pub text: String,
+}
+
+impl CommandHandler for BroadcastCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !is_admin(&message) {
+            return "This command is only available to admins.";
+        }
+
+        if self.text.is_empty() {
+            return "Usage: /broadcast <text>";
+        }
+
+        let markup = Markup::from_config(&AppConfig::from_args());
+        let notification = Notification::plain(self.text.as_str(), markup);
+
+        let mut db = SqliteDb::get_connection();
+
+        for user in db.get_all_user().into_iter().filter(|u| u.announcements) {
+            if let Ok(chat_id) = user.chat_id.parse::<i64>() {
+                telegram::queue_notification(&mut db, ChatId(chat_id), &notification, "");
+            }
+        }
+
+        db.drop();
+
+        "The broadcast is queued."
+    }
+}
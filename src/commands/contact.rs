@@ -0,0 +1,75 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{to_checksum_address, valid_eth_address};
+use crate::models::contact::Contact;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /contact add <address> <name>, /contact remove <address>, /contact list
+pub struct ContactCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for ContactCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        match args.as_slice() {
+            [] | ["list"] => {
+                let contacts = db.get_user_contacts(user_id);
+                db.drop();
+
+                if contacts.is_empty() {
+                    return "Your address book is empty.";
+                }
+
+                let bot = self.bot.clone();
+                let text = contacts.iter().map(|c| c.to_string()).collect::<Vec<String>>().join("\n");
+
+                task::spawn(async move {
+                    throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+                });
+
+                "Here is your address book:"
+            }
+            ["remove", address] => {
+                if !valid_eth_address(address) {
+                    return "Invalid eth address";
+                }
+
+                db.remove_contact(user_id, address.to_string());
+                db.drop();
+
+                "The contact removed."
+            }
+            ["add", address, name @ ..] if !name.is_empty() => {
+                if !valid_eth_address(address) {
+                    return "Invalid eth address";
+                }
+
+                let name = name.join(" ");
+
+                if name.chars().count() > 32 {
+                    return "Contact name should be at most 32 characters.";
+                }
+
+                db.add_contact(Contact::new(user_id, to_checksum_address(address), name, None));
+                db.drop();
+
+                "The contact saved."
+            }
+            _ => "Usage: /contact add <address> <name>, /contact remove <address>, /contact list",
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use rand::{rngs::OsRng, Rng};
+use structopt::StructOpt;
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{AppConfig, DataRepository, Message, SqliteDb};
+use crate::common::now;
+use crate::models::email_address::EmailAddress;
+use crate::sinks::email::{send_email, SmtpSettings};
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /email <address>, /email verify <code>, /email off
+pub struct EmailCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for EmailCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        match args.as_slice() {
+            [] => {
+                let email = db.get_user_email(user_id);
+                db.drop();
+
+                let email = match email {
+                    Some(e) => e,
+                    None => return "You have no email set, use /email <address>.",
+                };
+
+                let bot = self.bot.clone();
+                let text = format!("{} ({})", email.address, if email.verified { "verified" } else { "not verified" });
+
+                task::spawn(async move {
+                    throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+                });
+
+                "Large-transfer alerts are emailed to:"
+            }
+            ["off"] => {
+                db.remove_user_email(user_id);
+                db.drop();
+
+                "Email alerts turned off."
+            }
+            ["verify", code] => {
+                let mut email = match db.get_user_email(user_id) {
+                    Some(e) if e.can_verify(now()) => e,
+                    Some(e) if !e.verified && e.attempts >= EmailAddress::MAX_ATTEMPTS => return TOO_MANY_ATTEMPTS,
+                    _ => return "There is no pending verification, send /email <address> first.",
+                };
+
+                if email.code != *code {
+                    email.attempts += 1;
+                    db.set_user_email(email);
+                    db.drop();
+
+                    return "Invalid verification code.";
+                }
+
+                email.verified = true;
+                email.code = "".to_string();
+                db.set_user_email(email);
+                db.drop();
+
+                "Your email is verified, large-transfer alerts will be emailed as well."
+            }
+            [address] => {
+                if address.parse::<lettre::Address>().is_err() {
+                    return "Invalid email address.";
+                }
+
+                let settings = match SmtpSettings::from_config(&AppConfig::from_args()) {
+                    Some(s) => s,
+                    None => return "Email is not configured on this bot.",
+                };
+
+                let attempts = db.get_user_email(user_id).map(|e| e.carried_attempts(now())).unwrap_or(0);
+                if attempts >= EmailAddress::MAX_ATTEMPTS {
+                    return TOO_MANY_ATTEMPTS;
+                }
+
+                let (by_chat, to_address) = db.count_email_sends(message.chat.id.0, address.to_string(), now() - EmailAddress::SEND_WINDOW);
+                if by_chat >= EmailAddress::MAX_SENDS_PER_CHAT || to_address >= EmailAddress::MAX_SENDS_PER_ADDRESS {
+                    return "Too many verification emails requested, try again tomorrow.";
+                }
+
+                let code = verification_code();
+
+                db.set_user_email(EmailAddress::new(user_id, address.to_string(), false, code.clone(),
+                                                    now() + EmailAddress::CODE_TTL, attempts, None));
+                db.add_email_send(message.chat.id.0, address.to_string(), now());
+                db.drop();
+
+                let bot = self.bot.clone();
+                let address = address.to_string();
+
+                task::spawn(async move {
+                    let text = format!("Your verification code is {}. It expires in 15 minutes.\n", code);
+                    let html = format!("<html><body><p>Your verification code is <b>{}</b>. It expires in 15 minutes.</p></body></html>", code);
+
+                    if let Err(e) = send_email(&settings, address.as_str(), "Verify your email", text, html).await {
+                        throttle::send_message(&bot, message.chat.id, format!("Could not send the verification email: {}", e)).await.unwrap();
+                    }
+                });
+
+                "A verification code is on its way, confirm it with /email verify <code>."
+            }
+            _ => "Usage: /email <address>, /email verify <code>, /email off",
+        }
+    }
+}
+
+const TOO_MANY_ATTEMPTS: &str = "Too many invalid codes, try again tomorrow.";
+
+// 6 digits from the os random generator.
+fn verification_code() -> String {
+    format!("{:06}", OsRng.gen_range(0..1_000_000))
+}
@@ -0,0 +1,83 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{parse_date, valid_eth_address};
+use crate::export::{export_rows, serialize, ExportFormat};
+use crate::models::transaction::TransactionFilter;
+use crate::throttle;
+use teloxide::{prelude::*, types::InputFile};
+
+// /export <address|all> [csv|json] [from YYYY-MM-DD] [to YYYY-MM-DD]
+pub struct ExportCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for ExportCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        let target = match args.first() {
+            Some(t) => t.to_string(),
+            None => return "Usage: /export <address|all> [csv|json] [from] [to]",
+        };
+
+        let mut format = ExportFormat::Csv;
+        let mut filter = TransactionFilter::default();
+
+        for arg in args.iter().skip(1) {
+            if let Some(f) = ExportFormat::parse(arg) {
+                format = f;
+            } else if let Some(date) = parse_date(arg) {
+                if filter.from.is_none() {
+                    filter.from = Some(date);
+                } else {
+                    filter.to = Some(date + 86399);
+                }
+            } else {
+                return "Usage: /export <address|all> [csv|json] [from] [to]";
+            }
+        }
+
+        if target != "all" && !valid_eth_address(target.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let wallets = if target == "all" {
+            db.get_user_wallets(user_id)
+        } else {
+            match db.get_wallet(Some(user_id), target.to_string()) {
+                Some(w) => vec![w],
+                None => return "This wallet address is not tracked by you.",
+            }
+        };
+
+        let rows = export_rows(&db, &wallets, &filter);
+        db.drop();
+
+        if rows.is_empty() {
+            return "There is no transaction to export.";
+        }
+
+        let file_name = format!("transactions-{}.{}", target, format.extension());
+        let content = serialize(&rows, format);
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            throttle::acquire(message.chat.id).await;
+            bot.send_document(message.chat.id, InputFile::memory(content).file_name(file_name)).await.unwrap();
+        });
+
+        "Preparing your export..."
+    }
+}
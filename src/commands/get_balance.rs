@@ -0,0 +1,47 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{get_wallet_balances, valid_eth_address};
+use crate::throttle;
+use teloxide::{prelude::*};
+
+pub struct GetBalanceCommand<'a> {
+    pub address: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for GetBalanceCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !valid_eth_address(self.address.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+        db.drop();
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let address = self.address.clone();
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            let mut repo = SqliteDb::get_connection();
+            let balances = get_wallet_balances::<SqliteDb>(address.as_str(), &mut repo).await;
+
+            let text = if balances.is_empty() {
+                format!("Could not fetch balances of {}, try again later.", address)
+            } else {
+                let lines = balances.iter().map(|b| b.to_string()).collect::<Vec<String>>();
+                format!("Balance of {}:\n{}", address, lines.join("\n"))
+            };
+
+            throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+        });
+
+        "Fetching balances..."
+    }
+}
@@ -0,0 +1,84 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::get_wallet_balances;
+use crate::models::balance::Balance;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// sums balances of the same token (by contract, ETH has none) into total.
+fn add_balances(total: &mut Vec<Balance>, balances: Vec<Balance>) {
+    for balance in balances {
+        match total.iter_mut().find(|t| t.contract.eq_ignore_ascii_case(&balance.contract)) {
+            Some(t) => {
+                let sum = t.amount.parse::<u128>().unwrap_or(0) + balance.amount.parse::<u128>().unwrap_or(0);
+                t.amount = sum.to_string();
+            }
+            None => total.push(balance),
+        }
+    }
+}
+
+pub struct GetPortfolioCommand<'a> {
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for GetPortfolioCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let wallets = db.get_user_wallets(user_id);
+        db.drop();
+
+        if wallets.is_empty() {
+            return "You are not tracking any wallet.";
+        }
+
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            let mut repo = SqliteDb::get_connection();
+            let mut total: Vec<Balance> = vec![];
+
+            for wallet in wallets.iter() {
+                add_balances(&mut total, get_wallet_balances::<SqliteDb>(wallet.address.as_str(), &mut repo).await);
+            }
+
+            let lines = total.iter().map(|b| b.to_string()).collect::<Vec<String>>();
+            let text = format!("Portfolio of {} wallet(s):\n{}", wallets.len(), lines.join("\n"));
+
+            throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+        });
+
+        "Fetching portfolio..."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(contract: &str, token: &str, amount: &str) -> Balance {
+        Balance::new("0x1".to_string(), contract.to_string(), token.to_string(), 18, amount.to_string(), 0, None)
+    }
+
+    #[test]
+    fn same_tokens_are_summed_across_wallets() {
+        let mut total = vec![];
+        add_balances(&mut total, vec![balance("", "ETH", "1500000000000000000"), balance("0xAbC", "USDC", "5")]);
+        add_balances(&mut total, vec![balance("", "ETH", "500000000000000000"), balance("0xabc", "USDC", "7"),
+                                      balance("0xdef", "DAI", "1")]);
+
+        let lines = total.iter().map(|b| (b.token.as_str(), b.amount.as_str())).collect::<Vec<(&str, &str)>>();
+        assert_eq!(lines, vec![("ETH", "2000000000000000000"), ("USDC", "12"), ("DAI", "1")]);
+        assert_eq!(total[0].to_string(), "2 ETH");
+    }
+}
@@ -1,20 +1,36 @@
 use crate::commands::CommandHandler;
-use crate::{DataRepository, Message, SqliteDb};
-use crate::common::{valid_eth_address};
+use crate::{AppConfig, DataRepository, Message, SqliteDb};
+use crate::common::{now, send_notification, valid_eth_address};
+use crate::models::transaction::TransactionFilter;
+use crate::renderer::{Markup, render_transaction_page};
+use structopt::StructOpt;
 use teloxide::{prelude::*};
 use tokio::{task};
 
+// /txlist <address> [page <n>] [token <name>] [in|out] [from <YYYY-MM-DD>] [to <YYYY-MM-DD>]
 pub struct GetTransactionCommand<'a> {
-    pub address: String,
+    pub args: String,
     pub bot: &'a AutoSend<Bot>,
 }
 
 impl CommandHandler for GetTransactionCommand<'_> {
     fn handle<'a>(&mut self, message: Message) -> &'a str {
-        if !valid_eth_address(self.address.as_str()) {
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        let address = match args.first() {
+            Some(a) => a.to_string(),
+            None => return "Usage: /txlist <address> [page <n>] [token <name>] [in|out] [from <YYYY-MM-DD>] [to <YYYY-MM-DD>]",
+        };
+
+        if !valid_eth_address(address.as_str()) {
             return "Invalid eth address";
         }
 
+        let (filter, page) = match TransactionFilter::parse(&args[1..]) {
+            Ok(f) => f,
+            Err(_) => return "Usage: /txlist <address> [page <n>] [token <name>] [in|out] [from <YYYY-MM-DD>] [to <YYYY-MM-DD>]",
+        };
+
         let mut db = SqliteDb::get_connection();
 
         let user = db.get_user(message.chat.id.0);
@@ -25,22 +41,34 @@ impl CommandHandler for GetTransactionCommand<'_> {
 
         let user_id = user.unwrap().id.unwrap();
 
-        let wallet = db.get_wallet(Some(user_id), self.address.to_string());
+        let wallet = db.get_wallet(Some(user_id), address.to_string());
 
         if wallet.is_none() {
             return "This wallet address is not tracked by you.";
         }
 
-        let txs = db.get_all_transactions(wallet.unwrap().id.unwrap());
+        let wallet = wallet.unwrap();
+        let wallet_id = wallet.id.unwrap();
+
+        let total = db.count_transactions(wallet_id, wallet.address.clone(), &filter);
+        let txs = db.get_transactions(wallet_id, wallet.address.clone(), &filter, TransactionFilter::PAGE_SIZE,
+                                      (page - 1) * TransactionFilter::PAGE_SIZE);
+
+        // prev/next buttons refer to the filter by id.
+        let filter_id = if filter == TransactionFilter::default() {
+            0
+        } else {
+            db.add_page_filter(user_id, &filter, now()).unwrap_or(0)
+        };
         db.drop();
 
+        let notification = render_transaction_page(&txs, &wallet, filter_id, page, total, Markup::from_config(&AppConfig::from_args()));
         let bot = self.bot.clone();
+
         task::spawn(async move {
-            for tx in txs {
-                bot.send_message(message.chat.id, tx.to_string().as_str()).await.unwrap();
-            }
+            send_notification(&bot, message.chat.id, &notification).await.unwrap();
         });
 
         "Here is transactions for your address:"
     }
-}
\ No newline at end of file
+}
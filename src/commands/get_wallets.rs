@@ -1,6 +1,7 @@
 use tokio::task;
 use crate::commands::CommandHandler;
 use crate::{DataRepository, Message, SqliteDb};
+use crate::throttle;
 use teloxide::{prelude::*};
 
 pub struct GetWalletsCommand<'a> {
@@ -26,7 +27,13 @@ impl CommandHandler for GetWalletsCommand<'_> {
 
         task::spawn(async move {
             for wallet in wallets {
-                bot.send_message(message.chat.id, format!("https://etherscan.io/address/{wallet}", wallet = wallet.address)).await.unwrap();
+                let text = if wallet.label.is_empty() {
+                    format!("https://etherscan.io/address/{wallet}", wallet = wallet.address)
+                } else {
+                    format!("{label}: https://etherscan.io/address/{wallet}", label = wallet.label, wallet = wallet.address)
+                };
+
+                throttle::send_message(&bot, message.chat.id, text).await.unwrap();
             }
         });
 
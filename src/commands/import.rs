@@ -0,0 +1,210 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{background_wallet_worker, to_checksum_address, valid_eth_address};
+use crate::models::user::User;
+use crate::throttle;
+use teloxide::prelude::*;
+
+const MAX_ROWS: usize = 500;
+const CHAINS: [&str; 4] = ["", "eth", "ethereum", "mainnet"];
+
+// numbered non-empty lines, without a header row.
+fn rows(content: &str) -> Vec<(usize, &str)> {
+    content.lines()
+        .map(|l| l.trim())
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty() && !l.to_ascii_lowercase().starts_with("address"))
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct Import {
+    // checksummed address and label.
+    added: Vec<(String, String)>,
+    duplicates: Vec<String>,
+    rejected: Vec<String>,
+}
+
+// sorts numbered lines into wallets to add, already tracked ones and rejected lines. tracked are the
+// addresses the user has now, the quota counts them plus everything added before the line.
+fn classify(lines: &[(usize, &str)], user: &User, tracked: &[String]) -> Import {
+    let mut import = Import::default();
+
+    for (index, line) in lines {
+        let fields = if line.contains(',') {
+            line.split(',').map(|f| f.trim().trim_matches('"')).collect::<Vec<&str>>()
+        } else {
+            line.splitn(2, char::is_whitespace).map(|f| f.trim()).collect::<Vec<&str>>()
+        };
+
+        let address = fields[0].to_string();
+        let label = fields.get(1).map(|l| l.to_string()).unwrap_or_default();
+        let chain = fields.get(2).map(|c| c.to_ascii_lowercase()).unwrap_or_default();
+
+        if !valid_eth_address(address.as_str()) {
+            import.rejected.push(format!("line {}: invalid eth address {}", index + 1, address));
+        } else if !CHAINS.contains(&chain.as_str()) {
+            import.rejected.push(format!("line {}: unsupported chain {}", index + 1, chain));
+        } else if import.added.iter().any(|(a, _)| a.eq_ignore_ascii_case(&address))
+            || tracked.iter().any(|a| a.eq_ignore_ascii_case(&address)) {
+            import.duplicates.push(address);
+        } else if !user.within_quota(tracked.len() + import.added.len() + 1) {
+            import.rejected.push(format!("line {}: wallet quota of {} reached", index + 1, user.wallet_quota));
+        } else {
+            import.added.push((to_checksum_address(address.as_str()), label));
+        }
+    }
+
+    import
+}
+
+// /import with one `address[,label[,chain]]` per line, or replying to a csv document with the same columns.
+pub struct ImportCommand<'a> {
+    pub args: String,
+    pub csv: Option<String>,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for ImportCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let content = match &self.csv {
+            Some(c) => c.clone(),
+            None => self.args.clone(),
+        };
+
+        let lines = rows(content.as_str());
+
+        if lines.is_empty() {
+            return "Usage: /import followed by one address[,label[,chain]] per line, or reply to a csv document.";
+        }
+
+        if lines.len() > MAX_ROWS {
+            return "Too many rows, at most 500 wallets can be imported at once.";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user = user.unwrap();
+        let user_id = user.id.unwrap();
+        let tracked = db.get_user_wallets(user_id).into_iter().map(|w| w.address).collect::<Vec<String>>();
+
+        let Import { added, duplicates, rejected } = classify(&lines, &user, &tracked);
+
+        if !added.is_empty() {
+            db.add_wallets(user_id, &added);
+        }
+
+        db.drop();
+
+        let mut report = vec![format!("Added {}, duplicates {}, rejected {}.", added.len(), duplicates.len(), rejected.len())];
+
+        if !added.is_empty() {
+            report.push(format!("\nAdded:\n{}", added.iter()
+                .map(|(a, l)| if l.is_empty() { a.clone() } else { format!("{} ({})", a, l) })
+                .collect::<Vec<String>>().join("\n")));
+        }
+
+        if !duplicates.is_empty() {
+            report.push(format!("\nAlready tracked:\n{}", duplicates.join("\n")));
+        }
+
+        if !rejected.is_empty() {
+            report.push(format!("\nRejected:\n{}", rejected.join("\n")));
+        }
+
+        let bot = self.bot.clone();
+        let chat_id = message.chat.id;
+
+        task::spawn(async move {
+            throttle::send_message(&bot, chat_id, report.join("\n")).await.unwrap();
+
+            for (address, _) in added {
+                let bot = bot.clone();
+
+                task::spawn(async move {
+                    let mut repo = SqliteDb::get_connection();
+                    background_wallet_worker::<SqliteDb>(&bot, chat_id, address, user_id, &mut repo).await;
+                });
+            }
+        });
+
+        "Importing wallets..."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // eip-55 reference addresses, already in checksum form.
+    const A: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    const B: &str = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+    const C: &str = "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB";
+
+    fn user(quota: i64) -> User {
+        let mut user = User::new("1".to_string(), Some(1));
+        user.wallet_quota = quota;
+        user
+    }
+
+    #[test]
+    fn header_rows_are_skipped_in_any_case() {
+        let content = format!("Address,Label,Chain\n\n{}\nADDRESS LABEL\n  {}  ", A, B);
+
+        assert_eq!(rows(content.as_str()), vec![(2, A), (4, B)]);
+    }
+
+    #[test]
+    fn invalid_addresses_and_unsupported_chains_are_rejected() {
+        let row = format!("{},bridge,polygon", B);
+        let lines = vec![(0, "0x123"), (1, row.as_str()), (2, A)];
+        let import = classify(&lines, &user(0), &[]);
+
+        assert_eq!(import.added, vec![(A.to_string(), "".to_string())]);
+        assert_eq!(import.rejected, vec!["line 1: invalid eth address 0x123", "line 2: unsupported chain polygon"]);
+    }
+
+    #[test]
+    fn whitespace_separates_the_label() {
+        let row = format!("{}   my cold wallet", A);
+        let import = classify(&[(0, row.as_str())], &user(0), &[]);
+
+        assert_eq!(import.added, vec![(A.to_string(), "my cold wallet".to_string())]);
+    }
+
+    #[test]
+    fn repeated_rows_and_tracked_wallets_are_duplicates() {
+        let row = format!("\"{}\", \"cold storage\", ETH", A);
+        let lines = vec![(0, row.as_str()), (1, A), (2, B)];
+        let import = classify(&lines, &user(0), &[B.to_string()]);
+
+        assert_eq!(import.added, vec![(A.to_string(), "cold storage".to_string())]);
+        assert_eq!(import.duplicates, vec![A.to_string(), B.to_string()]);
+    }
+
+    #[test]
+    fn quota_counts_tracked_and_added_wallets() {
+        let lines = vec![(0, A), (1, B), (2, C)];
+        let import = classify(&lines, &user(2), &[C.to_string()]);
+
+        assert_eq!(import.added, vec![(A.to_string(), "".to_string())]);
+        assert_eq!(import.rejected, vec!["line 2: wallet quota of 2 reached"]);
+        assert_eq!(import.duplicates, vec![C.to_string()]);
+    }
+
+    #[test]
+    fn zero_quota_imports_everything() {
+        let lines = vec![(0, A), (1, B), (2, C)];
+        let import = classify(&lines, &user(0), &[]);
+
+        assert_eq!(import.added.len(), 3);
+        assert!(import.rejected.is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{now, parse_duration, valid_eth_address};
+use crate::models::mute::Mute;
+
+// /mute <address> <duration|forever>, notifications of the wallet are dropped until then.
+pub struct MuteCommand {
+    pub args: String,
+}
+
+impl CommandHandler for MuteCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        let (address, duration) = match args.as_slice() {
+            [address, duration] => (address.to_string(), duration.to_string()),
+            _ => return "Usage: /mute <address> <duration>, e.g. /mute 0x... 2h",
+        };
+
+        if !valid_eth_address(address.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let until = if duration == "forever" {
+            0
+        } else {
+            match parse_duration(duration.as_str()).and_then(|d| now().checked_add(d)) {
+                Some(until) => until,
+                None => return "Invalid duration, use something like 30m, 2h, 1d or forever.",
+            }
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let wallet = match db.get_wallet(Some(user_id), address) {
+            Some(w) => w,
+            None => return "This wallet address is not tracked by you.",
+        };
+
+        db.add_mute(Mute::new(user_id, wallet.id.unwrap(), "".to_string(), until, None));
+        db.drop();
+
+        "The wallet muted, use /unmute to undo."
+    }
+}
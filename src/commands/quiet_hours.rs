@@ -0,0 +1,71 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{format_clock, parse_clock};
+use crate::models::user::User;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /quiet 23:00-07:00 [silent|hold], /quiet off, /quiet shows the current setting. times are in the user's timezone.
+pub struct QuietHoursCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for QuietHoursCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user = user.unwrap();
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        match args.as_slice() {
+            [] => {
+                db.drop();
+
+                if user.quiet_start < 0 {
+                    return "Quiet hours are off.";
+                }
+
+                let bot = self.bot.clone();
+                let text = format!("Quiet hours: {}-{} ({})", format_clock(user.quiet_start), format_clock(user.quiet_end), user.quiet_mode);
+
+                task::spawn(async move {
+                    throttle::send_message(&bot, message.chat.id, text).await.unwrap();
+                });
+
+                "Your quiet hours:"
+            }
+            ["off"] => {
+                db.set_user_quiet_hours(user.id.unwrap(), -1, -1, user.quiet_mode);
+                db.drop();
+
+                "Quiet hours turned off."
+            }
+            [range, mode @ ..] if mode.len() <= 1 => {
+                let (start, end) = match range.split_once('-').map(|(s, e)| (parse_clock(s), parse_clock(e))) {
+                    Some((Some(s), Some(e))) if s != e => (s, e),
+                    _ => return "Usage: /quiet <HH:MM-HH:MM> [silent|hold] or /quiet off",
+                };
+
+                let mode = mode.first().map(|m| m.to_ascii_lowercase()).unwrap_or(User::SILENT.to_string());
+
+                if mode != User::SILENT && mode != User::HOLD {
+                    return "Mode should be silent or hold.";
+                }
+
+                db.set_user_quiet_hours(user.id.unwrap(), start, end, mode);
+                db.drop();
+
+                "Quiet hours updated."
+            }
+            _ => "Usage: /quiet <HH:MM-HH:MM> [silent|hold] or /quiet off",
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::is_admin;
+
+// /quota <chat> <n>, admins only. Zero removes the cap, wallets above a lowered cap are kept.
+pub struct QuotaCommand {
+    pub args: String,
+}
+
+impl CommandHandler for QuotaCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !is_admin(&message) {
+            return "This command is only available to admins.";
+        }
+
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        let (chat_id, quota) = match args.as_slice() {
+            [chat, quota] => match (chat.parse::<i64>(), quota.parse::<i64>()) {
+                (Ok(c), Ok(q)) if q >= 0 => (c, q),
+                _ => return "Usage: /quota <chat id> <wallets>",
+            },
+            _ => return "Usage: /quota <chat id> <wallets>",
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = match db.get_user(chat_id) {
+            Some(u) => u,
+            None => return "There is no user with this chat id.",
+        };
+
+        db.set_user_wallet_quota(user.id.unwrap(), quota);
+        db.drop();
+
+        if quota == 0 { "The wallet quota is removed." } else { "The wallet quota is set." }
+    }
+}
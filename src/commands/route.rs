@@ -0,0 +1,60 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::valid_eth_address;
+use crate::models::chat::Chat;
+
+// /route <address> <chat id|@channel> sends the wallet notifications to another chat, /route <address> brings them back.
+// the target chat is looked up and checked in the command handler since that needs the telegram api.
+pub struct RouteCommand {
+    pub args: String,
+    pub target: Result<Option<Chat>, &'static str>,
+}
+
+impl CommandHandler for RouteCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let address = match self.args.split_whitespace().next() {
+            Some(a) => a.to_string(),
+            None => return "Usage: /route <address> <chat id|@channel>",
+        };
+
+        if !valid_eth_address(address.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let target = match &self.target {
+            Ok(t) => t,
+            Err(e) => return *e,
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let wallet = match db.get_wallet(Some(user_id), address) {
+            Some(w) => w,
+            None => return "This wallet address is not tracked by you.",
+        };
+
+        match target {
+            Some(chat) => {
+                db.set_chat(chat.clone());
+                db.set_wallet_route(wallet.id.unwrap(), chat.chat_id);
+                db.drop();
+
+                "The wallet notifications are routed."
+            }
+            None => {
+                db.set_wallet_route(wallet.id.unwrap(), 0);
+                db.drop();
+
+                "The wallet notifications come to this chat again."
+            }
+        }
+    }
+}
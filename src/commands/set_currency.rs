@@ -0,0 +1,29 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+
+pub struct SetCurrencyCommand {
+    pub currency: String,
+}
+
+impl CommandHandler for SetCurrencyCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let currency = self.currency.to_ascii_uppercase();
+
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return "Invalid currency, send a code like USD or EUR.";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        db.set_user_currency(user.unwrap().id.unwrap(), currency);
+        db.drop();
+
+        "Fiat currency updated."
+    }
+}
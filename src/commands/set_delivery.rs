@@ -0,0 +1,60 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{parse_clock, valid_eth_address};
+use crate::models::wallet::Wallet;
+
+// /delivery <address> instant|hourly|daily [HH:MM], the time sets when daily digests are sent in the user's timezone.
+pub struct SetDeliveryCommand {
+    pub args: String,
+}
+
+impl CommandHandler for SetDeliveryCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        let (address, mode, time) = match args.as_slice() {
+            [address, mode] => (address.to_string(), mode.to_ascii_lowercase(), None),
+            [address, mode, time] => (address.to_string(), mode.to_ascii_lowercase(), Some(time.to_string())),
+            _ => return "Usage: /delivery <address> instant|hourly|daily [HH:MM]",
+        };
+
+        if !valid_eth_address(address.as_str()) {
+            return "Invalid eth address";
+        }
+
+        if mode != Wallet::INSTANT && mode != Wallet::HOURLY && mode != Wallet::DAILY {
+            return "Delivery should be instant, hourly or daily.";
+        }
+
+        let digest_time = match time.map(|t| parse_clock(t.as_str())) {
+            Some(Some(t)) if mode == Wallet::DAILY => Some(t),
+            Some(_) => return "Usage: /delivery <address> daily HH:MM",
+            None => None,
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let wallet = match db.get_wallet(Some(user_id), address) {
+            Some(w) => w,
+            None => return "This wallet address is not tracked by you.",
+        };
+
+        db.set_wallet_delivery(wallet.id.unwrap(), mode);
+
+        if let Some(t) = digest_time {
+            db.set_user_digest_time(user_id, t);
+        }
+
+        db.drop();
+
+        "Delivery mode updated."
+    }
+}
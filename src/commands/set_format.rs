@@ -0,0 +1,67 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::templates::{self, PRESETS, VARIABLES};
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /format <preset> or /format <custom template>, without arguments it shows the presets and variables.
+pub struct SetFormatCommand<'a> {
+    pub template: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for SetFormatCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        if self.template.is_empty() {
+            db.drop();
+
+            let presets = PRESETS.iter()
+                .map(|(n, t)| if t.is_empty() { format!("{}: built-in layout", n) } else { format!("{}: {}", n, t) })
+                .collect::<Vec<String>>();
+            let variables = VARIABLES.iter().map(|v| format!("{{{}}}", v)).collect::<Vec<String>>();
+
+            let text = format!("Presets:\n{}\n\nVariables:\n{}", presets.join("\n"), variables.join(" "));
+            let bot = self.bot.clone();
+
+            task::spawn(async move {
+                throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+            });
+
+            return "Send /format <preset> or /format <template>.";
+        }
+
+        let template = match templates::preset(self.template.as_str()) {
+            Some(t) => t.to_string(),
+            None => {
+                if let Err(e) = templates::validate(self.template.as_str()) {
+                    let bot = self.bot.clone();
+                    let text = format!("Invalid template: {}.", e);
+
+                    task::spawn(async move {
+                        throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+                    });
+
+                    return "The template was not saved.";
+                }
+
+                self.template.clone()
+            }
+        };
+
+        db.set_user_template(user_id, template);
+        db.drop();
+
+        "Notification format updated."
+    }
+}
@@ -0,0 +1,40 @@
+use chrono_tz::Tz;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{now, parse_utc_offset};
+use crate::models::user::User;
+
+// /timezone Europe/Berlin follows daylight saving time, /timezone UTC+2 or /timezone -05:00 is a fixed offset.
+pub struct SetTimezoneCommand {
+    pub timezone: String,
+}
+
+impl CommandHandler for SetTimezoneCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let (offset, name) = match self.timezone.parse::<Tz>() {
+            Ok(tz) => {
+                let mut zone = User::new("".to_string(), None);
+                zone.timezone = tz.name().to_string();
+
+                (zone.offset_at(now()), zone.timezone)
+            }
+            Err(_) => match parse_utc_offset(self.timezone.as_str()) {
+                Some(o) if !self.timezone.is_empty() => (o, "".to_string()),
+                _ => return "Usage: /timezone <IANA name or UTC offset>, e.g. /timezone Europe/Berlin or /timezone UTC+2",
+            },
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        db.set_user_timezone(user.unwrap().id.unwrap(), offset, name);
+        db.drop();
+
+        "Timezone updated."
+    }
+}
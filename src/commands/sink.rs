@@ -0,0 +1,131 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, logger, Message, SqliteDb};
+use crate::common::valid_eth_address;
+use crate::models::sink_config::SinkConfig;
+use crate::sinks::is_configurable;
+use crate::sinks::webhook::{self, generate_secret};
+use crate::throttle;
+use teloxide::{prelude::*};
+
+const MAX_SINKS: usize = 10;
+
+// /sink add <discord|slack|webhook> <url> [address], /sink remove <id>, /sink list
+pub struct SinkCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+    // whether the url of an add is https and resolves to public addresses only, see sinks::ensure_public.
+    pub reachable: Result<(), &'static str>,
+}
+
+impl CommandHandler for SinkCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        match args.as_slice() {
+            [] | ["list"] => {
+                let sinks = db.get_user_sinks(user_id);
+                let wallets = db.get_user_wallets(user_id);
+                db.drop();
+
+                if sinks.is_empty() {
+                    return "You have no sinks.";
+                }
+
+                let bot = self.bot.clone();
+                let text = sinks.iter()
+                    .map(|s| {
+                        let scope = wallets.iter().find(|w| w.id == Some(s.wallet_id))
+                            .map(|w| w.address.clone())
+                            .unwrap_or("all wallets".to_string());
+
+                        format!("#{} {} {} ({})", s.id.unwrap(), s.kind, s.masked_target(), scope)
+                    })
+                    .collect::<Vec<String>>().join("\n");
+
+                task::spawn(async move {
+                    throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+                });
+
+                "Here is your sinks:"
+            }
+            ["remove", id] => {
+                let id = match id.trim_start_matches('#').parse::<i64>() {
+                    Ok(id) => id,
+                    Err(_) => return "Invalid sink id.",
+                };
+
+                if !db.get_user_sinks(user_id).iter().any(|s| s.id == Some(id)) {
+                    return "This sink does not belong to you.";
+                }
+
+                db.remove_sink(user_id, id);
+                db.drop();
+
+                "The sink removed."
+            }
+            ["add", kind, url, rest @ ..] if rest.len() <= 1 => {
+                let kind = kind.to_ascii_lowercase();
+
+                if !is_configurable(kind.as_str()) {
+                    return "Sink should be discord, slack or webhook.";
+                }
+
+                if let Err(e) = self.reachable {
+                    return e;
+                }
+
+                let wallet_id = match rest.first() {
+                    Some(address) => {
+                        if !valid_eth_address(address) {
+                            return "Invalid eth address";
+                        }
+
+                        match db.get_wallet(Some(user_id), address.to_string()) {
+                            Some(w) => w.id.unwrap(),
+                            None => return "You are not tracking this wallet.",
+                        }
+                    }
+                    None => 0,
+                };
+
+                if db.get_user_sinks(user_id).len() >= MAX_SINKS {
+                    return "You have reached the maximum number of sinks.";
+                }
+
+                let mut sink = SinkConfig::new(user_id, wallet_id, kind, url.to_string(), None);
+                if sink.kind == webhook::KIND {
+                    sink.secret = generate_secret();
+                }
+
+                let secret = sink.secret.clone();
+
+                db.add_sink(sink);
+                db.drop();
+
+                if !secret.is_empty() {
+                    let bot = self.bot.clone();
+                    let text = format!("Events are signed with this secret, it is shown only once:\n{}", secret);
+
+                    task::spawn(async move {
+                        if let Err(e) = throttle::send_message(&bot, message.chat.id, text.as_str()).await {
+                            logger!("-> could not send the sink secret: {}", e);
+                        }
+                    });
+                }
+
+                "The sink added."
+            }
+            _ => "Usage: /sink add <discord|slack|webhook> <url> [address], /sink remove <id>, /sink list",
+        }
+    }
+}
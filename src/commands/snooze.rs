@@ -0,0 +1,34 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{now, parse_duration};
+
+// /snooze <duration> for every wallet of the user, /snooze off ends it early.
+pub struct SnoozeCommand {
+    pub duration: String,
+}
+
+impl CommandHandler for SnoozeCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let until = if self.duration == "off" {
+            0
+        } else {
+            match parse_duration(self.duration.as_str()).and_then(|d| now().checked_add(d)) {
+                Some(until) => until,
+                None => return "Usage: /snooze <duration>, e.g. /snooze 8h, or /snooze off",
+            }
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        db.set_user_snooze(user.unwrap().id.unwrap(), until);
+        db.drop();
+
+        if until == 0 { "Snooze ended." } else { "Notifications snoozed." }
+    }
+}
@@ -1,5 +1,7 @@
 use crate::commands::CommandHandler;
 use crate::{DataRepository, logger, Message, SqliteDb};
+use crate::models::chat::Chat;
+use crate::release_notes;
 
 // todo I think it could be implement a little better with macros, structs and other stuff
 pub struct StartCommand {}
@@ -17,10 +19,18 @@ impl CommandHandler for StartCommand {
         if db.get_user(message.chat.id.0).is_none() {
             db.add_user(message.chat.id.0);
             logger!("New user has been added.");
+
+            // past release notes are of no interest to someone who just started.
+            if let Some(user) = db.get_user(message.chat.id.0) {
+                db.set_user_release_seen(user.id.unwrap(), release_notes::latest());
+            }
         } else {
             logger!("User exists.");
         }
 
+        let added_by = message.from().map(|u| u.id.0 as i64).unwrap_or_default();
+        db.set_chat(Chat::from_telegram(&message.chat, added_by));
+
         db.drop();
         "Send wallet address: /add <wallet_address>"
     }
@@ -0,0 +1,33 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, logger, Message, SqliteDb};
+use crate::common::is_admin;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /stats, admins only.
+pub struct StatsCommand<'a> {
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for StatsCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !is_admin(&message) {
+            return "This command is only available to admins.";
+        }
+
+        let mut db = SqliteDb::get_connection();
+        let stats = db.get_stats();
+        db.drop();
+
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            if let Err(e) = throttle::send_message(&bot, message.chat.id, stats.to_string()).await {
+                logger!("-> could not send the /stats reply: {}", e);
+            }
+        });
+
+        "Bot statistics:"
+    }
+}
@@ -0,0 +1,95 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::days_from_civil;
+use crate::models::transaction::TransactionFilter;
+use crate::tax::{build_report, LotMethod};
+use crate::throttle;
+use teloxide::{prelude::*, types::InputFile};
+
+// /taxreport <year> [fifo|lifo|hifo]
+pub struct TaxReportCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for TaxReportCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        let year = match args.first().and_then(|y| y.parse::<i64>().ok()) {
+            Some(y) if (2015..=9999).contains(&y) => y,
+            _ => return "Usage: /taxreport <year> [fifo|lifo|hifo]",
+        };
+
+        let method = match args.get(1) {
+            Some(m) => match LotMethod::parse(m) {
+                Some(m) => m,
+                None => return "Usage: /taxreport <year> [fifo|lifo|hifo]",
+            },
+            None => LotMethod::Fifo,
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user = user.unwrap();
+        let wallets = db.get_user_wallets(user.id.unwrap());
+
+        // lots acquired in earlier years are needed for the cost basis, so everything up to the year end is read.
+        let filter = TransactionFilter {
+            to: Some(days_from_civil(year + 1, 1, 1) * 86400 - 1),
+            ..TransactionFilter::default()
+        };
+
+        let mut txs = vec![];
+
+        for wallet in wallets.iter() {
+            for trx in db.get_transactions(wallet.id.unwrap(), wallet.address.clone(), &filter, -1, 0) {
+                txs.push((wallet.address.clone(), trx));
+            }
+        }
+
+        db.drop();
+
+        let own_addresses = wallets.iter().map(|w| w.address.clone()).collect::<Vec<String>>();
+        let report = match build_report(txs, &own_addresses, year, method, user.currency.as_str()) {
+            Ok(r) => r,
+            Err(_) => return "Your transactions were priced in more than one currency, gains can not be added up.",
+        };
+
+        if report.events.is_empty() {
+            return "There is no taxable transaction in this year.";
+        }
+
+        let gains = report.gains().iter()
+            .map(|(asset, gain)| format!("{}: {:.2} {}", asset, gain, report.currency))
+            .collect::<Vec<String>>();
+        let mut summary = if gains.is_empty() {
+            format!("Tax report {} ({}): no disposals.", year, method.name())
+        } else {
+            format!("Tax report {} ({}), realised gains:\n{}", year, method.name(), gains.join("\n"))
+        };
+
+        if report.unpriced() > 0 {
+            summary = format!("{}\n{} rows had no price and count as zero, they are marked in the file.", summary, report.unpriced());
+        }
+
+        let file_name = format!("tax-report-{}-{}.csv", year, method.name().to_lowercase());
+        let content = report.to_csv();
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            throttle::acquire(message.chat.id).await;
+            bot.send_document(message.chat.id, InputFile::memory(content).file_name(file_name)).await.unwrap();
+            throttle::send_message(&bot, message.chat.id, summary).await.unwrap();
+        });
+
+        "Preparing your tax report..."
+    }
+}
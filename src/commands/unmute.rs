@@ -0,0 +1,36 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{valid_eth_address};
+
+pub struct UnmuteCommand {
+    pub address: String,
+}
+
+impl CommandHandler for UnmuteCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !valid_eth_address(self.address.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let wallet = db.get_wallet(Some(user_id), self.address.to_string());
+
+        if wallet.is_none() {
+            return "This wallet address is not tracked by you.";
+        }
+
+        db.remove_wallet_mutes(user_id, wallet.unwrap().id.unwrap());
+        db.drop();
+
+        "The wallet unmuted."
+    }
+}
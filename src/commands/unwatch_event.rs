@@ -0,0 +1,34 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+
+pub struct UnwatchEventCommand {
+    pub id: String,
+}
+
+impl CommandHandler for UnwatchEventCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let id = match self.id.trim_start_matches('#').parse::<i64>() {
+            Ok(id) => id,
+            Err(_) => return "Invalid event watch id.",
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        if !db.get_user_event_watches(user_id).iter().any(|w| w.id == Some(id)) {
+            return "This event is not watched by you.";
+        }
+
+        db.remove_event_watch(user_id, id);
+        db.drop();
+
+        "The event removed from watch list."
+    }
+}
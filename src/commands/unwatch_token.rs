@@ -0,0 +1,34 @@
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{valid_eth_address};
+
+pub struct UnwatchTokenCommand {
+    pub contract: String,
+}
+
+impl CommandHandler for UnwatchTokenCommand {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !valid_eth_address(self.contract.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        if db.get_token_watch(user_id, self.contract.to_string()).is_none() {
+            return "This token is not watched by you.";
+        }
+
+        db.remove_token_watch(user_id, self.contract.to_string());
+        db.drop();
+
+        "The token removed from watch list."
+    }
+}
@@ -0,0 +1,62 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, logger, Message, SqliteDb};
+use crate::common::is_admin;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+const PAGE_SIZE: i64 = 20;
+
+// /users [page], admins only.
+pub struct UsersCommand<'a> {
+    pub page: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for UsersCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !is_admin(&message) {
+            return "This command is only available to admins.";
+        }
+
+        let page = if self.page.is_empty() {
+            1
+        } else {
+            match self.page.parse::<i64>() {
+                Ok(p) if p > 0 => p,
+                _ => return "Usage: /users [page]",
+            }
+        };
+
+        let mut db = SqliteDb::get_connection();
+
+        let total = db.get_stats().users;
+        let users = db.get_users(PAGE_SIZE, (page - 1) * PAGE_SIZE);
+        let wallets = db.get_all_wallets_with_user();
+        db.drop();
+
+        if users.is_empty() {
+            return "No users on this page.";
+        }
+
+        let pages = (total + PAGE_SIZE - 1) / PAGE_SIZE;
+        let mut lines = vec![format!("Users {}/{}:", page, pages)];
+
+        lines.extend(users.iter().map(|u| {
+            let count = wallets.iter().filter(|w| w.user.as_ref().and_then(|wu| wu.id) == u.id).count();
+            let quota = if u.wallet_quota == 0 { "".to_string() } else { format!(" (quota {})", u.wallet_quota) };
+
+            format!("{} wallets {}{}{}", u.chat_id, count, quota, if u.banned { " banned" } else { "" })
+        }));
+
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            if let Err(e) = throttle::send_message(&bot, message.chat.id, lines.join("\n")).await {
+                logger!("-> could not send the /users reply: {}", e);
+            }
+        });
+
+        "Here is the users:"
+    }
+}
@@ -0,0 +1,83 @@
+use tokio::task;
+use crate::abi::EventAbi;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{background_event_worker, to_checksum_address, valid_eth_address};
+use crate::models::event_watch::EventWatch;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /watchevent <contract> <event signature>, when sent as a reply to an abi json document the
+// event name alone is enough. Without arguments it lists the watched events.
+pub struct WatchEventCommand<'a> {
+    pub args: String,
+    pub abi: Option<String>,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for WatchEventCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+
+        let (contract, signature) = match self.args.split_once(' ') {
+            Some((c, s)) => (c.trim().to_string(), s.trim().to_string()),
+            None if self.args.is_empty() => {
+                let watches = db.get_user_event_watches(user_id);
+                db.drop();
+
+                if watches.is_empty() {
+                    return "You are not watching any event.";
+                }
+
+                let bot = self.bot.clone();
+                let text = watches.iter().map(|w| w.to_string()).collect::<Vec<String>>().join("\n");
+
+                task::spawn(async move {
+                    throttle::send_message(&bot, message.chat.id, text.as_str()).await.unwrap();
+                });
+
+                return "Here is the events you are watching:";
+            }
+            None => return "Usage: /watchevent <contract> <event signature>",
+        };
+
+        if !valid_eth_address(contract.as_str()) {
+            return "Invalid eth address";
+        }
+
+        let contract = to_checksum_address(contract.as_str());
+
+        let event = match &self.abi {
+            Some(abi) if !signature.contains('(') => EventAbi::from_abi_json(abi.as_str(), signature.as_str()),
+            _ => EventAbi::parse(signature.as_str()),
+        };
+
+        let event = match event {
+            Some(e) => e,
+            None => return "Invalid or unsupported event signature.",
+        };
+
+        let watch_id = match db.add_event_watch(EventWatch::new(user_id, contract, event.to_string(), None)) {
+            Some(id) => id,
+            None => return "Could not save the event watch.",
+        };
+        db.drop();
+
+        let chat_id = message.chat.id;
+
+        task::spawn(async move {
+            let mut repo = SqliteDb::get_connection();
+            background_event_worker::<SqliteDb>(chat_id, watch_id, &mut repo).await;
+        });
+
+        "The event added to watch list."
+    }
+}
@@ -0,0 +1,79 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, Message, SqliteDb};
+use crate::common::{background_token_worker, to_checksum_address, valid_eth_address};
+use crate::models::token_watch::TokenWatch;
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// /watchtoken <contract> <min_amount>, without arguments it lists the watched tokens.
+pub struct WatchTokenCommand<'a> {
+    pub args: String,
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for WatchTokenCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        let mut db = SqliteDb::get_connection();
+
+        let user = db.get_user(message.chat.id.0);
+
+        if user.is_none() {
+            return "Please send /start command.";
+        }
+
+        let user_id = user.unwrap().id.unwrap();
+        let args = self.args.split_whitespace().collect::<Vec<&str>>();
+
+        match args.as_slice() {
+            [] => {
+                let watches = db.get_user_token_watches(user_id);
+                db.drop();
+
+                if watches.is_empty() {
+                    return "You are not watching any token.";
+                }
+
+                let bot = self.bot.clone();
+
+                task::spawn(async move {
+                    for watch in watches {
+                        throttle::send_message(&bot, message.chat.id, format!("https://etherscan.io/token/{} above {}", watch.contract, watch.threshold)).await.unwrap();
+                    }
+                });
+
+                "Here is the tokens you are watching:"
+            }
+            [contract, amount] => {
+                if !valid_eth_address(contract) {
+                    return "Invalid eth address";
+                }
+
+                let contract = to_checksum_address(contract);
+
+                let threshold = match amount.replace(',', "").parse::<f64>() {
+                    Ok(v) if v >= 0f64 => v,
+                    _ => return "Invalid amount.",
+                };
+
+                if db.get_token_watch(user_id, contract.clone()).is_some() {
+                    return "This token is currently being watched.";
+                }
+
+                db.add_token_watch(TokenWatch::new(user_id, contract.clone(), threshold, None));
+                db.drop();
+
+                let bot = self.bot.clone();
+                let chat_id = message.chat.id;
+
+                task::spawn(async move {
+                    let mut repo = SqliteDb::get_connection();
+                    background_token_worker::<SqliteDb>(&bot.clone(), chat_id, contract, user_id, &mut repo).await;
+                });
+
+                "The token added to watch list."
+            }
+            _ => "Usage: /watchtoken <contract> <min_amount>",
+        }
+    }
+}
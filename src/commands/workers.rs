@@ -0,0 +1,64 @@
+use tokio::task;
+use crate::commands::CommandHandler;
+use crate::{DataRepository, logger, Message, SqliteDb};
+use crate::common::{format_timestamp, is_admin, now};
+use crate::throttle;
+use teloxide::{prelude::*};
+
+// keeps the reply under the telegram message size, the stalest addresses come first.
+const MAX_ADDRESSES: usize = 30;
+
+// /workers, admins only.
+pub struct WorkersCommand<'a> {
+    pub bot: &'a AutoSend<Bot>,
+}
+
+impl CommandHandler for WorkersCommand<'_> {
+    fn handle<'a>(&mut self, message: Message) -> &'a str {
+        if !is_admin(&message) {
+            return "This command is only available to admins.";
+        }
+
+        let mut db = SqliteDb::get_connection();
+
+        let addresses = db.get_stats().addresses;
+        let statuses = db.get_poll_statuses();
+        let paused = db.count_paused_addresses();
+        let (queued, due) = db.count_queued_deliveries(now());
+        db.drop();
+
+        let now = now();
+        let stale = statuses.iter().filter(|s| s.is_stale(now)).count();
+        let never = (addresses - paused - statuses.len() as i64).max(0);
+
+        let mut lines = vec![
+            format!("Outbox: {} queued, {} due", queued, due),
+            format!("Addresses: {} tracked, {} stale, {} paused (banned), {} never polled", addresses, stale, paused, never),
+        ];
+
+        lines.extend(statuses.iter().take(MAX_ADDRESSES).map(|s| {
+            let last_poll = if s.last_success_at == 0 { "never".to_string() } else { format_timestamp(s.last_success_at) };
+            let error = if s.last_error.is_empty() {
+                "".to_string()
+            } else {
+                format!(", last error {}: {}", format_timestamp(s.last_error_at), s.last_error.chars().take(60).collect::<String>())
+            };
+
+            format!("{} {} last poll {}, {} errors{}", if s.is_stale(now) { "⚠️" } else { "✅" }, s.address, last_poll, s.errors, error)
+        }));
+
+        if statuses.len() > MAX_ADDRESSES {
+            lines.push(format!("…and {} more", statuses.len() - MAX_ADDRESSES));
+        }
+
+        let bot = self.bot.clone();
+
+        task::spawn(async move {
+            if let Err(e) = throttle::send_message(&bot, message.chat.id, lines.join("\n")).await {
+                logger!("-> could not send the /workers reply: {}", e);
+            }
+        });
+
+        "Worker status:"
+    }
+}
@@ -1,10 +1,27 @@
+use std::collections::HashSet;
 use std::time::{Duration};
 use crypto::{sha3::Sha3, digest::Digest};
 use structopt::StructOpt;
-use teloxide::{prelude::*, types::ChatId};
-use tokio::{time, task};
+use teloxide::{prelude::*, net::Download, types::{ChatId, UserId}, RequestError};
+use tokio::{sync::mpsc, time, task};
 use crate::{AppConfig, DataRepository, SqliteDb};
-use crate::models::{etherscan::*, transaction::Transaction};
+use crate::abi::EventAbi;
+use crate::{ens, entities, release_notes, sinks, throttle};
+use crate::sinks::{NotificationEvent, NotificationSink, SinkError};
+use crate::sinks::telegram::{self, TelegramSink};
+use crate::models::balance::Balance;
+use crate::models::held_notification::HeldNotification;
+use crate::models::member::Member;
+use crate::models::mute::Mute;
+use crate::models::queued_delivery::QueuedDelivery;
+use crate::models::user::User;
+use crate::models::etherscan::EtherScanLogDetail;
+use crate::models::transaction::Transaction;
+use crate::models::wallet::Wallet;
+use crate::models::transfer::{group_by_hash, TransferGroup};
+use crate::providers::{etherscan, rpc};
+use crate::prices::{self, PriceSource, price_symbol};
+use crate::renderer::{Labels, Markup, Notification, render_digest, render_transaction};
 
 #[macro_export]
 macro_rules! logger {
@@ -29,43 +46,252 @@ macro_rules! logger_l {
 }
 
 
+// all-lowercase and all-uppercase addresses carry no checksum, mixed case ones must match eip-55.
 pub fn valid_eth_address(address: &str) -> bool {
-    if address.len() != 42 {
+    if address.len() != 42 || !address.starts_with("0x") {
         return false;
     }
 
-    let normalized_address = normalize_address(address);
+    let digits = &address[2..];
 
-    if normalized_address.len() != 40 {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
         return false;
     }
 
+    if digits == digits.to_ascii_lowercase() || digits == digits.to_ascii_uppercase() {
+        return true;
+    }
+
+    to_checksum_address(address) == address
+}
+
+// the eip-55 form addresses are stored and displayed in.
+pub fn to_checksum_address(address: &str) -> String {
+    let normalized_address = normalize_address(address);
+
     let mut hasher = Sha3::keccak256();
     hasher.input_str(&normalized_address);
     let address_hash = hasher.result_str();
 
-    for i in 0..40 {
-        if u32::from_str_radix(&address_hash[i..i + 1], 16).unwrap() > 7 {
-            if address[i + 2..i + 3].to_ascii_uppercase() != address[i + 2..i + 3] {
-                return false;
+    let checksummed = normalized_address.chars().zip(address_hash.chars())
+        .map(|(c, h)| if h.to_digit(16).unwrap_or(0) > 7 { c.to_ascii_uppercase() } else { c })
+        .collect::<String>();
+
+    format!("0x{}", checksummed)
+}
+
+fn normalize_address(address: &str) -> String {
+    address.to_ascii_lowercase().trim_start_matches("0x").to_string()
+}
+
+// "2024-01-31 13:05 UTC" without pulling a date crate in.
+pub fn format_timestamp(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
+    let seconds = timestamp.rem_euclid(86400);
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, seconds / 3600, seconds % 3600 / 60)
+}
+
+// unix timestamp of the start of a `YYYY-MM-DD` day in utc.
+pub fn parse_date(date: &str) -> Option<i64> {
+    let parts = date.split('-').map(|p| p.parse::<i64>()).collect::<Result<Vec<i64>, _>>().ok()?;
+
+    match parts.as_slice() {
+        [year, month, day] if (1..=12).contains(month) && (1..=31).contains(day) => {
+            Some(days_from_civil(*year, *month, *day) * 86400)
+        }
+        _ => None,
+    }
+}
+
+// `30m`, `2h`, `1d`, `1w` in seconds.
+pub fn parse_duration(duration: &str) -> Option<i64> {
+    let duration = duration.trim().to_ascii_lowercase();
+    let (index, unit) = duration.char_indices().last()?;
+    let value = duration[..index].parse::<i64>().ok().filter(|v| *v > 0)?;
+
+    let seconds = match unit {
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 604800,
+        _ => return None,
+    };
+
+    value.checked_mul(seconds)
+}
+
+// `UTC+2`, `+05:30`, `-4` as minutes east of utc.
+pub fn parse_utc_offset(offset: &str) -> Option<i64> {
+    let offset = offset.trim().to_ascii_uppercase();
+    let offset = offset.trim_start_matches("UTC").trim_start_matches("GMT");
+
+    if offset.is_empty() {
+        return Some(0);
+    }
+
+    let (sign, rest) = match offset.chars().next()? {
+        '+' => (1, &offset[1..]),
+        '-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h.parse::<i64>().ok()?, m.parse::<i64>().ok()?),
+        None => (rest.parse::<i64>().ok()?, 0),
+    };
+
+    let total = sign * (hours * 60 + minutes);
+
+    if minutes >= 60 || !(-720..=840).contains(&total) { None } else { Some(total) }
+}
+
+// `HH:MM` as minutes since midnight.
+pub fn parse_clock(clock: &str) -> Option<i64> {
+    let (hours, minutes) = clock.trim().split_once(':')?;
+    let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+
+    if (0..24).contains(&hours) && (0..60).contains(&minutes) { Some(hours * 60 + minutes) } else { None }
+}
+
+pub fn format_clock(minutes: i64) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+// days since unix epoch of the given gregorian date.
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+// gregorian date of the given days since unix epoch.
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+// exact decimal representation of a raw on-chain integer amount.
+pub fn format_units(raw: &str, decimal: i64) -> String {
+    let decimal = decimal.max(0) as usize;
+    let digits = raw.trim().trim_start_matches('0');
+    let padded = format!("{:0>width$}", digits, width = decimal + 1);
+    let (int_part, frac_part) = padded.split_at(padded.len() - decimal);
+    let frac_part = frac_part.trim_end_matches('0');
+
+    if frac_part.is_empty() {
+        int_part.to_string()
+    } else {
+        format!("{}.{}", int_part, frac_part)
+    }
+}
+
+// human friendly amount, e.g. "1,000" or "0.42".
+pub fn format_amount(raw: &str, decimal: i64) -> String {
+    let exact = format_units(raw, decimal);
+    let (int_part, frac_part) = match exact.split_once('.') {
+        Some((i, f)) => (i.to_string(), f.to_string()),
+        None => (exact.clone(), "".to_string()),
+    };
+
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    let precision = if int_part == "0" {
+        frac_part.len() - frac_part.trim_start_matches('0').len() + 4
+    } else {
+        6
+    };
+    let frac_part = frac_part.chars().take(precision).collect::<String>();
+    let frac_part = frac_part.trim_end_matches('0');
+
+    if frac_part.is_empty() {
+        grouped
+    } else {
+        format!("{}.{}", grouped, frac_part)
+    }
+}
+
+// the longest prefix of text that fits in max_bytes without cutting a character in half.
+pub fn truncate_bytes(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    &text[..end]
+}
+
+// telegram refuses longer messages, the limit is in characters.
+pub const MESSAGE_LIMIT: usize = 4096;
+
+// splits text at line breaks into parts of at most limit characters, a line longer than that is cut.
+pub fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut parts: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for mut line in text.split('\n') {
+        loop {
+            let line_len = line.chars().count();
+            let needed = if current.is_empty() { line_len } else { current_len + 1 + line_len };
+
+            if needed <= limit {
+                if !current.is_empty() {
+                    current.push('\n');
+                }
+                current.push_str(line);
+                current_len = needed;
+                break;
             }
-        } else {
-            if address[i + 2..i + 3].to_ascii_lowercase() != address[i + 2..i + 3] {
-                return false;
+
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+                current_len = 0;
+                continue;
             }
+
+            let cut = line.char_indices().nth(limit).map(|(i, _)| i).unwrap_or(line.len());
+            parts.push(line[..cut].to_string());
+            line = &line[cut..];
         }
     }
 
-    true
-}
+    if !current.is_empty() || parts.is_empty() {
+        parts.push(current);
+    }
 
-fn normalize_address(address: &str) -> String {
-    address.to_ascii_lowercase().trim_start_matches("0x").to_string()
+    parts
 }
 
 pub async fn background_wallet_worker<R>(bot: &AutoSend<Bot>, chat_id: ChatId, wallet: String, user_id: i64, repo: &mut R)
     where R: DataRepository {
     let config = AppConfig::from_args();
+    let price_source = prices::from_config(&config);
+    let markup = Markup::from_config(&config);
     let mut interval = time::interval(Duration::from_secs(60));
 
     let address = wallet.clone();
@@ -74,113 +300,877 @@ pub async fn background_wallet_worker<R>(bot: &AutoSend<Bot>, chat_id: ChatId, w
     interval.tick().await;
 
     loop {
-        if repo.get_wallet(Some(user_id), wallet_address.address.clone()).is_none() {
-            logger!("tracking wallet {} for user {} stopped.", wallet_address.address, user_id);
-            break;
+        // re-read every tick so removals and /route changes are picked up.
+        let current = match repo.get_wallet(Some(user_id), wallet_address.address.clone()) {
+            Some(w) => w,
+            None => {
+                logger!("tracking wallet {} for user {} stopped.", wallet_address.address, user_id);
+                break;
+            }
+        };
+        let delivery_chat = ChatId(current.delivery_chat(chat_id.0));
+
+        let user = match repo.get_user(chat_id.0) {
+            Some(u) => u,
+            None => User::new(chat_id.0.to_string(), Some(user_id)),
+        };
+
+        // banned users keep their wallets but are not polled, /unban resumes them.
+        if user.banned {
+            interval.tick().await;
+            continue;
+        }
+
+        let (currency, template) = (user.currency.clone(), user.template.clone());
+        let mut labels = user_labels(repo, user_id);
+
+        let mut external_sinks = sinks::external_sinks(&config);
+        external_sinks.extend(sinks::user_sinks(repo, user_id, current.id.unwrap()));
+
+        let mut transfers = vec![];
+        let mut latest_hashes = vec![];
+        let mut poll_error = None;
+
+        match etherscan::check_trx(config.ether_api.as_str(), address.as_str()).await {
+            Ok(v) => {
+                if let Some(d) = v.result.get(0) {
+                    if d.value.parse::<f64>().unwrap_or(0f64) != 0f64 {
+                        latest_hashes.push(d.hash.to_owned());
+                        transfers.push(d.to_transfer());
+                    }
+                }
+            }
+            Err(e) => poll_error = Some(e.to_string()),
+        }
+
+        match etherscan::check_erc(config.ether_api.as_str(), address.as_str()).await {
+            Ok(v) => {
+                if let Some(d) = v.result.get(0) {
+                    latest_hashes.push(d.hash.to_owned());
+                }
+
+                transfers.extend(v.result.iter().map(|d| d.to_transfer()));
+            }
+            Err(e) => poll_error = Some(e.to_string()),
+        }
+
+        match etherscan::check_internal(config.ether_api.as_str(), address.as_str()).await {
+            Ok(v) => transfers.extend(v.result.iter().filter(|d| d.isError == "0").map(|d| d.to_transfer())),
+            Err(e) => poll_error = Some(e.to_string()),
         }
 
-        let trx_res = check_trx(config.ether_api.as_str(), address.as_str()).await;
+        match poll_error {
+            Some(e) => repo.add_poll_error(address.clone(), e, now()),
+            None => repo.set_poll_success(address.clone(), now()),
+        };
+
+        for (hash, group) in group_by_hash(transfers) {
+            if !latest_hashes.iter().any(|h| h.eq_ignore_ascii_case(&hash)) {
+                continue;
+            }
+
+            let group = TransferGroup::classify(address.as_str(), group);
+
+            if let TransferGroup::Swap { .. } = group {
+                if repo.get_transaction(hash.to_owned(), wallet_address.id, None).is_some() {
+                    continue;
+                }
+            }
+
+            for mut trx in group.into_transactions(wallet_address.id.unwrap()) {
+                if !repo.has_transfer(trx.tx_hash.to_owned(), wallet_address.id.unwrap(), trx.log_index, trx.token.clone()) {
+                    set_fiat_value(&mut trx, price_source.as_ref(), currency.as_str()).await;
+
+                    ens::label_addresses(&mut labels, &[trx.from.as_str(), trx.to.as_str()], repo);
+
+                    let mut notification = render_transaction(&trx, Some(&wallet_address), &labels, template.as_str(), markup);
+                    let delivery = delivery_for(&user, &repo.get_user_mutes(user_id), wallet_address.id.unwrap(),
+                                                trx.token.as_str(), now());
+                    let summary = format!("{}: {}", labels.get(&wallet_address.address), trx.to_string());
+                    let event = NotificationEvent::from_transaction(&trx, wallet_address.address.as_str());
 
-        if let Ok(v) = trx_res {
-            if let Some(d) = v.result.get(0) {
-                let trx_amount = match d.value.parse::<f64>() {
-                    Ok(v) => {
-                        v
+                    repo.add_transaction(trx);
+
+                    if !matches!(delivery, Delivery::Drop) {
+                        sinks::enqueue(&external_sinks, &event, &notification, repo);
                     }
-                    Err(_) => {
-                        0f64
+
+                    // hourly and daily wallets are summarized by background_digest_worker instead.
+                    if !current.is_instant() {
+                        continue;
                     }
-                };
 
-                if trx_amount != 0f64 {
-                    let trx = Transaction::new(
-                        d.from.to_owned(),
-                        d.to.to_owned(),
-                        d.value.to_owned(),
-                        d.hash.to_owned(),
-                        "ETH".to_string(),
-                        wallet_address.id.unwrap(),
-                        18i64,
-                        None,
-                        Some(d.isError != "0"),
-                    );
-
-                    if repo.get_transaction(d.hash.to_owned(), wallet_address.id, Some("ETH".to_string())).is_none() {
-                        repo.add_transaction(trx);
-                        bot.send_message(chat_id, d.format_as_str().as_str()).await.unwrap();
+                    match delivery {
+                        Delivery::Normal | Delivery::Silent => {
+                            notification.silent = matches!(delivery, Delivery::Silent);
+
+                            let telegram_sink: Vec<Box<dyn NotificationSink>> = vec![Box::new(TelegramSink::new(bot.clone(), delivery_chat))];
+                            sinks::enqueue(&telegram_sink, &event, &notification, repo);
+                        }
+                        Delivery::Hold => {
+                            repo.add_held_notification(HeldNotification::new(user_id, wallet_address.id.unwrap(), delivery_chat.0,
+                                                                             summary, now(), None));
+                        }
+                        Delivery::Drop => {}
                     }
                 }
             }
         }
 
-        let erc_res = check_erc(config.ether_api.as_str(), address.as_str()).await;
+        interval.tick().await;
+    }
+}
 
-        if let Ok(v) = erc_res {
-            if let Some(d) = v.result.get(0) {
-                let trx = Transaction::new(
-                    d.from.to_owned(),
-                    d.to.to_owned(),
-                    d.value.to_owned(),
-                    d.hash.to_owned(),
-                    d.tokenName.to_owned(),
-                    wallet_address.id.unwrap(),
-                    d.tokenDecimal.parse::<i64>().unwrap_or(0i64),
-                    None,
-                    Some(true),
-                );
+pub const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
 
-                if repo.get_transaction(d.hash.to_owned(), wallet_address.id, Some(trx.token.clone())).is_none() {
-                    repo.add_transaction(trx);
-                    bot.send_message(chat_id, d.format_as_str().as_str()).await.unwrap();
+pub async fn background_token_worker<R>(bot: &AutoSend<Bot>, chat_id: ChatId, contract: String, user_id: i64, repo: &mut R)
+    where R: DataRepository {
+    let config = AppConfig::from_args();
+    let price_source = prices::from_config(&config);
+    let markup = Markup::from_config(&config);
+    let mut interval = time::interval(Duration::from_secs(60));
+
+    let mut token_info: Option<(String, String, i64)> = None;
+
+    interval.tick().await;
+
+    loop {
+        let watch = match repo.get_token_watch(user_id, contract.clone()) {
+            Some(w) => w,
+            None => {
+                logger!("watching token {} for user {} stopped.", contract, user_id);
+                break;
+            }
+        };
+
+        if repo.get_user(chat_id.0).map(|u| u.banned).unwrap_or(false) {
+            interval.tick().await;
+            continue;
+        }
+
+        if token_info.is_none() {
+            if let Ok(v) = etherscan::check_token_info(config.ether_api.as_str(), contract.as_str()).await {
+                if let Some(d) = v.result.get(0) {
+                    token_info = Some((d.tokenName.to_owned(), d.tokenSymbol.to_owned(), d.tokenDecimal.parse::<i64>().unwrap_or(0i64)));
+                }
+            }
+        }
+
+        let (name, symbol, decimal) = match &token_info {
+            Some(t) => t.clone(),
+            None => {
+                interval.tick().await;
+                continue;
+            }
+        };
+
+        let logs = poll_logs(config.ether_api.as_str(), contract.as_str(), TRANSFER_TOPIC, watch.last_block).await;
+
+        if let Some((latest_block, logs)) = logs {
+            let (currency, template) = match repo.get_user(chat_id.0) {
+                Some(u) => (u.currency, u.template),
+                None => ("USD".to_string(), "".to_string()),
+            };
+
+            let mut labels = user_labels(repo, user_id);
+
+            for log in logs.iter() {
+                let transfer = match log.to_transfer(name.as_str(), symbol.as_str(), decimal) {
+                    Some(t) => t,
+                    None => continue,
+                };
+
+                if format_units(&transfer.value, decimal).parse::<f64>().unwrap_or(0f64) < watch.threshold {
+                    continue;
                 }
+
+                let mut trx = transfer.to_transaction(0);
+                set_fiat_value(&mut trx, price_source.as_ref(), currency.as_str()).await;
+
+                ens::label_addresses(&mut labels, &[trx.from.as_str(), trx.to.as_str()], repo);
+
+                let mut notification = render_transaction(&trx, None, &labels, template.as_str(), markup);
+                notification.text = format!("{}\n{}", markup.escape(format!("Large {} transfer:", symbol).as_str()), notification.text);
+
+                let mut token_sinks = sinks::external_sinks(&config);
+                token_sinks.extend(sinks::user_sinks(repo, user_id, 0));
+                token_sinks.extend(sinks::email_sinks(&config, repo, user_id));
+                token_sinks.push(Box::new(TelegramSink::new(bot.clone(), chat_id)));
+
+                sinks::enqueue(&token_sinks, &NotificationEvent::from_transaction(&trx, ""), &notification, repo);
             }
+
+            repo.set_token_watch_block(watch.id.unwrap(), latest_block);
         }
 
         interval.tick().await;
     }
 }
 
-async fn check_trx(api_token: &str, wallet: &str) -> Result<EtherScanTrx, reqwest::Error> {
-    let url = format!("https://api.etherscan.io/api?module=account&action=txlist&address={wallet}&startblock=0&endblock=99999999&page=1&offset=1&sort=desc&apikey={api_token}",
-                      wallet = wallet, api_token = api_token);
+pub async fn background_event_worker<R>(chat_id: ChatId, watch_id: i64, repo: &mut R)
+    where R: DataRepository {
+    let config = AppConfig::from_args();
+    let markup = Markup::from_config(&config);
+    let mut interval = time::interval(Duration::from_secs(60));
+
+    interval.tick().await;
 
-    let resp = reqwest::get(url).await?
-        .json::<EtherScanTrx>().await?;
-    Ok(resp)
+    loop {
+        let watch = match repo.get_event_watch(watch_id) {
+            Some(w) => w,
+            None => {
+                logger!("watching event {} stopped.", watch_id);
+                break;
+            }
+        };
+
+        if repo.get_user(chat_id.0).map(|u| u.banned).unwrap_or(false) {
+            interval.tick().await;
+            continue;
+        }
+
+        let event = match EventAbi::parse(watch.signature.as_str()) {
+            Some(e) => e,
+            None => {
+                logger!("invalid signature {} for event watch {}.", watch.signature, watch_id);
+                break;
+            }
+        };
+
+        let logs = poll_logs(config.ether_api.as_str(), watch.contract.as_str(), event.topic().as_str(), watch.last_block).await;
+
+        if let Some((latest_block, logs)) = logs {
+            for log in logs {
+                let fields = event.decode(&log.topics, log.data.as_str()).iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect::<Vec<String>>();
+
+                let text = format!("Event {e} on {c}:\n{f}\nLink: https://etherscan.io/tx/{tx}",
+                                   e = event.name, c = watch.contract, f = fields.join("\n"), tx = log.transactionHash);
+
+                telegram::queue_notification(repo, chat_id, &Notification::plain(text.as_str(), markup), "");
+            }
+
+            repo.set_event_watch_block(watch_id, latest_block);
+        }
+
+        interval.tick().await;
+    }
 }
 
-async fn check_erc(api_token: &str, wallet: &str) -> Result<EtherScanErc, reqwest::Error> {
-    let url = format!("https://api.etherscan.io/api?module=account&action=tokentx&address={wallet}&page=1&offset=1&startblock=0&endblock=9999999999&sort=desc&apikey={api_token}",
-                      wallet = wallet, api_token = api_token);
+// logs of the contract mined after last_block together with the block scanned up to,
+// None when the chain could not be queried or nothing new was mined. When the range holds more
+// logs than can be paged through, or a page fails, only the blocks read completely are returned
+// and the next poll continues from there.
+async fn poll_logs(api_token: &str, contract: &str, topic: &str, last_block: i64) -> Option<(i64, Vec<EtherScanLogDetail>)> {
+    let latest_block = match etherscan::check_block_number(api_token).await {
+        Ok(v) => hex_to_i64(v.result.as_str()),
+        Err(_) => 0,
+    };
+
+    if latest_block == 0 || latest_block <= last_block {
+        return None;
+    }
+
+    // first poll only marks where scanning starts, history is not reported.
+    if last_block == 0 {
+        return Some((latest_block, vec![]));
+    }
+
+    let mut logs: Vec<EtherScanLogDetail> = vec![];
+
+    for page in 1..=MAX_LOG_PAGES {
+        if page > 1 {
+            // etherscan free tier allows 5 calls per second.
+            time::sleep(Duration::from_millis(250)).await;
+        }
 
-    let resp = reqwest::get(url).await?
-        .json::<EtherScanErc>().await?;
-    Ok(resp)
+        match etherscan::check_logs(api_token, contract, Some(topic), last_block + 1, latest_block, page, LOG_PAGE_SIZE).await {
+            Ok(v) => {
+                let complete = (v.result.len() as i64) < LOG_PAGE_SIZE;
+                logs.extend(v.result);
+
+                if complete {
+                    return Some((latest_block, logs));
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    complete_blocks(logs, last_block)
 }
 
-pub async fn notice_changelog<R>(bot: AutoSend<Bot>, repo: R) where R: DataRepository {
-    let users = repo.get_all_user();
+// the page limit was hit, the last block seen may be cut off so it is read again on the next poll.
+fn complete_blocks(mut logs: Vec<EtherScanLogDetail>, last_block: i64) -> Option<(i64, Vec<EtherScanLogDetail>)> {
+    let last_seen = logs.iter().map(|l| hex_to_i64(l.blockNumber.as_str())).max()?;
+    if last_seen <= last_block + 1 {
+        return None;
+    }
 
-    let changelog = r#"
-Bot has been update here is usage:
-/start: Starts the bot
-/add <wallet>: adds the wallet to tracker
-/remove <wallet>: removes wallet and all it's data from the bot and stops tracker for the wallet
-/list: shows list of wallets that have been tracked for you
-/txlist <address>: shows list of transactions for the wallet by the tracker.
-Update(s): 
-[+] fix bug in reporting 0 ETH txs.
-[+] fix bug in not reporting some ERC20 tokens txs.
-    "#;
+    logs.retain(|l| hex_to_i64(l.blockNumber.as_str()) < last_seen);
 
-    for user in users {
-        if let Ok(chat_id) = user.chat_id.parse::<i64>() {
-            bot.send_message(ChatId(chat_id), changelog).await.unwrap();
+    Some((last_seen - 1, logs))
+}
+
+pub enum Delivery {
+    Normal,
+    Silent,
+    Hold,
+    Drop,
+}
+
+// token and wallet mutes drop the notification, /snooze and quiet hours silence or hold it
+// depending on the quiet mode of the user.
+pub fn delivery_for(user: &User, mutes: &[Mute], wallet_id: i64, token: &str, now: i64) -> Delivery {
+    if mutes.iter().any(|m| m.is_active(now) && m.covers(wallet_id, token)) {
+        return Delivery::Drop;
+    }
+
+    if user.is_snoozed(now) || user.in_quiet_hours(now) {
+        return if user.holds() { Delivery::Hold } else { Delivery::Silent };
+    }
+
+    Delivery::Normal
+}
+
+pub async fn send_notification(bot: &AutoSend<Bot>, chat_id: ChatId, notification: &Notification) -> Result<Message, RequestError> {
+    throttle::acquire(chat_id).await;
+
+    let mut request = bot.send_message(chat_id, notification.text.as_str())
+        .parse_mode(notification.parse_mode)
+        .disable_web_page_preview(true)
+        .disable_notification(notification.silent);
+
+    if let Some(keyboard) = &notification.keyboard {
+        request = request.reply_markup(keyboard.clone());
+    }
+
+    request.await
+}
+
+pub fn hex_to_i64(hex: &str) -> i64 {
+    i64::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap_or(0)
+}
+
+// decimal string of a hex encoded uint256, None when it is not valid hex.
+pub fn hex_to_decimal(hex: &str) -> Option<String> {
+    // base 1e9 limbs, least significant first.
+    let mut limbs: Vec<u64> = vec![0];
+
+    for c in hex.trim_start_matches("0x").chars() {
+        let mut carry = c.to_digit(16)? as u64;
+
+        for limb in limbs.iter_mut() {
+            let v = *limb * 16 + carry;
+            *limb = v % 1_000_000_000;
+            carry = v / 1_000_000_000;
         }
+
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+
+    let mut res = limbs.last().unwrap().to_string();
+    for limb in limbs.iter().rev().skip(1) {
+        res.push_str(format!("{:09}", limb).as_str());
+    }
+
+    Some(res)
+}
+
+pub fn topic_to_address(topic: &str) -> String {
+    let topic = topic.trim_start_matches("0x");
+
+    format!("0x{}", &topic[topic.len().saturating_sub(40)..])
+}
+
+pub async fn set_fiat_value(trx: &mut Transaction, price_source: &dyn PriceSource, currency: &str) {
+    let symbol = if trx.symbol.is_empty() { trx.token.clone() } else { trx.symbol.clone() };
+    let timestamp = if trx.timestamp != 0 { trx.timestamp } else { now() };
+
+    if let Some(price) = price_source.get_price(price_symbol(symbol.as_str()).as_str(), currency, timestamp).await {
+        let amount = format_units(&trx.amount, trx.decimal).parse::<f64>().unwrap_or(0f64);
+
+        trx.fiat_value = amount * price;
+        trx.fiat_currency = currency.to_string();
+    }
+}
+
+// names shown instead of raw addresses in the notifications of the user, the first source
+// naming an address wins: tracked wallet labels, then contacts, then known entities.
+pub fn user_labels<R>(repo: &R, user_id: i64) -> Labels where R: DataRepository {
+    let mut labels = Labels::new();
+
+    for wallet in repo.get_user_wallets(user_id).into_iter().filter(|w| !w.label.is_empty()) {
+        labels.insert(wallet.address.as_str(), wallet.label);
+    }
+
+    for contact in repo.get_user_contacts(user_id) {
+        labels.insert(contact.address.as_str(), contact.name);
     }
+
+    for entity in entities::known_entities() {
+        labels.insert(entity.address.as_str(), entity.name.clone());
+    }
+
+    labels
+}
+
+pub fn now() -> i64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64
+}
+
+const BALANCE_TTL: i64 = 60;
+const ETHERSCAN_CALLS_PER_SECOND: usize = 5;
+const LOG_PAGE_SIZE: i64 = 1000;
+const MAX_LOG_PAGES: i64 = 10;
+
+// native balance first, followed by every erc20 token with a non-zero holding.
+// takes the repository mutably so the returned future stays Send while holding it.
+pub async fn get_wallet_balances<R>(address: &str, repo: &mut R) -> Vec<Balance> where R: DataRepository {
+    fetch_wallet_balances(address, repo).await.into_iter()
+        .filter(|b| b.is_native() || b.amount.trim_start_matches('0') != "")
+        .collect()
 }
 
-pub async fn start_previous_workers<R>(bot: AutoSend<Bot>, repo: R) where R: DataRepository {
+// every balance that could be fetched, empty ones included, so a missing token means it is unknown.
+pub async fn fetch_wallet_balances<R>(address: &str, repo: &mut R) -> Vec<Balance> where R: DataRepository {
+    let cached = repo.get_balances(address.to_string());
+
+    if !cached.is_empty() && cached.iter().all(|b| now() - b.updated_at < BALANCE_TTL) {
+        return cached;
+    }
+
+    let config = AppConfig::from_args();
+    let mut balances = vec![];
+
+    if let Ok(v) = etherscan::check_balance(config.ether_api.as_str(), address).await {
+        if v.status != "1" {
+            return vec![];
+        }
+
+        balances.push(Balance::new(address.to_string(), "".to_string(), "ETH".to_string(), 18i64,
+                                   v.result, now(), None));
+    }
+
+    if let Ok(v) = etherscan::check_erc_history(config.ether_api.as_str(), address).await {
+        let mut tokens: Vec<(String, String, i64)> = vec![];
+
+        for d in v.result {
+            if tokens.iter().any(|(c, _, _)| c.eq_ignore_ascii_case(&d.contractAddress)) {
+                continue;
+            }
+
+            tokens.push((d.contractAddress.to_owned(), d.tokenSymbol.to_owned(), d.tokenDecimal.parse::<i64>().unwrap_or(0i64)));
+        }
+
+        balances.extend(get_token_balances(&config, address, &tokens).await);
+    }
+
+    if balances.is_empty() {
+        return balances;
+    }
+
+    repo.set_balances(address.to_string(), &balances);
+
+    balances
+}
+
+// balanceOf of every (contract, symbol, decimal) in one json-rpc batch. Nodes that refuse batches fall
+// back to etherscan, a few tokens at a time as the free tier allows 5 calls per second.
+async fn get_token_balances(config: &AppConfig, address: &str, tokens: &[(String, String, i64)]) -> Vec<Balance> {
+    let data = format!("0x70a08231{:0>64}", address.trim_start_matches("0x").to_ascii_lowercase());
+    let calls = tokens.iter().map(|(c, _, _)| (c.clone(), data.clone())).collect::<Vec<(String, String)>>();
+
+    let amounts = match rpc::batch_eth_call(config.rpc_url.as_str(), &calls).await {
+        Ok(results) => results.into_iter().map(|r| r.and_then(|hex| hex_to_decimal(hex.as_str()))).collect::<Vec<Option<String>>>(),
+        Err(_) => {
+            let mut amounts = vec![];
+
+            for chunk in tokens.chunks(ETHERSCAN_CALLS_PER_SECOND) {
+                let started = time::Instant::now();
+
+                let handles = chunk.iter().map(|(contract, _, _)| {
+                    let (api, address, contract) = (config.ether_api.clone(), address.to_string(), contract.clone());
+
+                    task::spawn(async move {
+                        etherscan::check_token_balance(api.as_str(), address.as_str(), contract.as_str()).await.ok()
+                            .filter(|b| b.status == "1")
+                            .map(|b| b.result)
+                    })
+                }).collect::<Vec<task::JoinHandle<Option<String>>>>();
+
+                for handle in handles {
+                    amounts.push(handle.await.unwrap_or(None));
+                }
+
+                if amounts.len() < tokens.len() {
+                    time::sleep_until(started + Duration::from_secs(1)).await;
+                }
+            }
+
+            amounts
+        }
+    };
+
+    tokens.iter().zip(amounts)
+        .filter_map(|((contract, symbol, decimal), amount)| {
+            amount.map(|a| Balance::new(address.to_string(), contract.to_owned(), symbol.to_owned(), *decimal, a, now(), None))
+        })
+        .collect()
+}
+
+// summaries of hourly and daily wallets built from the stored transactions, sent once a period closes
+// in the local time of the user. held periods are retried on the next tick.
+pub async fn background_digest_worker<R>(mut repo: R) where R: DataRepository {
+    let markup = Markup::from_config(&AppConfig::from_args());
+    let mut interval = time::interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        for wallet in repo.get_all_wallets_with_user().into_iter().filter(|w| !w.is_instant()) {
+            // the watermark is kept for banned users, /unban sends everything stored meanwhile.
+            let user = match &wallet.user {
+                Some(u) if !u.banned => u,
+                _ => continue,
+            };
+
+            let boundary = wallet.digest_boundary(now(), user.offset_at(now()), user.digest_time);
+
+            // a queued digest marks its period sent once delivered.
+            if wallet.digest_sent_at >= boundary || repo.has_queued_source(format!("digest:{}:", wallet.id.unwrap())) {
+                continue;
+            }
+
+            let notification_chat = ChatId(wallet.delivery_chat(user.chat_id.parse::<i64>().unwrap()));
+
+            // everything stored since the last digest, periods missed while the bot was down included.
+            let until = now();
+            let txs = repo.get_digest_transactions(wallet.id.unwrap(), wallet.digest_until, until);
+
+            if txs.is_empty() {
+                repo.set_wallet_digest_sent(wallet.id.unwrap(), boundary, wallet.digest_until);
+                continue;
+            }
+
+            // held digests keep the watermark and are sent with everything stored meanwhile.
+            let silent = match delivery_for(user, &repo.get_user_mutes(user.id.unwrap()), wallet.id.unwrap(), "", now()) {
+                Delivery::Hold => continue,
+                Delivery::Drop => {
+                    repo.set_wallet_digest_sent(wallet.id.unwrap(), boundary, until);
+                    continue;
+                }
+                Delivery::Silent => true,
+                Delivery::Normal => false,
+            };
+
+            let period = if wallet.delivery == Wallet::HOURLY { "Hourly" } else { "Daily" };
+            let mut notification = render_digest(&txs, &wallet, &user_labels(&repo, user.id.unwrap()), period, markup);
+            notification.silent = silent;
+
+            let source = format!("digest:{}:{}:{}", wallet.id.unwrap(), boundary, until);
+
+            if !telegram::queue_notification(&mut repo, notification_chat, &notification, source.as_str()) {
+                repo.set_wallet_digest_sent(wallet.id.unwrap(), boundary, until);
+            }
+        }
+    }
+}
+
+// a sink that does not answer in time is retried later, it does not hold up the others.
+const SINK_TIMEOUT: Duration = Duration::from_secs(30);
+
+// delivers the outbox. Every target gets its own task, one delivery at a time so its order is kept,
+// and a slow sink or a chat waiting on its rate limit does not block the others. Telegram flood
+// waits pause every telegram delivery for the requested time, transient errors back off up to
+// sinks::MAX_ATTEMPTS and chats that blocked the bot are marked inactive.
+pub async fn background_outbox_worker<R>(bot: AutoSend<Bot>, mut repo: R) where R: DataRepository {
+    let mut interval = time::interval(Duration::from_secs(1));
+    let mut telegram_paused_until = 0;
+    let mut in_flight: HashSet<String> = HashSet::new();
+    let (results_sender, mut results) = mpsc::unbounded_channel::<(QueuedDelivery, Result<(), SinkError>)>();
+
+    loop {
+        interval.tick().await;
+
+        while let Ok((delivery, result)) = results.try_recv() {
+            in_flight.remove(&format!("{}:{}", delivery.sink, delivery.target));
+
+            if let Err(SinkError::RetryAfter(secs)) = &result {
+                if delivery.sink == telegram::KIND {
+                    telegram_paused_until = now() + secs;
+                }
+            }
+
+            finish_delivery(&mut repo, delivery, result);
+        }
+
+        for delivery in repo.get_due_deliveries(now()) {
+            let key = format!("{}:{}", delivery.sink, delivery.target);
+
+            if in_flight.contains(&key) || (delivery.sink == telegram::KIND && telegram_paused_until > now()) {
+                continue;
+            }
+
+            let sink = match sinks::from_parts(&bot, &repo, delivery.sink.as_str(), delivery.target.as_str()) {
+                Some(s) => s,
+                None => {
+                    repo.remove_queued_delivery(delivery.id.unwrap());
+                    continue;
+                }
+            };
+
+            in_flight.insert(key);
+            let results_sender = results_sender.clone();
+
+            task::spawn(async move {
+                let result = match time::timeout(SINK_TIMEOUT, sink.deliver(delivery.payload.as_str())).await {
+                    Ok(r) => r,
+                    Err(_) => Err(SinkError::Transient(format!("no answer in {}s", SINK_TIMEOUT.as_secs()))),
+                };
+
+                results_sender.send((delivery, result)).unwrap_or_default();
+            });
+        }
+    }
+}
+
+fn finish_delivery<R>(repo: &mut R, mut delivery: QueuedDelivery, result: Result<(), SinkError>) where R: DataRepository {
+    match result {
+        Ok(_) => {
+            repo.remove_queued_delivery(delivery.id.unwrap());
+
+            // the last part of a split message completes its source.
+            if !delivery.source.is_empty() && !repo.has_queued_source(delivery.source.clone()) {
+                apply_source(repo, delivery.source.as_str());
+            }
+        }
+        Err(SinkError::RetryAfter(secs)) => {
+            logger!("-> {} asked to retry after {}s", delivery.sink, secs);
+
+            // rate limiting is not the delivery's fault, it does not count as an attempt.
+            delivery.next_attempt_at = now() + secs;
+            delivery.last_error = SinkError::RetryAfter(secs).to_string();
+
+            repo.update_queued_delivery(&delivery);
+        }
+        Err(SinkError::Blocked(e)) => {
+            logger!("-> chat {} is unreachable, marking it inactive: {}", delivery.target, e);
+
+            if let Ok(chat_id) = delivery.target.parse::<i64>() {
+                repo.set_chat_active(chat_id, false);
+            }
+
+            repo.remove_queued_deliveries(delivery.sink.clone(), delivery.target.clone());
+        }
+        Err(SinkError::Rejected(e)) => {
+            logger!("-> {} delivery to {} rejected: {}", delivery.sink, sinks::redact(delivery.target.as_str()), e);
+            repo.remove_queued_delivery(delivery.id.unwrap());
+
+            // it would be rejected the same way again, queueing the source anew would loop forever.
+            if !delivery.source.is_empty() && !repo.has_queued_source(delivery.source.clone()) {
+                apply_source(repo, delivery.source.as_str());
+            }
+        }
+        Err(SinkError::Transient(e)) if delivery.attempts + 1 >= sinks::MAX_ATTEMPTS => {
+            // the source is kept, held and digest workers queue it again.
+            logger!("-> giving up {} delivery to {} after {} attempts: {}", delivery.sink, sinks::redact(delivery.target.as_str()),
+                    delivery.attempts + 1, e);
+            repo.remove_queued_delivery(delivery.id.unwrap());
+        }
+        Err(SinkError::Transient(e)) => {
+            delivery.attempts += 1;
+            delivery.next_attempt_at = now() + sinks::backoff(delivery.attempts);
+            delivery.last_error = e;
+
+            repo.update_queued_delivery(&delivery);
+        }
+    }
+}
+
+// see QueuedDelivery::source.
+fn apply_source<R>(repo: &mut R, source: &str) where R: DataRepository {
+    match source.split(':').collect::<Vec<&str>>().as_slice() {
+        ["held", _, ids] => {
+            for id in ids.split(',').filter_map(|id| id.parse::<i64>().ok()) {
+                repo.remove_held_notification(id);
+            }
+        }
+        ["digest", wallet_id, boundary, until] => {
+            if let (Ok(wallet_id), Ok(boundary), Ok(until)) = (wallet_id.parse::<i64>(), boundary.parse::<i64>(), until.parse::<i64>()) {
+                repo.set_wallet_digest_sent(wallet_id, boundary, until);
+            }
+        }
+        _ => {}
+    }
+}
+
+// sends what was held back as one digest per chat once nothing holds it anymore.
+pub async fn background_held_worker<R>(mut repo: R) where R: DataRepository {
+    let markup = Markup::from_config(&AppConfig::from_args());
+    let mut interval = time::interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        let held = repo.get_all_held_notifications();
+
+        if held.is_empty() {
+            continue;
+        }
+
+        let users = repo.get_all_user();
+        let mut digests: Vec<(i64, Vec<HeldNotification>)> = vec![];
+
+        for item in held {
+            let user = match users.iter().find(|u| u.id == Some(item.user_id)) {
+                Some(u) if !u.banned => u,
+                _ => continue,
+            };
+
+            if let Delivery::Hold = delivery_for(user, &repo.get_user_mutes(item.user_id), item.wallet_id, "", now()) {
+                continue;
+            }
+
+            // queued held digests delete their items once delivered.
+            if repo.has_queued_source(format!("held:{}:", item.chat_id)) {
+                continue;
+            }
+
+            match digests.iter_mut().find(|(chat, _)| *chat == item.chat_id) {
+                Some((_, items)) => items.push(item),
+                None => digests.push((item.chat_id, vec![item])),
+            }
+        }
+
+        for (chat, items) in digests {
+            let mut lines = vec![markup.bold(format!("While notifications were held ({}):", items.len()).as_str())];
+            lines.extend(items.iter().map(|i| markup.escape(i.text.as_str())));
+
+            let ids = items.iter().map(|i| i.id.unwrap().to_string()).collect::<Vec<String>>();
+            let source = format!("held:{}:{}", chat, ids.join(","));
+            let mut queued = false;
+
+            for text in split_message(lines.join("\n").as_str(), MESSAGE_LIMIT) {
+                let notification = Notification {
+                    text,
+                    parse_mode: markup.parse_mode(),
+                    keyboard: None,
+                    silent: false,
+                };
+
+                queued |= telegram::queue_notification(&mut repo, ChatId(chat), &notification, source.as_str());
+            }
+
+            // nothing is sent to inactive chats, the items are dropped right away.
+            if !queued {
+                apply_source(&mut repo, source.as_str());
+            }
+        }
+    }
+}
+
+// reverse lookups queued by ens::label_addresses, a few per tick so the rpc node is not flooded.
+pub async fn background_ens_worker<R>(mut repo: R) where R: DataRepository {
+    let mut interval = time::interval(Duration::from_secs(10));
+
+    loop {
+        interval.tick().await;
+
+        for address in ens::next_lookups(20) {
+            if time::timeout(Duration::from_secs(30), ens::lookup_address(address.as_str(), &mut repo)).await.is_err() {
+                logger!("ens lookup of {} timed out.", address);
+            }
+        }
+    }
+}
+
+pub async fn background_alert_worker<R>(mut repo: R) where R: DataRepository {
+    let markup = Markup::from_config(&AppConfig::from_args());
+    let mut interval = time::interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        // banned users keep their alerts, they are evaluated again after /unban.
+        let alerts = repo.get_all_alerts_with_user().into_iter()
+            .filter(|a| !a.user.as_ref().map(|u| u.banned).unwrap_or(false))
+            .collect::<Vec<_>>();
+        let mut addresses: Vec<String> = vec![];
+
+        for alert in alerts.iter() {
+            if !addresses.iter().any(|a| a.eq_ignore_ascii_case(&alert.address)) {
+                addresses.push(alert.address.clone());
+            }
+        }
+
+        for address in addresses {
+            let balances = fetch_wallet_balances(address.as_str(), &mut repo).await;
+
+            if balances.is_empty() {
+                continue;
+            }
+
+            for alert in alerts.iter().filter(|a| a.address.eq_ignore_ascii_case(&address)) {
+                // a token whose balance could not be fetched is left for the next tick rather than read as empty.
+                let balance = match balances.iter()
+                    .find(|b| b.token.eq_ignore_ascii_case(&alert.token) || b.contract.eq_ignore_ascii_case(&alert.token)) {
+                    Some(b) => format_units(&b.amount, b.decimal).parse::<f64>().unwrap_or(0f64),
+                    None => continue,
+                };
+
+                if !alert.triggered && alert.is_crossed(balance) {
+                    repo.set_alert_triggered(alert.id.unwrap(), true);
+
+                    let chat_id = alert.user.as_ref().unwrap().chat_id.parse::<i64>().unwrap();
+                    let text = format!("Alert: {} balance of {} is {} {} ({}).",
+                                       alert.token, alert.address, alert.direction, alert.threshold, balance);
+
+                    telegram::queue_notification(&mut repo, ChatId(chat_id), &Notification::plain(text.as_str(), markup), "");
+                } else if alert.triggered && alert.is_rearmed(balance) {
+                    repo.set_alert_triggered(alert.id.unwrap(), false);
+                }
+            }
+        }
+    }
+}
+
+// release notes a user has not seen yet, once. Users that turned announcements off are only marked as up to date.
+pub async fn notice_release_notes<R>(mut repo: R) where R: DataRepository {
+    let markup = Markup::from_config(&AppConfig::from_args());
+    let latest = release_notes::latest();
+
+    for user in repo.get_all_user().into_iter().filter(|u| u.release_seen < latest) {
+        if let (true, Ok(chat_id)) = (user.announcements, user.chat_id.parse::<i64>()) {
+            for notes in release_notes::unseen(user.release_seen) {
+                telegram::queue_notification(&mut repo, ChatId(chat_id), &Notification::plain(notes, markup), "");
+            }
+        }
+
+        repo.set_user_release_seen(user.id.unwrap(), latest);
+    }
+}
+
+pub fn is_banned(chat_id: ChatId) -> bool {
+    let mut repo = SqliteDb::get_connection();
+    let banned = repo.get_user(chat_id.0).map(|u| u.banned).unwrap_or(false);
+    repo.drop();
+
+    banned
+}
+
+// admins are the telegram user ids passed with --admins.
+pub fn is_admin(message: &Message) -> bool {
+    let admins = AppConfig::from_args().admins;
+
+    message.from().map(|u| admins.contains(&u.id.0)).unwrap_or(false)
+}
+
+pub async fn start_previous_workers<R>(bot: AutoSend<Bot>, mut repo: R) where R: DataRepository {
+    let markup = Markup::from_config(&AppConfig::from_args());
     let wallets = repo.get_all_wallets_with_user();
 
     logger!("Starting previous workers...");
@@ -204,6 +1194,219 @@ pub async fn start_previous_workers<R>(bot: AutoSend<Bot>, repo: R) where R: Dat
                                                  wallet_address, user.id.unwrap(), &mut repo).await;
         });
 
-        bot.send_message(ChatId(user_id), format!("Worker for {} wallet started.", wallet.address).as_str()).await.unwrap();
+        telegram::queue_notification(&mut repo, ChatId(user_id),
+                                     &Notification::plain(format!("Worker for {} wallet started.", wallet.address).as_str(), markup), "");
+    }
+
+    for watch in repo.get_all_token_watches_with_user() {
+        let user = match watch.user {
+            Some(u) => {
+                u
+            }
+            None => {
+                continue;
+            }
+        };
+
+        let clone_bot = bot.clone();
+        let chat_id = user.chat_id.parse::<i64>().unwrap();
+        let contract = watch.contract.clone();
+
+        task::spawn(async move {
+            let mut repo = SqliteDb::get_connection();
+            background_token_worker::<SqliteDb>(&clone_bot, ChatId(chat_id), contract, user.id.unwrap(), &mut repo).await;
+        });
+    }
+
+    for watch in repo.get_all_event_watches_with_user() {
+        let user = match watch.user {
+            Some(u) => {
+                u
+            }
+            None => {
+                continue;
+            }
+        };
+
+        let chat_id = user.chat_id.parse::<i64>().unwrap();
+        let watch_id = watch.id.unwrap();
+
+        task::spawn(async move {
+            let mut repo = SqliteDb::get_connection();
+            background_event_worker::<SqliteDb>(ChatId(chat_id), watch_id, &mut repo).await;
+        });
+    }
+}
+
+// whether the user may change what the chat tracks, everyone may in a private chat. The role is
+// remembered per member for a while so telegram is not asked on every command.
+pub async fn has_manage_role(bot: &AutoSend<Bot>, chat: &teloxide::types::Chat, user_id: UserId) -> bool {
+    if chat.is_private() {
+        return true;
+    }
+
+    let config = AppConfig::from_args();
+
+    let mut db = SqliteDb::get_connection();
+    let cached = db.get_member(chat.id.0, user_id.0 as i64).filter(|m| m.is_fresh(now()));
+    db.drop();
+
+    if let Some(member) = cached {
+        return member.may_manage(config.manage_role.as_str());
+    }
+
+    let member = match bot.get_chat_member(chat.id, user_id).await {
+        Ok(m) => Member::from_telegram(chat.id.0, user_id.0 as i64, &m.kind, now()),
+        Err(_) => return false,
+    };
+    let allowed = member.may_manage(config.manage_role.as_str());
+
+    let mut db = SqliteDb::get_connection();
+    db.set_member(member);
+    db.drop();
+
+    allowed
+}
+
+// text of a document attached to the message or to the message it replies to.
+pub async fn download_reply_document(bot: &AutoSend<Bot>, message: &Message) -> Option<String> {
+    let document = message.document()
+        .or_else(|| message.reply_to_message().and_then(|m| m.document()))?;
+
+    let file = bot.get_file(document.file_id.clone()).await.ok()?;
+
+    let mut content = vec![];
+    bot.inner().download_file(file.file_path.as_str(), &mut content).await.ok()?;
+
+    String::from_utf8(content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // eip-55 reference vectors.
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn checksum_vectors_round_trip() {
+        for address in CHECKSUMMED {
+            assert!(valid_eth_address(address), "{}", address);
+            assert_eq!(to_checksum_address(address.to_ascii_lowercase().as_str()), address);
+            assert_eq!(to_checksum_address(address.to_ascii_uppercase().replacen("0X", "0x", 1).as_str()), address);
+        }
+
+        assert_eq!(to_checksum_address("0x52908400098527886E0F7030069857D2E4169EE7"), "0x52908400098527886E0F7030069857D2E4169EE7");
+        assert_eq!(to_checksum_address("0xde709f2102306220921060314715629080e2fb77"), "0xde709f2102306220921060314715629080e2fb77");
+    }
+
+    #[test]
+    fn single_case_addresses_carry_no_checksum() {
+        for address in CHECKSUMMED {
+            assert!(valid_eth_address(address.to_ascii_lowercase().as_str()));
+            assert!(valid_eth_address(address.to_ascii_uppercase().replacen("0X", "0x", 1).as_str()));
+        }
+    }
+
+    #[test]
+    fn bad_checksums_and_malformed_addresses_are_rejected() {
+        assert!(!valid_eth_address("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(!valid_eth_address("0xD1220A0CF47c7B9Be7A2E6BA89F429762e7b9aDb"));
+        assert!(!valid_eth_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe"));
+        assert!(!valid_eth_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAedd"));
+        assert!(!valid_eth_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg"));
+        assert!(!valid_eth_address("005aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+    }
+
+    #[test]
+    fn truncate_bytes_keeps_whole_characters() {
+        assert_eq!(truncate_bytes("USDC", 10), "USDC");
+        assert_eq!(truncate_bytes("USDC", 2), "US");
+        assert_eq!(truncate_bytes("Ünïcode", 2), "Ü");
+        assert_eq!(truncate_bytes("🦄🦄", 5), "🦄");
+        assert_eq!(truncate_bytes("🦄", 3), "");
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("30m"), Some(1800));
+        assert_eq!(parse_duration(" 2H "), Some(7200));
+        assert_eq!(parse_duration("1w"), Some(604800));
+        assert_eq!(parse_duration("0h"), None);
+        assert_eq!(parse_duration("5y"), None);
+        assert_eq!(parse_duration("m"), None);
+    }
+
+    #[test]
+    fn parse_duration_rejects_bad_input() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("2é"), None);
+        assert_eq!(parse_duration("é"), None);
+        assert_eq!(parse_duration("9223372036854775807w"), None);
+    }
+
+    #[test]
+    fn hex_to_decimal_handles_uint256() {
+        assert_eq!(hex_to_decimal("0x"), Some("0".to_string()));
+        assert_eq!(hex_to_decimal("0x00000000000000000000000000000000000000000000000000000000000003e8"), Some("1000".to_string()));
+        assert_eq!(hex_to_decimal(&format!("0x{}", "f".repeat(64))),
+                   Some("115792089237316195423570985008687907853269984665640564039457584007913129639935".to_string()));
+        assert_eq!(hex_to_decimal("0xzz"), None);
+    }
+
+    #[test]
+    fn format_balances() {
+        assert_eq!(format_units("1500000000000000000", 18), "1.5");
+        assert_eq!(format_units("0", 6), "0");
+        assert_eq!(format_units("000123", 2), "1.23");
+        assert_eq!(format_amount("1234567000000", 6), "1,234,567");
+        assert_eq!(format_amount("1234567891234567", 6), "1,234,567,891.234567");
+        assert_eq!(format_amount("42", 18), "0.000000000000000042");
+        assert_eq!(format_amount("123456789", 18), "0.0000000001234");
+    }
+
+    fn log_at(block: i64) -> EtherScanLogDetail {
+        serde_json::from_value(serde_json::json!({
+            "address": "0xc", "topics": [], "data": "0x", "blockNumber": format!("0x{:x}", block), "timeStamp": "0x0",
+            "gasPrice": "0x0", "gasUsed": "0x0", "logIndex": "0x0", "transactionHash": "0xabc", "transactionIndex": "0x0"
+        })).unwrap()
+    }
+
+    #[test]
+    fn truncated_log_pages_keep_only_complete_blocks() {
+        let logs = vec![log_at(101), log_at(102), log_at(103), log_at(103)];
+        let (last_block, logs) = complete_blocks(logs, 100).unwrap();
+
+        assert_eq!(last_block, 102);
+        assert_eq!(logs.iter().map(|l| hex_to_i64(l.blockNumber.as_str())).collect::<Vec<i64>>(), vec![101, 102]);
+    }
+
+    #[test]
+    fn truncated_log_pages_of_one_block_wait_for_the_next_poll() {
+        assert!(complete_blocks(vec![log_at(101), log_at(101)], 100).is_none());
+        assert!(complete_blocks(vec![], 100).is_none());
+    }
+
+    #[test]
+    fn split_message_on_lines() {
+        assert_eq!(split_message("", 10), vec![""]);
+        assert_eq!(split_message("one\ntwo", 10), vec!["one\ntwo"]);
+        assert_eq!(split_message("one\ntwo\nthree", 8), vec!["one\ntwo", "three"]);
+    }
+
+    #[test]
+    fn split_message_cuts_long_lines() {
+        assert_eq!(split_message("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(split_message("ab\nüüüüü", 4), vec!["ab", "üüüü", "ü"]);
+
+        let text = vec!["x".repeat(100); 100].join("\n");
+        let parts = split_message(text.as_str(), MESSAGE_LIMIT);
+        assert!(parts.iter().all(|p| p.chars().count() <= MESSAGE_LIMIT));
+        assert_eq!(parts.join("\n"), text);
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use crypto::{sha3::Sha3, digest::Digest};
+use structopt::StructOpt;
+use crate::{AppConfig, DataRepository};
+use crate::abi::decode_output;
+use crate::common::{now, to_checksum_address, valid_eth_address};
+use crate::models::ens_record::EnsRecord;
+use crate::providers::rpc;
+use crate::renderer::Labels;
+
+const REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+const RESOLVER_SELECTOR: &str = "0178b8bf";
+const ADDR_SELECTOR: &str = "3b3b57de";
+const NAME_SELECTOR: &str = "691f3431";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+pub const ENS_TTL: i64 = 86400;
+const MAX_PENDING_LOOKUPS: usize = 1000;
+
+static PENDING_LOOKUPS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+// the top level label must be alphabetic so amounts like `1.5` are not taken for names.
+pub fn is_ens_name(name: &str) -> bool {
+    match name.rsplit_once('.') {
+        Some((rest, tld)) => !rest.is_empty() && !rest.starts_with('.') && tld.len() >= 2
+            && tld.chars().all(|c| c.is_ascii_alphabetic()) && !name.chars().any(|c| c.is_whitespace()),
+        None => false,
+    }
+}
+
+// uts-46 mapping as ens names are registered: case folding, NFC and the punycode form decoded.
+// None for names uts-46 disallows.
+pub fn normalize(name: &str) -> Option<String> {
+    match idna::domain_to_unicode(name) {
+        (normalized, Ok(())) => Some(normalized),
+        _ => None,
+    }
+}
+
+// expects a name already passed through normalize().
+pub fn namehash(name: &str) -> [u8; 32] {
+    let mut node = [0u8; 32];
+
+    for label in name.rsplit('.').filter(|l| !l.is_empty()) {
+        let mut label_hash = [0u8; 32];
+        let mut hasher = Sha3::keccak256();
+        hasher.input(label.as_bytes());
+        hasher.result(&mut label_hash);
+
+        let mut hasher = Sha3::keccak256();
+        hasher.input(&node);
+        hasher.input(&label_hash);
+        hasher.result(&mut node);
+    }
+
+    node
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+async fn call(rpc_url: &str, to: &str, selector: &str, node: &[u8; 32]) -> Option<String> {
+    let resp = rpc::eth_call(rpc_url, to, format!("0x{}{}", selector, to_hex(node)).as_str()).await.ok()?;
+
+    resp.result.filter(|r| r.len() > 2)
+}
+
+async fn resolver(rpc_url: &str, node: &[u8; 32]) -> Option<String> {
+    let result = call(rpc_url, REGISTRY, RESOLVER_SELECTOR, node).await?;
+    let resolver = decode_output("address", result.as_str());
+
+    if resolver == ZERO_ADDRESS || resolver == "?" { None } else { Some(resolver) }
+}
+
+async fn forward(rpc_url: &str, name: &str) -> Option<String> {
+    let node = namehash(normalize(name)?.as_str());
+    let resolver = resolver(rpc_url, &node).await?;
+    let result = call(rpc_url, resolver.as_str(), ADDR_SELECTOR, &node).await?;
+    let address = decode_output("address", result.as_str());
+
+    if address == ZERO_ADDRESS || !valid_eth_address(address.as_str()) { None } else { Some(to_checksum_address(address.as_str())) }
+}
+
+// the primary name only counts when it resolves back to the same address.
+async fn reverse(rpc_url: &str, address: &str) -> Option<String> {
+    let node = namehash(format!("{}.addr.reverse", address.to_ascii_lowercase().trim_start_matches("0x")).as_str());
+    let resolver = resolver(rpc_url, &node).await?;
+    let result = call(rpc_url, resolver.as_str(), NAME_SELECTOR, &node).await?;
+    let name = decode_output("string", result.as_str());
+
+    if name.is_empty() || name == "?" {
+        return None;
+    }
+
+    match forward(rpc_url, name.as_str()).await {
+        Some(a) if a.eq_ignore_ascii_case(address) => Some(name),
+        _ => None,
+    }
+}
+
+// takes the repository mutably so the returned future stays Send while holding it.
+pub async fn resolve_name<R>(name: &str, repo: &mut R) -> Option<String> where R: DataRepository {
+    let name = normalize(name)?;
+
+    if let Some(record) = repo.get_ens_record(name.clone(), false) {
+        if now() - record.updated_at < ENS_TTL {
+            return if record.address.is_empty() { None } else { Some(record.address) };
+        }
+    }
+
+    let config = AppConfig::from_args();
+    let address = forward(config.rpc_url.as_str(), name.as_str()).await;
+
+    repo.set_ens_record(EnsRecord::new(name, address.clone().unwrap_or_default(), false, now(), None));
+
+    address
+}
+
+pub async fn lookup_address<R>(address: &str, repo: &mut R) -> Option<String> where R: DataRepository {
+    if let Some(record) = repo.get_ens_record(address.to_string(), true) {
+        if now() - record.updated_at < ENS_TTL {
+            return if record.name.is_empty() { None } else { Some(record.name) };
+        }
+    }
+
+    let config = AppConfig::from_args();
+    let name = reverse(config.rpc_url.as_str(), address).await;
+
+    repo.set_ens_record(EnsRecord::new(name.clone().unwrap_or_default(), to_checksum_address(address), true, now(), None));
+
+    name
+}
+
+// replaces the word at `position` with its address when it is an ens name. Other words are kept
+// as typed, so a contact named `Coinbase.com` stays a name.
+pub async fn resolve_arg<R>(args: &str, position: usize, repo: &mut R) -> Result<String, String> where R: DataRepository {
+    let mut words = args.split_whitespace().map(|w| w.to_string()).collect::<Vec<String>>();
+
+    if let Some(word) = words.get_mut(position).filter(|w| is_ens_name(w)) {
+        match resolve_name(word, repo).await {
+            Some(address) => *word = address,
+            None => return Err(format!("Could not resolve {}.", word)),
+        }
+    }
+
+    Ok(words.join(" "))
+}
+
+// labels from cached reverse records only, so polling never waits on the rpc node. Addresses without
+// a fresh record are looked up by background_ens_worker and get their label on a later notification.
+// addresses that already have a label keep it.
+pub fn label_addresses<R>(labels: &mut Labels, addresses: &[&str], repo: &R) where R: DataRepository {
+    for address in addresses {
+        if labels.has(address) || !valid_eth_address(address) {
+            continue;
+        }
+
+        let record = repo.get_ens_record(address.to_string(), true);
+
+        if record.as_ref().map(|r| now() - r.updated_at >= ENS_TTL).unwrap_or(true) {
+            queue_lookup(address);
+        }
+
+        if let Some(r) = record.filter(|r| !r.name.is_empty()) {
+            labels.insert(address, r.name);
+        }
+    }
+}
+
+fn queue_lookup(address: &str) {
+    let mut pending = PENDING_LOOKUPS.get_or_init(|| Mutex::new(VecDeque::new())).lock().unwrap();
+
+    if pending.len() < MAX_PENDING_LOOKUPS && !pending.iter().any(|a| a.eq_ignore_ascii_case(address)) {
+        pending.push_back(address.to_string());
+    }
+}
+
+// the next addresses waiting for a reverse lookup, oldest first.
+pub fn next_lookups(count: usize) -> Vec<String> {
+    let mut pending = PENDING_LOOKUPS.get_or_init(|| Mutex::new(VecDeque::new())).lock().unwrap();
+    let count = count.min(pending.len());
+
+    pending.drain(..count).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namehash_vectors() {
+        assert_eq!(to_hex(&namehash("")), "0".repeat(64));
+        assert_eq!(to_hex(&namehash("eth")), "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae");
+        assert_eq!(to_hex(&namehash("foo.eth")), "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f");
+    }
+
+    #[test]
+    fn names_are_normalized_before_hashing() {
+        assert_eq!(normalize("Foo.ETH"), Some("foo.eth".to_string()));
+        assert_eq!(namehash(normalize("Foo.ETH").unwrap().as_str()), namehash("foo.eth"));
+        assert_eq!(normalize("xn--mnchen-3ya.eth"), Some("münchen.eth".to_string()));
+    }
+
+    #[test]
+    fn only_names_with_an_alphabetic_tld() {
+        assert!(is_ens_name("vitalik.eth"));
+        assert!(is_ens_name("Coinbase.com"));
+        assert!(!is_ens_name("1.5"));
+        assert!(!is_ens_name("eth"));
+        assert!(!is_ens_name(".eth"));
+    }
+}
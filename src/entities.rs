@@ -0,0 +1,75 @@
+use std::fs;
+use std::sync::OnceLock;
+use structopt::StructOpt;
+use crate::{AppConfig, logger};
+
+const BUNDLED: &str = include_str!("../data/known_addresses.csv");
+
+pub struct KnownEntity {
+    pub address: String,
+    pub name: String,
+    pub category: String,
+}
+
+fn parse(content: &str) -> Vec<KnownEntity> {
+    content.lines()
+        .map(|l| l.split(',').map(|f| f.trim()).collect::<Vec<&str>>())
+        .filter(|f| f.len() >= 2 && f[0].len() == 42 && f[0].starts_with("0x") && !f[1].is_empty())
+        .map(|f| KnownEntity {
+            address: f[0].to_string(),
+            name: f[1].to_string(),
+            category: f.get(2).map(|c| c.to_string()).unwrap_or_default(),
+        })
+        .collect()
+}
+
+static ENTITIES: OnceLock<Vec<KnownEntity>> = OnceLock::new();
+
+// entries of the --entities-file come first so an updated dataset overrides the bundled one.
+// Both are read once, a replaced file is picked up on the next restart.
+pub fn known_entities() -> &'static [KnownEntity] {
+    ENTITIES.get_or_init(|| {
+        let config = AppConfig::from_args();
+        let mut entities = vec![];
+
+        if let Some(path) = config.entities_file {
+            match fs::read_to_string(path.as_str()) {
+                Ok(content) => entities.extend(parse(content.as_str())),
+                Err(e) => logger!("Could not read entities file {}: {}", path, e),
+            }
+        }
+
+        entities.extend(parse(BUNDLED));
+
+        entities
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::valid_eth_address;
+
+    #[test]
+    fn bundled_dataset_parses() {
+        let entities = parse(BUNDLED);
+
+        assert_eq!(entities.len(), BUNDLED.lines().count() - 1);
+        assert!(entities.iter().all(|e| valid_eth_address(e.address.as_str())));
+        assert!(entities.iter().all(|e| !e.category.is_empty()));
+    }
+
+    #[test]
+    fn header_and_malformed_rows_are_skipped() {
+        let entities = parse("address,name,category\n\
+            0x28C6c06298d514Db089934071355E5743bf21d60 , Binance 14\n\
+            0x28C6c06298d514Db089934071355E5743bf21d6,Too short,exchange\n\
+            0xBE0eB53F46cd790Cd13851d5EFf43D12404d33E8,,exchange\n\
+            0xBE0eB53F46cd790Cd13851d5EFf43D12404d33E8");
+
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].address, "0x28C6c06298d514Db089934071355E5743bf21d60");
+        assert_eq!(entities[0].name, "Binance 14");
+        assert_eq!(entities[0].category, "");
+    }
+}
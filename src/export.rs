@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use crate::DataRepository;
+use crate::common::{format_timestamp, format_units, parse_date};
+use crate::models::transaction::{Transaction, TransactionFilter};
+use crate::models::wallet::Wallet;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRow {
+    pub wallet: String,
+    pub hash: String,
+    pub block: i64,
+    pub timestamp: i64,
+    pub date: String,
+    pub kind: String,
+    pub from: String,
+    pub to: String,
+    pub token: String,
+    pub amount: String,
+    pub received_token: String,
+    pub received_amount: String,
+    pub fee: String,
+    pub fiat_value: String,
+    pub fiat_currency: String,
+    pub status: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+// every stored transaction of the wallets, oldest first, amounts are exact decimal strings.
+pub fn export_rows<R>(repo: &R, wallets: &[Wallet], filter: &TransactionFilter) -> Vec<ExportRow> where R: DataRepository {
+    let mut rows = vec![];
+
+    for wallet in wallets {
+        // sqlite treats a negative limit as no limit.
+        let mut txs = repo.get_transactions(wallet.id.unwrap(), wallet.address.clone(), filter, -1, 0);
+        txs.reverse();
+
+        rows.extend(txs.iter().map(|trx| export_row(wallet, trx)));
+    }
+
+    rows
+}
+
+fn export_row(wallet: &Wallet, trx: &Transaction) -> ExportRow {
+    ExportRow {
+        wallet: wallet.address.clone(),
+        hash: trx.tx_hash.clone(),
+        block: trx.block_number,
+        timestamp: trx.timestamp,
+        date: if trx.timestamp != 0 { format_timestamp(trx.timestamp) } else { "".to_string() },
+        kind: trx.kind.clone(),
+        from: trx.from.clone(),
+        to: trx.to.clone(),
+        token: trx.token.clone(),
+        amount: format_units(&trx.amount, trx.token_decimal()),
+        received_token: trx.received_token.clone(),
+        received_amount: if trx.is_swap() { format_units(&trx.received_amount, trx.received_decimal) } else { "".to_string() },
+        fee: format_units(&trx.fee, 18),
+        fiat_value: if trx.has_fiat_value() { format!("{:.2}", trx.fiat_value) } else { "".to_string() },
+        fiat_currency: trx.fiat_currency.clone(),
+        status: trx.status,
+    }
+}
+
+pub fn serialize(rows: &[ExportRow], format: ExportFormat) -> Vec<u8> {
+    match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(rows).unwrap_or_default(),
+        ExportFormat::Csv => {
+            let mut csv = vec![csv_line(&["wallet", "hash", "block", "timestamp", "date", "kind", "from", "to", "token", "amount",
+                                          "received_token", "received_amount", "fee", "fiat_value", "fiat_currency", "status"])];
+
+            for row in rows {
+                csv.push(csv_line(&[
+                    row.wallet.as_str(), row.hash.as_str(), row.block.to_string().as_str(), row.timestamp.to_string().as_str(),
+                    row.date.as_str(), row.kind.as_str(), row.from.as_str(), row.to.as_str(), row.token.as_str(),
+                    row.amount.as_str(), row.received_token.as_str(), row.received_amount.as_str(), row.fee.as_str(),
+                    row.fiat_value.as_str(), row.fiat_currency.as_str(), row.status.to_string().as_str(),
+                ]));
+            }
+
+            csv.join("\n").into_bytes()
+        }
+    }
+}
+
+pub fn csv_line(fields: &[&str]) -> String {
+    fields.iter()
+        .map(|f| if f.contains(|c: char| c == ',' || c == '"' || c == '\n') { format!("\"{}\"", f.replace('"', "\"\"")) } else { f.to_string() })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// backs the `export` cli subcommand, returns the number of exported rows.
+pub fn cli_export<R>(repo: &R, address: &str, format: &str, from: Option<String>, to: Option<String>,
+                     output: String) -> Result<usize, String> where R: DataRepository {
+    let format = ExportFormat::parse(format).ok_or(format!("Unknown format {}", format))?;
+
+    let mut filter = TransactionFilter::default();
+
+    if let Some(f) = from {
+        filter.from = Some(parse_date(f.as_str()).ok_or(format!("Invalid date {}", f))?);
+    }
+
+    if let Some(t) = to {
+        filter.to = Some(parse_date(t.as_str()).ok_or(format!("Invalid date {}", t))? + 86399);
+    }
+
+    let wallets = if address == "all" {
+        repo.get_all_wallets_with_user()
+    } else {
+        match repo.get_wallet(None, address.to_string()) {
+            Some(w) => vec![w],
+            None => return Err(format!("Wallet {} is not tracked", address)),
+        }
+    };
+
+    let rows = export_rows(repo, &wallets, &filter);
+    let content = serialize(&rows, format);
+
+    std::fs::write(output.as_str(), content).map_err(|e| e.to_string())?;
+
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+
+    fn swap() -> Transaction {
+        let mut trx = Transaction::new(WALLET.to_string(), "0x0000000000000000000000000000000000000001".to_string(),
+                                       "1500000000000000000".to_string(), "0xabc".to_string(), "ETH".to_string(), 1, 0, None, None);
+        trx.kind = Transaction::SWAP.to_string();
+        trx.received_token = "USD Coin".to_string();
+        trx.received_amount = "2500000000".to_string();
+        trx.received_decimal = 6;
+        trx.block_number = 100;
+        trx.timestamp = 1704067200;
+        trx.fee = "420000000000000".to_string();
+        trx.fiat_value = 3000.456;
+        trx.fiat_currency = "USD".to_string();
+        trx
+    }
+
+    #[test]
+    fn rows_hold_exact_amounts() {
+        let row = export_row(&Wallet::new(WALLET.to_string(), 1, Some(1)), &swap());
+
+        assert_eq!(row.amount, "1.5");
+        assert_eq!(row.received_amount, "2500");
+        assert_eq!(row.fee, "0.00042");
+        assert_eq!(row.fiat_value, "3000.46");
+        assert_eq!(row.date, format_timestamp(1704067200));
+        assert!(row.status);
+    }
+
+    #[test]
+    fn transfers_leave_swap_and_fiat_columns_empty() {
+        let mut trx = swap();
+        trx.kind = Transaction::TRANSFER.to_string();
+        trx.fiat_currency = "".to_string();
+        trx.timestamp = 0;
+        trx.status = false;
+
+        let row = export_row(&Wallet::new(WALLET.to_string(), 1, Some(1)), &trx);
+
+        assert_eq!((row.received_amount.as_str(), row.fiat_value.as_str(), row.date.as_str()), ("", "", ""));
+        assert!(!row.status);
+    }
+
+    #[test]
+    fn csv_quotes_only_when_needed() {
+        assert_eq!(csv_line(&["a", "b,c", "say \"hi\"", "two\nlines"]), "a,\"b,c\",\"say \"\"hi\"\"\",\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_and_json_hold_every_row() {
+        let rows = vec![export_row(&Wallet::new(WALLET.to_string(), 1, Some(1)), &swap()); 2];
+
+        let csv = String::from_utf8(serialize(&rows, ExportFormat::Csv)).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("wallet,hash,block"));
+        assert_eq!(lines[1], format!("{w},0xabc,100,1704067200,{d},swap,{w},0x0000000000000000000000000000000000000001,ETH,1.5,\
+                                      USD Coin,2500,0.00042,3000.46,USD,true", w = WALLET, d = format_timestamp(1704067200)));
+
+        let json = serde_json::from_slice::<Vec<ExportRow>>(serialize(&rows, ExportFormat::Json).as_slice()).unwrap();
+        assert_eq!(json.len(), 2);
+        assert_eq!(json[0].received_amount, "2500");
+    }
+}
@@ -5,30 +5,87 @@ mod models;
 mod repositories;
 mod commands;
 mod command_handler;
+mod callback_handler;
 mod common;
+mod providers;
+mod prices;
+mod abi;
+mod renderer;
+mod templates;
+mod export;
+mod tax;
+mod ens;
+mod entities;
+mod sinks;
+mod throttle;
+mod release_notes;
 
-use crate::app_config::AppConfig;
+use crate::app_config::{AppConfig, CliCommand};
 use crate::repositories::{DataRepository};
 use crate::commands::{Command};
 use structopt::StructOpt;
-use teloxide::{prelude::*, utils::command::BotCommands};
+use teloxide::{prelude::*};
 use crate::command_handler::{handler};
-use crate::common::{notice_changelog, start_previous_workers};
+use crate::callback_handler::{callback_handler};
+use crate::common::{background_alert_worker, background_digest_worker, background_ens_worker, background_held_worker, background_outbox_worker, notice_release_notes, start_previous_workers};
 use crate::repositories::sqlite_db::SqliteDb;
 
 #[tokio::main]
 async fn main() {
     let app_config = AppConfig::from_args();
 
+    if let Some(CliCommand::Export { address, format, from, to, output }) = app_config.cmd.clone() {
+        let db = SqliteDb::get_connection();
+
+        match export::cli_export(&db, address.as_str(), format.as_str(), from, to, output) {
+            Ok(count) => eprintln!("{} transactions exported.", count),
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    if app_config.bot_token.is_empty() || app_config.ether_api.is_empty() {
+        eprintln!("--bot-token and --ether-api are required to run the bot.");
+        std::process::exit(1);
+    }
+
+    // receivers could not tell our events from forged ones.
+    if app_config.webhook_url.is_some() && app_config.webhook_secret.as_deref().unwrap_or_default().is_empty() {
+        eprintln!("--webhook-url requires --webhook-secret.");
+        std::process::exit(1);
+    }
+
     logger!("Starting bot...");
     let bot = Bot::new(app_config.bot_token).auto_send();
 
     let worker_db = SqliteDb::get_connection();
     let notice_db = SqliteDb::get_connection();
+    let alert_db = SqliteDb::get_connection();
+    let held_db = SqliteDb::get_connection();
+    let digest_db = SqliteDb::get_connection();
+    let outbox_db = SqliteDb::get_connection();
+    let ens_db = SqliteDb::get_connection();
     let bot_clone = bot.clone();
 
-    notice_changelog::<SqliteDb>(bot_clone.clone(), notice_db).await;
-    start_previous_workers::<SqliteDb>(bot_clone, worker_db).await;
+    notice_release_notes::<SqliteDb>(notice_db).await;
+    start_previous_workers::<SqliteDb>(bot_clone.clone(), worker_db).await;
+    tokio::task::spawn(background_held_worker::<SqliteDb>(held_db));
+    tokio::task::spawn(background_digest_worker::<SqliteDb>(digest_db));
+    tokio::task::spawn(background_outbox_worker::<SqliteDb>(bot_clone, outbox_db));
+    tokio::task::spawn(background_alert_worker::<SqliteDb>(alert_db));
+    tokio::task::spawn(background_ens_worker::<SqliteDb>(ens_db));
+
+    let update_handler = dptree::entry()
+        .branch(Update::filter_message().filter_command::<Command>().endpoint(handler))
+        .branch(Update::filter_callback_query().endpoint(callback_handler));
 
-    teloxide::commands_repl(bot, handler, Command::ty()).await;
+    Dispatcher::builder(bot, update_handler)
+        .build()
+        .setup_ctrlc_handler()
+        .dispatch()
+        .await;
 }
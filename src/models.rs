@@ -1,4 +1,21 @@
 pub mod user;
 pub mod transaction;
 pub mod wallet;
-pub mod etherscan;
\ No newline at end of file
+pub mod etherscan;
+pub mod transfer;
+pub mod balance;
+pub mod alert;
+pub mod token_watch;
+pub mod event_watch;
+pub mod mute;
+pub mod rpc;
+pub mod ens_record;
+pub mod contact;
+pub mod chat;
+pub mod held_notification;
+pub mod queued_delivery;
+pub mod sink_config;
+pub mod email_address;
+pub mod poll_status;
+pub mod stats;
+pub mod member;
@@ -0,0 +1,118 @@
+use sqlite::Statement;
+use crate::models::user::User;
+
+pub struct Alert {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub address: String,
+    pub token: String,
+    pub direction: String,
+    pub threshold: f64,
+    pub triggered: bool,
+    pub user: Option<User>,
+}
+
+impl Alert {
+    pub const COLUMNS: usize = 7;
+    pub const BELOW: &'static str = "below";
+    pub const ABOVE: &'static str = "above";
+
+    // share of the threshold the balance has to move back before the alert can fire again.
+    pub const HYSTERESIS: f64 = 0.05;
+
+    pub fn new(user_id: i64, address: String, token: String, direction: String, threshold: f64, id: Option<i64>) -> Self {
+        Alert {
+            id,
+            user_id,
+            address,
+            token,
+            direction,
+            threshold,
+            triggered: false,
+            user: None,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        let mut alert = Alert::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<String>(4).unwrap(),
+            statement.read::<f64>(5).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        );
+
+        alert.triggered = statement.read::<i64>(6).unwrap() != 0;
+
+        alert
+    }
+
+    pub fn is_crossed(&self, balance: f64) -> bool {
+        match self.direction.as_str() {
+            Alert::BELOW => balance < self.threshold,
+            _ => balance > self.threshold,
+        }
+    }
+
+    pub fn is_rearmed(&self, balance: f64) -> bool {
+        match self.direction.as_str() {
+            Alert::BELOW => balance >= self.threshold * (1f64 + Alert::HYSTERESIS),
+            _ => balance <= self.threshold * (1f64 - Alert::HYSTERESIS),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("#{id}: {a} {tn} {d} {th}",
+                id = self.id.unwrap_or(0), a = self.address, tn = self.token, d = self.direction, th = self.threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(direction: &str, threshold: f64) -> Alert {
+        Alert::new(1, "0x1".to_string(), "ETH".to_string(), direction.to_string(), threshold, Some(1))
+    }
+
+    #[test]
+    fn below_fires_under_the_threshold() {
+        let alert = alert(Alert::BELOW, 100f64);
+
+        assert!(alert.is_crossed(99.9));
+        assert!(!alert.is_crossed(100f64));
+        assert!(!alert.is_crossed(150f64));
+    }
+
+    #[test]
+    fn above_fires_over_the_threshold() {
+        let alert = alert(Alert::ABOVE, 100f64);
+
+        assert!(alert.is_crossed(100.1));
+        assert!(!alert.is_crossed(100f64));
+        assert!(!alert.is_crossed(50f64));
+    }
+
+    #[test]
+    fn triggered_alerts_stay_armed_inside_the_band() {
+        let below = alert(Alert::BELOW, 100f64);
+        assert!(!below.is_rearmed(100f64));
+        assert!(!below.is_rearmed(104.9));
+        assert!(below.is_rearmed(105f64));
+
+        let above = alert(Alert::ABOVE, 100f64);
+        assert!(!above.is_rearmed(100f64));
+        assert!(!above.is_rearmed(95.1));
+        assert!(above.is_rearmed(95f64));
+    }
+
+    #[test]
+    fn zero_threshold_rearms_at_zero() {
+        let above = alert(Alert::ABOVE, 0f64);
+
+        assert!(above.is_crossed(0.01));
+        assert!(!above.is_rearmed(0.01));
+        assert!(above.is_rearmed(0f64));
+    }
+}
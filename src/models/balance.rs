@@ -0,0 +1,47 @@
+use sqlite::Statement;
+use crate::common::format_amount;
+
+pub struct Balance {
+    pub id: Option<i64>,
+    pub address: String,
+    pub contract: String,
+    pub token: String,
+    pub decimal: i64,
+    pub amount: String,
+    pub updated_at: i64,
+}
+
+impl Balance {
+    pub fn new(address: String, contract: String, token: String, decimal: i64, amount: String, updated_at: i64,
+               id: Option<i64>) -> Self {
+        Balance {
+            id,
+            address,
+            contract,
+            token,
+            decimal,
+            amount,
+            updated_at,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        Balance::new(
+            statement.read::<String>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<i64>(4).unwrap(),
+            statement.read::<String>(5).unwrap(),
+            statement.read::<i64>(6).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.contract.is_empty()
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("{a} {tn}", a = format_amount(&self.amount, self.decimal), tn = self.token)
+    }
+}
@@ -0,0 +1,63 @@
+use sqlite::Statement;
+
+// a telegram chat the bot delivers to, kept apart from the users that own watchlists so
+// a wallet can be routed to a channel that never sent /start.
+#[derive(Clone)]
+pub struct Chat {
+    pub id: Option<i64>,
+    pub chat_id: i64,
+    pub kind: String,
+    pub title: String,
+    pub added_by: i64,
+    // false once telegram reported the bot blocked or removed, set back by the next /start.
+    pub active: bool,
+}
+
+impl Chat {
+    pub const PRIVATE: &'static str = "private";
+    pub const GROUP: &'static str = "group";
+    pub const CHANNEL: &'static str = "channel";
+
+    pub fn new(chat_id: i64, kind: String, title: String, added_by: i64, id: Option<i64>) -> Self {
+        Chat {
+            id,
+            chat_id,
+            kind,
+            title,
+            added_by,
+            active: true,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        let mut chat = Chat::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<i64>(4).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        );
+
+        chat.active = statement.read::<i64>(5).unwrap() == 1;
+
+        chat
+    }
+
+    pub fn from_telegram(chat: &teloxide::types::Chat, added_by: i64) -> Self {
+        let kind = if chat.is_private() {
+            Chat::PRIVATE
+        } else if chat.is_channel() {
+            Chat::CHANNEL
+        } else {
+            Chat::GROUP
+        };
+
+        let title = chat.title().or_else(|| chat.username()).unwrap_or_default().to_string();
+
+        Chat::new(chat.id.0, kind.to_string(), title, added_by, None)
+    }
+
+    pub fn to_string(&self) -> String {
+        if self.title.is_empty() { self.chat_id.to_string() } else { format!("{} ({})", self.title, self.chat_id) }
+    }
+}
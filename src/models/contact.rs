@@ -0,0 +1,32 @@
+use sqlite::Statement;
+
+pub struct Contact {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub address: String,
+    pub name: String,
+}
+
+impl Contact {
+    pub fn new(user_id: i64, address: String, name: String, id: Option<i64>) -> Self {
+        Contact {
+            id,
+            user_id,
+            address,
+            name,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        Contact::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("{} {}", self.name, self.address)
+    }
+}
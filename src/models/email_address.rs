@@ -0,0 +1,80 @@
+use sqlite::Statement;
+
+// the one email address of a user, alerts only go out once the code sent to it was confirmed.
+pub struct EmailAddress {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub address: String,
+    pub verified: bool,
+    pub code: String,
+    pub code_expires_at: i64,
+    pub attempts: i64,
+}
+
+impl EmailAddress {
+    pub const CODE_TTL: i64 = 900;
+    pub const MAX_ATTEMPTS: i64 = 5;
+    // verification emails a chat may request, and an address may receive, per SEND_WINDOW.
+    pub const MAX_SENDS_PER_CHAT: i64 = 5;
+    pub const MAX_SENDS_PER_ADDRESS: i64 = 3;
+    pub const SEND_WINDOW: i64 = 86400;
+
+    pub fn new(user_id: i64, address: String, verified: bool, code: String, code_expires_at: i64, attempts: i64, id: Option<i64>) -> Self {
+        EmailAddress {
+            id,
+            user_id,
+            address,
+            verified,
+            code,
+            code_expires_at,
+            attempts,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        EmailAddress::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<i64>(3).unwrap() == 1,
+            statement.read::<String>(4).unwrap(),
+            statement.read::<i64>(5).unwrap(),
+            statement.read::<i64>(6).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+
+    // failed attempts count against the next code too, until a day passed since this one was sent.
+    pub fn carried_attempts(&self, now: i64) -> i64 {
+        if self.verified || self.code_expires_at - EmailAddress::CODE_TTL + EmailAddress::SEND_WINDOW <= now {
+            0
+        } else {
+            self.attempts
+        }
+    }
+
+    pub fn can_verify(&self, now: i64) -> bool {
+        !self.verified && self.code_expires_at > now && self.attempts < EmailAddress::MAX_ATTEMPTS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(attempts: i64, sent_at: i64) -> EmailAddress {
+        EmailAddress::new(1, "a@example.com".to_string(), false, "123456".to_string(), sent_at + EmailAddress::CODE_TTL, attempts, None)
+    }
+
+    #[test]
+    fn attempts_carry_over_for_a_day() {
+        assert_eq!(pending(3, 1000).carried_attempts(1000 + 3600), 3);
+        assert_eq!(pending(3, 1000).carried_attempts(1000 + EmailAddress::SEND_WINDOW), 0);
+    }
+
+    #[test]
+    fn verification_locks_after_max_attempts() {
+        assert!(pending(EmailAddress::MAX_ATTEMPTS - 1, 1000).can_verify(1001));
+        assert!(!pending(EmailAddress::MAX_ATTEMPTS, 1000).can_verify(1001));
+        assert!(!pending(0, 1000).can_verify(1000 + EmailAddress::CODE_TTL));
+    }
+}
@@ -0,0 +1,33 @@
+use sqlite::Statement;
+
+// a cached forward (name -> address) or reverse (address -> primary name) lookup,
+// an empty value caches a miss.
+pub struct EnsRecord {
+    pub id: Option<i64>,
+    pub name: String,
+    pub address: String,
+    pub reverse: bool,
+    pub updated_at: i64,
+}
+
+impl EnsRecord {
+    pub fn new(name: String, address: String, reverse: bool, updated_at: i64, id: Option<i64>) -> Self {
+        EnsRecord {
+            id,
+            name,
+            address,
+            reverse,
+            updated_at,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        EnsRecord::new(
+            statement.read::<String>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<i64>(3).unwrap() == 1,
+            statement.read::<i64>(4).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+}
@@ -1,4 +1,11 @@
 use serde::{Serialize, Deserialize};
+use crate::common::{hex_to_decimal, hex_to_i64, topic_to_address};
+use crate::models::transfer::Transfer;
+
+// fee paid by the sender in wei.
+fn fee(gas_used: &str, gas_price: &str) -> String {
+    (gas_used.parse::<u128>().unwrap_or(0) * gas_price.parse::<u128>().unwrap_or(0)).to_string()
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct EtherScanTrx {
@@ -31,11 +38,67 @@ pub struct EtherScanTrxDetail {
 }
 
 impl EtherScanTrxDetail {
-    pub fn format_as_str(&self) -> String {
-        format!("Transfer {a} ETH, From {f} To {t}.\nLink: https://etherscan.io/tx/{tx}",
-                f = self.from, t = self.to, tx = self.hash,
-                a = (self.value.parse::<f64>().unwrap() / 10f64.powi(18i32))
-        )
+    pub fn to_transfer(&self) -> Transfer {
+        Transfer {
+            hash: self.hash.to_owned(),
+            from: self.from.to_owned(),
+            to: self.to.to_owned(),
+            value: self.value.to_owned(),
+            token: "ETH".to_string(),
+            symbol: "ETH".to_string(),
+            decimal: 18i64,
+            block_number: self.blockNumber.parse::<i64>().unwrap_or(0i64),
+            timestamp: self.timeStamp.parse::<i64>().unwrap_or(0i64),
+            fee: fee(self.gasUsed.as_str(), self.gasPrice.as_str()),
+            status: self.isError == "0",
+            log_index: -1,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EtherScanInternal {
+    pub status: String,
+    pub message: String,
+    pub result: Vec<EtherScanInternalDetail>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EtherScanInternalDetail {
+    pub blockNumber: String,
+    pub timeStamp: String,
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub value: String,
+    pub contractAddress: String,
+    pub input: String,
+    #[serde(rename = "type")]
+    pub callType: String,
+    pub gas: String,
+    pub gasUsed: String,
+    pub traceId: String,
+    pub isError: String,
+    pub errCode: String,
+}
+
+impl EtherScanInternalDetail {
+    pub fn to_transfer(&self) -> Transfer {
+        Transfer {
+            hash: self.hash.to_owned(),
+            from: self.from.to_owned(),
+            to: self.to.to_owned(),
+            value: self.value.to_owned(),
+            token: "ETH".to_string(),
+            symbol: "ETH".to_string(),
+            decimal: 18i64,
+            block_number: self.blockNumber.parse::<i64>().unwrap_or(0i64),
+            timestamp: self.timeStamp.parse::<i64>().unwrap_or(0i64),
+            fee: "0".to_string(),
+            status: self.isError == "0",
+            log_index: -1,
+        }
     }
 }
 
@@ -68,13 +131,175 @@ pub struct EtherScanErcDetails {
     pub cumulativeGasUsed: String,
     pub input: String,
     pub confirmations: String,
+    #[serde(default)]
+    pub logIndex: String,
 }
 
 impl EtherScanErcDetails {
-    pub fn format_as_str(&self) -> String {
-        format!("Transfer {a} {tn}, From {f} To {t}.\nLink: https://etherscan.io/tx/{tx}",
-                tn = self.tokenName, f = self.from, t = self.to, tx = self.hash,
-                a = (self.value.parse::<f64>().unwrap() / (10f64.powi(self.tokenDecimal.parse::<i32>().unwrap())))
-        )
+    pub fn to_transfer(&self) -> Transfer {
+        Transfer {
+            hash: self.hash.to_owned(),
+            from: self.from.to_owned(),
+            to: self.to.to_owned(),
+            value: self.value.to_owned(),
+            token: self.tokenName.to_owned(),
+            symbol: self.tokenSymbol.to_owned(),
+            decimal: self.tokenDecimal.parse::<i64>().unwrap_or(0i64),
+            block_number: self.blockNumber.parse::<i64>().unwrap_or(0i64),
+            timestamp: self.timeStamp.parse::<i64>().unwrap_or(0i64),
+            fee: fee(self.gasUsed.as_str(), self.gasPrice.as_str()),
+            status: true,
+            log_index: self.logIndex.parse::<i64>().unwrap_or(-1i64),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EtherScanBalance {
+    pub status: String,
+    pub message: String,
+    pub result: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EtherScanLogs {
+    pub status: String,
+    pub message: String,
+    pub result: Vec<EtherScanLogDetail>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EtherScanLogDetail {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub blockNumber: String,
+    pub timeStamp: String,
+    pub gasPrice: String,
+    pub gasUsed: String,
+    pub logIndex: String,
+    pub transactionHash: String,
+    pub transactionIndex: String,
+}
+
+impl EtherScanLogDetail {
+    // an erc20 Transfer event, None for erc721 transfers which share the topic but carry the token id
+    // as a third indexed topic.
+    pub fn to_transfer(&self, name: &str, symbol: &str, decimal: i64) -> Option<Transfer> {
+        if self.topics.len() != 3 {
+            return None;
+        }
+
+        Some(Transfer {
+            hash: self.transactionHash.to_owned(),
+            from: topic_to_address(self.topics[1].as_str()),
+            to: topic_to_address(self.topics[2].as_str()),
+            value: hex_to_decimal(self.data.as_str())?,
+            token: name.to_string(),
+            symbol: symbol.to_string(),
+            decimal,
+            block_number: hex_to_i64(self.blockNumber.as_str()),
+            timestamp: hex_to_i64(self.timeStamp.as_str()),
+            fee: (hex_to_i64(self.gasUsed.as_str()) as u128 * hex_to_i64(self.gasPrice.as_str()) as u128).to_string(),
+            status: true,
+            log_index: hex_to_i64(self.logIndex.as_str()),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EtherScanProxy {
+    pub jsonrpc: String,
+    pub id: i64,
+    pub result: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn trx_detail(is_error: &str) -> EtherScanTrxDetail {
+        serde_json::from_value(json!({
+            "blockNumber": "100", "timeStamp": "1700000000", "hash": "0xabc", "nonce": "1", "blockHash": "0xb",
+            "transactionIndex": "0", "from": "0x1", "to": "0x2", "value": "1000", "gas": "21000",
+            "gasPrice": "10", "isError": is_error, "txreceipt_status": "1", "input": "0x", "contractAddress": "",
+            "cumulativeGasUsed": "21000", "gasUsed": "21000", "confirmations": "5"
+        })).unwrap()
+    }
+
+    fn internal_detail(is_error: &str) -> EtherScanInternalDetail {
+        serde_json::from_value(json!({
+            "blockNumber": "100", "timeStamp": "1700000000", "hash": "0xabc", "from": "0x1", "to": "0x2",
+            "value": "1000", "contractAddress": "", "input": "", "type": "call", "gas": "0", "gasUsed": "0",
+            "traceId": "0_1", "isError": is_error, "errCode": ""
+        })).unwrap()
+    }
+
+    #[test]
+    fn trx_status_follows_is_error() {
+        let ok = trx_detail("0").to_transfer();
+        assert!(ok.status);
+        assert_eq!(ok.fee, "210000");
+        assert_eq!(ok.block_number, 100);
+        assert_eq!(ok.timestamp, 1700000000);
+
+        assert!(!trx_detail("1").to_transfer().status);
+    }
+
+    #[test]
+    fn internal_status_follows_is_error() {
+        assert!(internal_detail("0").to_transfer().status);
+        assert!(!internal_detail("1").to_transfer().status);
+        assert_eq!(internal_detail("0").to_transfer().fee, "0");
+    }
+
+    #[test]
+    fn erc_keeps_name_symbol_and_log_index() {
+        let mut detail = json!({
+            "blockNumber": "100", "timeStamp": "1700000000", "hash": "0xabc", "nonce": "1", "blockHash": "0xb",
+            "from": "0x1", "contractAddress": "0xc", "to": "0x2", "value": "1000000", "tokenName": "USD Coin",
+            "tokenSymbol": "USDC", "tokenDecimal": "6", "transactionIndex": "0", "gas": "0", "gasPrice": "0",
+            "gasUsed": "0", "cumulativeGasUsed": "0", "input": "deprecated", "confirmations": "5"
+        });
+
+        let transfer = serde_json::from_value::<EtherScanErcDetails>(detail.clone()).unwrap().to_transfer();
+        assert_eq!(transfer.token, "USD Coin");
+        assert_eq!(transfer.symbol, "USDC");
+        assert_eq!(transfer.decimal, 6);
+        assert_eq!(transfer.log_index, -1);
+
+        detail["logIndex"] = json!("42");
+        assert_eq!(serde_json::from_value::<EtherScanErcDetails>(detail).unwrap().to_transfer().log_index, 42);
+    }
+
+    #[test]
+    fn transfer_logs_become_transfers() {
+        let mut log = json!({
+            "address": "0xc", "data": "0x00000000000000000000000000000000000000000000000000000000000f4240",
+            "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                       "0x000000000000000000000000ab5801a7d398351b8be11c439e05c5b3259aec9b",
+                       "0x000000000000000000000000742d35cc6634c0532925a3b844bc454e4438f44e"],
+            "blockNumber": "0x64", "timeStamp": "0x6553f100", "gasPrice": "0xa", "gasUsed": "0x5208",
+            "logIndex": "0x2a", "transactionHash": "0xabc", "transactionIndex": "0x0"
+        });
+
+        let transfer = serde_json::from_value::<EtherScanLogDetail>(log.clone()).unwrap()
+            .to_transfer("USD Coin", "USDC", 6).unwrap();
+        assert_eq!(transfer.from, "0xab5801a7d398351b8be11c439e05c5b3259aec9b");
+        assert_eq!(transfer.to, "0x742d35cc6634c0532925a3b844bc454e4438f44e");
+        assert_eq!(transfer.value, "1000000");
+        assert_eq!(transfer.block_number, 100);
+        assert_eq!(transfer.timestamp, 1700000000);
+        assert_eq!(transfer.fee, "210000");
+        assert_eq!(transfer.log_index, 42);
+
+        log["data"] = json!("0xnothex");
+        assert!(serde_json::from_value::<EtherScanLogDetail>(log.clone()).unwrap().to_transfer("USD Coin", "USDC", 6).is_none());
+
+        log["data"] = json!("0x");
+        log["topics"].as_array_mut().unwrap().push(json!("0x01"));
+        assert!(serde_json::from_value::<EtherScanLogDetail>(log).unwrap().to_transfer("Punk", "PUNK", 0).is_none());
     }
 }
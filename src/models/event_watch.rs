@@ -0,0 +1,44 @@
+use sqlite::Statement;
+use crate::models::user::User;
+
+// a contract event subscription, signature is kept in the `Name(type indexed name, ...)` form.
+pub struct EventWatch {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub contract: String,
+    pub signature: String,
+    pub last_block: i64,
+    pub user: Option<User>,
+}
+
+impl EventWatch {
+    pub const COLUMNS: usize = 5;
+
+    pub fn new(user_id: i64, contract: String, signature: String, id: Option<i64>) -> Self {
+        EventWatch {
+            id,
+            user_id,
+            contract,
+            signature,
+            last_block: 0,
+            user: None,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        let mut watch = EventWatch::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        );
+
+        watch.last_block = statement.read::<i64>(4).unwrap();
+
+        watch
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("#{id}: {c} {s}", id = self.id.unwrap_or(0), c = self.contract, s = self.signature)
+    }
+}
@@ -0,0 +1,35 @@
+use sqlite::Statement;
+
+// a notification kept back during quiet hours, a snooze or a mute, sent later as part of a digest.
+pub struct HeldNotification {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub wallet_id: i64,
+    pub chat_id: i64,
+    pub text: String,
+    pub created_at: i64,
+}
+
+impl HeldNotification {
+    pub fn new(user_id: i64, wallet_id: i64, chat_id: i64, text: String, created_at: i64, id: Option<i64>) -> Self {
+        HeldNotification {
+            id,
+            user_id,
+            wallet_id,
+            chat_id,
+            text,
+            created_at,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        HeldNotification::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<i64>(2).unwrap(),
+            statement.read::<i64>(3).unwrap(),
+            statement.read::<String>(4).unwrap(),
+            statement.read::<i64>(5).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+}
@@ -0,0 +1,100 @@
+use sqlite::Statement;
+
+// a telegram user as seen in a chat. Watchlists and settings belong to the chat (the users table
+// is keyed by chat id), the people acting in it are kept here with the role telegram reported,
+// so permission checks are per person and do not ask telegram on every command.
+pub struct Member {
+    pub id: Option<i64>,
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub role: String,
+    pub updated_at: i64,
+}
+
+impl Member {
+    pub const OWNER: &'static str = "owner";
+    pub const ADMIN: &'static str = "admin";
+    pub const MEMBER: &'static str = "member";
+    pub const LEFT: &'static str = "left";
+
+    // roles change rarely, promotions and demotions take effect after this many seconds.
+    pub const TTL: i64 = 600;
+
+    pub fn new(chat_id: i64, user_id: i64, role: String, updated_at: i64, id: Option<i64>) -> Self {
+        Member {
+            id,
+            chat_id,
+            user_id,
+            role,
+            updated_at,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        Member::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<i64>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<i64>(4).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+
+    pub fn from_telegram(chat_id: i64, user_id: i64, kind: &teloxide::types::ChatMemberKind, now: i64) -> Self {
+        let role = if kind.is_owner() {
+            Member::OWNER
+        } else if kind.is_privileged() {
+            Member::ADMIN
+        } else if kind.is_present() {
+            Member::MEMBER
+        } else {
+            Member::LEFT
+        };
+
+        Member::new(chat_id, user_id, role.to_string(), now, None)
+    }
+
+    pub fn is_fresh(&self, now: i64) -> bool {
+        now - self.updated_at < Member::TTL
+    }
+
+    // manage_role is the --manage-role setting: admins, owner or members.
+    pub fn may_manage(&self, manage_role: &str) -> bool {
+        match manage_role {
+            "owner" => self.role == Member::OWNER,
+            "members" => self.role != Member::LEFT,
+            _ => self.role == Member::OWNER || self.role == Member::ADMIN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(role: &str) -> Member {
+        Member::new(1, 2, role.to_string(), 1000, None)
+    }
+
+    #[test]
+    fn admins_manage_by_default() {
+        assert!(member(Member::OWNER).may_manage("admins"));
+        assert!(member(Member::ADMIN).may_manage("admins"));
+        assert!(!member(Member::MEMBER).may_manage("admins"));
+        assert!(!member(Member::LEFT).may_manage("admins"));
+    }
+
+    #[test]
+    fn owner_and_members_settings() {
+        assert!(member(Member::OWNER).may_manage("owner"));
+        assert!(!member(Member::ADMIN).may_manage("owner"));
+        assert!(member(Member::MEMBER).may_manage("members"));
+        assert!(!member(Member::LEFT).may_manage("members"));
+    }
+
+    #[test]
+    fn cached_role_expires() {
+        assert!(member(Member::ADMIN).is_fresh(1000 + Member::TTL - 1));
+        assert!(!member(Member::ADMIN).is_fresh(1000 + Member::TTL));
+    }
+}
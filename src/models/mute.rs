@@ -0,0 +1,41 @@
+use sqlite::Statement;
+
+// an empty token mutes the whole wallet, until of zero never expires.
+pub struct Mute {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub wallet_id: i64,
+    pub token: String,
+    pub until: i64,
+}
+
+impl Mute {
+    pub fn new(user_id: i64, wallet_id: i64, token: String, until: i64, id: Option<i64>) -> Self {
+        Mute {
+            id,
+            user_id,
+            wallet_id,
+            token,
+            until,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        Mute::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<i64>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<i64>(4).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.until == 0 || self.until > now
+    }
+
+    // token names are cut to fit in callback data, so a prefix match is enough.
+    pub fn covers(&self, wallet_id: i64, token: &str) -> bool {
+        self.wallet_id == wallet_id && token.starts_with(self.token.as_str())
+    }
+}
@@ -0,0 +1,42 @@
+use sqlite::Statement;
+
+// health of the etherscan polling of an address, shared by every user tracking it.
+pub struct PollStatus {
+    pub id: Option<i64>,
+    pub address: String,
+    pub last_success_at: i64,
+    pub last_error_at: i64,
+    pub errors: i64,
+    pub last_error: String,
+}
+
+impl PollStatus {
+    // a worker polls every minute, a few missed rounds mean it is stuck or failing.
+    pub const STALE_AFTER: i64 = 300;
+
+    pub fn new(address: String, last_success_at: i64, last_error_at: i64, errors: i64, last_error: String, id: Option<i64>) -> Self {
+        PollStatus {
+            id,
+            address,
+            last_success_at,
+            last_error_at,
+            errors,
+            last_error,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        PollStatus::new(
+            statement.read::<String>(1).unwrap(),
+            statement.read::<i64>(2).unwrap(),
+            statement.read::<i64>(3).unwrap(),
+            statement.read::<i64>(4).unwrap(),
+            statement.read::<String>(5).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        )
+    }
+
+    pub fn is_stale(&self, now: i64) -> bool {
+        now - self.last_success_at > PollStatus::STALE_AFTER
+    }
+}
@@ -0,0 +1,47 @@
+use sqlite::Statement;
+
+// a sink delivery waiting for its next attempt.
+pub struct QueuedDelivery {
+    pub id: Option<i64>,
+    pub sink: String,
+    pub target: String,
+    pub payload: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_error: String,
+    // what the delivery stands for, applied once it is delivered: `held:<chat>:<ids>` deletes the
+    // held notifications, `digest:<wallet>:<boundary>:<until>` marks a digest period sent. Empty for others.
+    pub source: String,
+}
+
+impl QueuedDelivery {
+    pub fn new(sink: String, target: String, payload: String, attempts: i64, next_attempt_at: i64, last_error: String,
+               id: Option<i64>) -> Self {
+        QueuedDelivery {
+            id,
+            sink,
+            target,
+            payload,
+            attempts,
+            next_attempt_at,
+            last_error,
+            source: "".to_string(),
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        let mut delivery = QueuedDelivery::new(
+            statement.read::<String>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<i64>(4).unwrap(),
+            statement.read::<i64>(5).unwrap(),
+            statement.read::<String>(6).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        );
+
+        delivery.source = statement.read::<String>(7).unwrap_or_default();
+
+        delivery
+    }
+}
@@ -0,0 +1,50 @@
+use sqlite::Statement;
+
+// an extra destination of a user's notifications, wallet_id of zero covers every wallet.
+pub struct SinkConfig {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub wallet_id: i64,
+    pub kind: String,
+    pub target: String,
+    // hmac key of webhook sinks, generated when the sink is added.
+    pub secret: String,
+}
+
+impl SinkConfig {
+    pub const KINDS: [&'static str; 3] = ["webhook", "discord", "slack"];
+
+    pub fn new(user_id: i64, wallet_id: i64, kind: String, target: String, id: Option<i64>) -> Self {
+        SinkConfig {
+            id,
+            user_id,
+            wallet_id,
+            kind,
+            target,
+            secret: "".to_string(),
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        let mut sink = SinkConfig::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<i64>(2).unwrap(),
+            statement.read::<String>(3).unwrap(),
+            statement.read::<String>(4).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        );
+
+        sink.secret = statement.read::<String>(5).unwrap_or_default();
+
+        sink
+    }
+
+    pub fn covers(&self, wallet_id: i64) -> bool {
+        self.wallet_id == 0 || self.wallet_id == wallet_id
+    }
+
+    // webhook urls carry their secret in the path, only the host is shown back.
+    pub fn masked_target(&self) -> String {
+        crate::sinks::redact(self.target.as_str())
+    }
+}
@@ -0,0 +1,16 @@
+// totals shown by /stats.
+pub struct Stats {
+    pub users: i64,
+    pub banned_users: i64,
+    pub wallets: i64,
+    pub addresses: i64,
+    pub transactions: i64,
+    pub poll_errors: i64,
+}
+
+impl Stats {
+    pub fn to_string(&self) -> String {
+        format!("Users: {} ({} banned)\nWallets: {}\nDistinct addresses: {}\nTransactions: {}\nPoll errors: {}",
+                self.users, self.banned_users, self.wallets, self.addresses, self.transactions, self.poll_errors)
+    }
+}
@@ -0,0 +1,40 @@
+use sqlite::Statement;
+use crate::models::user::User;
+
+// a token contract watched for large transfers regardless of the addresses involved.
+pub struct TokenWatch {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub contract: String,
+    pub threshold: f64,
+    pub last_block: i64,
+    pub user: Option<User>,
+}
+
+impl TokenWatch {
+    pub const COLUMNS: usize = 5;
+
+    pub fn new(user_id: i64, contract: String, threshold: f64, id: Option<i64>) -> Self {
+        TokenWatch {
+            id,
+            user_id,
+            contract,
+            threshold,
+            last_block: 0,
+            user: None,
+        }
+    }
+
+    pub fn read_from_statement(statement: &Statement) -> Self {
+        let mut watch = TokenWatch::new(
+            statement.read::<i64>(1).unwrap(),
+            statement.read::<String>(2).unwrap(),
+            statement.read::<f64>(3).unwrap(),
+            Some(statement.read::<i64>(0).unwrap()),
+        );
+
+        watch.last_block = statement.read::<i64>(4).unwrap();
+
+        watch
+    }
+}
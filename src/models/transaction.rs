@@ -1,4 +1,6 @@
+use serde::{Deserialize, Serialize};
 use sqlite::Statement;
+use crate::common::{format_amount, parse_date};
 
 pub struct Transaction {
     pub id: Option<i64>,
@@ -10,9 +12,24 @@ pub struct Transaction {
     pub decimal: i64,
     pub status: bool,
     pub wallet_id: i64,
+    pub kind: String,
+    pub received_token: String,
+    pub received_amount: String,
+    pub received_decimal: i64,
+    pub block_number: i64,
+    pub timestamp: i64,
+    pub symbol: String,
+    pub fiat_value: f64,
+    pub fiat_currency: String,
+    pub fee: String,
+    pub received_symbol: String,
+    pub log_index: i64,
 }
 
 impl Transaction {
+    pub const TRANSFER: &'static str = "transfer";
+    pub const SWAP: &'static str = "swap";
+
     pub fn new(from: String, to: String, amount: String, tx_hash: String, token: String, wallet_id: i64,
                decimal: i64, id: Option<i64>, status: Option<bool>) -> Self {
         let mut trx = Transaction {
@@ -25,6 +42,18 @@ impl Transaction {
             decimal,
             id,
             status: true,
+            kind: Transaction::TRANSFER.to_string(),
+            received_token: "".to_string(),
+            received_amount: "0".to_string(),
+            received_decimal: 0,
+            block_number: 0,
+            timestamp: 0,
+            symbol: "".to_string(),
+            fiat_value: 0f64,
+            fiat_currency: "".to_string(),
+            fee: "0".to_string(),
+            received_symbol: "".to_string(),
+            log_index: -1,
         };
 
         if let Some(s) = status {
@@ -35,7 +64,7 @@ impl Transaction {
     }
 
     pub fn read_from_statement(statement: &Statement) -> Transaction {
-        Transaction::new(
+        let mut trx = Transaction::new(
             statement.read::<String>(1).unwrap(),
             statement.read::<String>(3).unwrap(),
             statement.read::<String>(4).unwrap(),
@@ -45,25 +74,160 @@ impl Transaction {
             statement.read::<i64>(8).unwrap(),
             Some(statement.read::<i64>(0).unwrap()),
             Some(statement.read::<i64>(6).unwrap() != 0),
-        )
+        );
+
+        trx.kind = statement.read::<String>(9).unwrap();
+        trx.received_token = statement.read::<String>(10).unwrap();
+        trx.received_amount = statement.read::<String>(11).unwrap();
+        trx.received_decimal = statement.read::<i64>(12).unwrap();
+        trx.block_number = statement.read::<i64>(13).unwrap();
+        trx.timestamp = statement.read::<i64>(14).unwrap();
+        trx.symbol = statement.read::<String>(15).unwrap();
+        trx.fiat_value = statement.read::<f64>(16).unwrap();
+        trx.fiat_currency = statement.read::<String>(17).unwrap();
+        trx.fee = statement.read::<String>(18).unwrap();
+        trx.received_symbol = statement.read::<String>(19).unwrap();
+        trx.log_index = statement.read::<i64>(20).unwrap();
+
+        trx
     }
 
-    pub fn to_string(&self) -> String {
-        let mut decimal = match self.token.as_str() {
+    pub fn is_swap(&self) -> bool {
+        self.kind == Transaction::SWAP
+    }
+
+    // the short ticker when the explorer reported one, the token name otherwise.
+    pub fn token_symbol(&self) -> &str {
+        if self.symbol.is_empty() { self.token.as_str() } else { self.symbol.as_str() }
+    }
+
+    pub fn received_token_symbol(&self) -> &str {
+        if self.received_symbol.is_empty() { self.received_token.as_str() } else { self.received_symbol.as_str() }
+    }
+
+    // rows stored before decimals were saved only know the token name.
+    pub fn token_decimal(&self) -> i64 {
+        if self.decimal != 0i64 {
+            return self.decimal;
+        }
+
+        match self.token.as_str() {
             "ETH" => 18,
             "Tether USD" => 6,
             _ => 0,
-        };
+        }
+    }
 
-        decimal = if self.decimal != 0i64 {
-            self.decimal as i32
-        } else {
-            decimal
-        };
+    pub fn has_fiat_value(&self) -> bool {
+        !self.fiat_currency.is_empty()
+    }
+
+    pub fn fiat_to_string(&self) -> String {
+        if !self.has_fiat_value() {
+            return "".to_string();
+        }
 
-        format!("Transfer {a} {tn}, From {f} To {t}.\nLink: https://etherscan.io/tx/{tx}",
+        format!(" (≈ {:.2} {})", self.fiat_value, self.fiat_currency)
+    }
+
+    pub fn to_string(&self) -> String {
+        if self.is_swap() {
+            return format!("Swapped {a} {tn} → {ra} {rtn}{fv}\nLink: https://etherscan.io/tx/{tx}",
+                           a = format_amount(&self.amount, self.decimal), tn = self.token_symbol(),
+                           ra = format_amount(&self.received_amount, self.received_decimal), rtn = self.received_token_symbol(),
+                           fv = self.fiat_to_string(), tx = self.tx_hash,
+            );
+        }
+
+        format!("Transfer {a} {tn}{fv}, From {f} To {t}.\nLink: https://etherscan.io/tx/{tx}",
                 tn = self.token, f = self.from, t = self.to, tx = self.tx_hash,
-                a = (self.amount.parse::<f64>().unwrap() / (10f64.powi(decimal)))
+                a = (self.amount.parse::<f64>().unwrap() / (10f64.powi(self.token_decimal() as i32))),
+                fv = self.fiat_to_string(),
         )
     }
 }
+
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TransactionFilter {
+    pub token: Option<String>,
+    pub incoming: Option<bool>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl TransactionFilter {
+    pub const PAGE_SIZE: i64 = 10;
+
+    // `page 2`, `token USDT`, `in`/`out`, `from 2024-01-01`, `to 2024-02-01` in any order, Err on unknown words.
+    pub fn parse(args: &[&str]) -> Result<(Self, i64), ()> {
+        let mut filter = TransactionFilter::default();
+        let mut page = 1;
+        let mut i = 0;
+
+        while i < args.len() {
+            let value = args.get(i + 1).copied();
+
+            match (args[i].to_ascii_lowercase().as_str(), value) {
+                ("in", _) => filter.incoming = Some(true),
+                ("out", _) => filter.incoming = Some(false),
+                ("page", Some(v)) => {
+                    page = v.parse::<i64>().map_err(|_| ())?.max(1);
+                    i += 1;
+                }
+                ("token", Some(v)) => {
+                    filter.token = Some(v.to_string());
+                    i += 1;
+                }
+                ("from", Some(v)) => {
+                    filter.from = Some(parse_date(v).ok_or(())?);
+                    i += 1;
+                }
+                ("to", Some(v)) => {
+                    filter.to = Some(parse_date(v).ok_or(())? + 86399);
+                    i += 1;
+                }
+                _ => return Err(()),
+            }
+
+            i += 1;
+        }
+
+        Ok((filter, page))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_words_in_any_order() {
+        let (filter, page) = TransactionFilter::parse(&["token", "USDT", "page", "3", "OUT", "from", "2024-01-01", "to", "2024-01-31"]).unwrap();
+
+        assert_eq!(page, 3);
+        assert_eq!(filter, TransactionFilter {
+            token: Some("USDT".to_string()),
+            incoming: Some(false),
+            from: Some(1704067200),
+            to: Some(1706745599),
+        });
+        assert_eq!(TransactionFilter::parse(&[]), Ok((TransactionFilter::default(), 1)));
+        assert_eq!(TransactionFilter::parse(&["page", "0"]).unwrap().1, 1);
+    }
+
+    #[test]
+    fn unknown_or_incomplete_filter_words_are_rejected() {
+        assert!(TransactionFilter::parse(&["token"]).is_err());
+        assert!(TransactionFilter::parse(&["page", "x"]).is_err());
+        assert!(TransactionFilter::parse(&["from", "2024-13-01"]).is_err());
+        assert!(TransactionFilter::parse(&["sideways"]).is_err());
+    }
+
+    #[test]
+    fn filters_survive_the_page_filter_table() {
+        let (filter, _) = TransactionFilter::parse(&["in", "token", "USDC", "from", "2024-01-01"]).unwrap();
+        let stored = serde_json::to_string(&filter).unwrap();
+
+        assert_eq!(serde_json::from_str::<TransactionFilter>(stored.as_str()).unwrap(), filter);
+    }
+}
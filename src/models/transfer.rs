@@ -0,0 +1,226 @@
+use crate::models::transaction::Transaction;
+
+// a single token movement as reported by the explorer, several of them can share one tx hash.
+#[derive(Clone)]
+pub struct Transfer {
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub value: String,
+    pub token: String,
+    pub symbol: String,
+    pub decimal: i64,
+    pub block_number: i64,
+    pub timestamp: i64,
+    pub fee: String,
+    pub status: bool,
+    // position of the log in the block, negative when the explorer does not report one.
+    pub log_index: i64,
+}
+
+pub enum TransferGroup {
+    Transfers(Vec<Transfer>),
+    Swap { sent: Transfer, received: Transfer },
+}
+
+impl TransferGroup {
+    // tokens leaving the wallet and different tokens coming back in the same tx is treated as a swap.
+    pub fn classify(wallet: &str, transfers: Vec<Transfer>) -> Self {
+        let transfers = transfers.into_iter()
+            .filter(|t| t.value.trim_start_matches('0') != "")
+            .collect::<Vec<Transfer>>();
+
+        let sent = merge_by_token(transfers.iter()
+            .filter(|t| t.from.eq_ignore_ascii_case(wallet) && !t.to.eq_ignore_ascii_case(wallet))
+            .collect());
+        let received = merge_by_token(transfers.iter()
+            .filter(|t| t.to.eq_ignore_ascii_case(wallet) && !t.from.eq_ignore_ascii_case(wallet))
+            .collect());
+
+        let sent_leg = sent.iter()
+            .find(|s| !received.iter().any(|r| r.symbol == s.symbol));
+        let received_leg = received.iter()
+            .find(|r| !sent.iter().any(|s| s.symbol == r.symbol));
+
+        match (sent_leg, received_leg) {
+            (Some(s), Some(r)) => TransferGroup::Swap {
+                sent: s.clone(),
+                received: r.clone(),
+            },
+            _ => TransferGroup::Transfers(transfers),
+        }
+    }
+
+    pub fn into_transactions(self, wallet_id: i64) -> Vec<Transaction> {
+        match self {
+            TransferGroup::Transfers(transfers) => {
+                transfers.iter().map(|t| t.to_transaction(wallet_id)).collect()
+            }
+            TransferGroup::Swap { sent, received } => {
+                let mut trx = sent.to_transaction(wallet_id);
+
+                trx.kind = Transaction::SWAP.to_string();
+                trx.received_token = received.token.to_owned();
+                trx.received_symbol = received.symbol.to_owned();
+                trx.received_amount = received.value.to_owned();
+                trx.received_decimal = received.decimal;
+
+                vec![trx]
+            }
+        }
+    }
+}
+
+impl Transfer {
+    pub fn to_transaction(&self, wallet_id: i64) -> Transaction {
+        let mut trx = Transaction::new(
+            self.from.to_owned(),
+            self.to.to_owned(),
+            self.value.to_owned(),
+            self.hash.to_owned(),
+            self.token.to_owned(),
+            wallet_id,
+            self.decimal,
+            None,
+            Some(self.status),
+        );
+
+        trx.block_number = self.block_number;
+        trx.timestamp = self.timestamp;
+        trx.symbol = self.symbol.to_owned();
+        trx.fee = self.fee.to_owned();
+        trx.log_index = self.log_index;
+
+        trx
+    }
+}
+
+// keeps the order of first appearance so the latest tx comes first. Transfers without a log index
+// get their position in the group instead, so two equal legs of one tx are still told apart.
+pub fn group_by_hash(transfers: Vec<Transfer>) -> Vec<(String, Vec<Transfer>)> {
+    let mut groups: Vec<(String, Vec<Transfer>)> = vec![];
+
+    for mut transfer in transfers {
+        match groups.iter_mut().find(|(hash, _)| hash.eq_ignore_ascii_case(&transfer.hash)) {
+            Some((_, group)) => {
+                if transfer.log_index < 0 {
+                    transfer.log_index = -(group.len() as i64) - 1;
+                }
+
+                group.push(transfer)
+            }
+            None => groups.push((transfer.hash.to_owned(), vec![transfer])),
+        }
+    }
+
+    groups
+}
+
+fn merge_by_token(transfers: Vec<&Transfer>) -> Vec<Transfer> {
+    let mut merged: Vec<Transfer> = vec![];
+
+    for transfer in transfers {
+        match merged.iter_mut().find(|m| m.symbol == transfer.symbol) {
+            Some(m) => {
+                let total = m.value.parse::<u128>().unwrap_or(0) + transfer.value.parse::<u128>().unwrap_or(0);
+                m.value = total.to_string();
+            }
+            None => merged.push(transfer.clone()),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0xWallet";
+
+    fn transfer(from: &str, to: &str, value: &str, token: &str, symbol: &str, log_index: i64) -> Transfer {
+        Transfer {
+            hash: "0xhash".to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+            token: token.to_string(),
+            symbol: symbol.to_string(),
+            decimal: 6,
+            block_number: 1,
+            timestamp: 1700000000,
+            fee: "100".to_string(),
+            status: true,
+            log_index,
+        }
+    }
+
+    #[test]
+    fn different_tokens_in_and_out_are_a_swap() {
+        let transfers = vec![
+            transfer("0xwallet", "0xpool", "1000000", "USD Coin", "USDC", 3),
+            transfer("0xpool", "0xwallet", "420", "Wrapped Ether", "WETH", 5),
+        ];
+
+        let transactions = TransferGroup::classify(WALLET, transfers).into_transactions(7);
+
+        assert_eq!(transactions.len(), 1);
+        let swap = &transactions[0];
+        assert!(swap.is_swap());
+        assert_eq!(swap.token, "USD Coin");
+        assert_eq!(swap.symbol, "USDC");
+        assert_eq!(swap.received_token, "Wrapped Ether");
+        assert_eq!(swap.received_symbol, "WETH");
+        assert_eq!(swap.received_amount, "420");
+        assert_eq!(swap.wallet_id, 7);
+    }
+
+    #[test]
+    fn legs_of_the_same_token_are_merged() {
+        let transfers = vec![
+            transfer("0xwallet", "0xpool", "600", "USD Coin", "USDC", 1),
+            transfer("0xwallet", "0xpool", "400", "USD Coin", "USDC", 2),
+            transfer("0xpool", "0xwallet", "7", "Wrapped Ether", "WETH", 3),
+        ];
+
+        match TransferGroup::classify(WALLET, transfers) {
+            TransferGroup::Swap { sent, received } => {
+                assert_eq!(sent.value, "1000");
+                assert_eq!(received.value, "7");
+            }
+            TransferGroup::Transfers(_) => panic!("expected a swap"),
+        }
+    }
+
+    #[test]
+    fn one_direction_stays_transfers() {
+        let transfers = vec![
+            transfer("0xa", "0xwallet", "5", "USD Coin", "USDC", 1),
+            transfer("0xb", "0xwallet", "0", "USD Coin", "USDC", 2),
+            transfer("0xc", "0xwallet", "6", "Tether USD", "USDT", 3),
+        ];
+
+        match TransferGroup::classify(WALLET, transfers) {
+            TransferGroup::Transfers(t) => assert_eq!(t.len(), 2),
+            TransferGroup::Swap { .. } => panic!("expected transfers"),
+        }
+    }
+
+    #[test]
+    fn grouping_keeps_order_and_numbers_unindexed_legs() {
+        let mut other = transfer("0xa", "0xwallet", "5", "USD Coin", "USDC", -1);
+        other.hash = "0xother".to_string();
+
+        let groups = group_by_hash(vec![
+            transfer("0xa", "0xwallet", "5", "USD Coin", "USDC", -1),
+            other,
+            transfer("0xa", "0xwallet", "5", "USD Coin", "USDC", -1),
+            transfer("0xa", "0xwallet", "5", "USD Coin", "USDC", 9),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "0xhash");
+        assert_eq!(groups[0].1.iter().map(|t| t.log_index).collect::<Vec<i64>>(), vec![-1, -2, 9]);
+        assert_eq!(groups[1].1.len(), 1);
+    }
+}
@@ -1,17 +1,171 @@
+use chrono::{DateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use sqlite::Statement;
 use crate::models::wallet::Wallet;
 
 pub struct User {
     pub id: Option<i64>,
     pub chat_id: String,
+    pub currency: String,
+    pub template: String,
+    pub utc_offset: i64,
+    // IANA name like Europe/Berlin, follows daylight saving time. Empty uses utc_offset.
+    pub timezone: String,
+    pub quiet_start: i64,
+    pub quiet_end: i64,
+    pub quiet_mode: String,
+    pub snooze_until: i64,
+    pub digest_time: i64,
+    pub announcements: bool,
+    // version of the last release notes sent, see release_notes::RELEASE_NOTES.
+    pub release_seen: i64,
+    pub banned: bool,
+    // most wallets the user may track, zero is unlimited.
+    pub wallet_quota: i64,
     pub wallets: Vec<Wallet>,
 }
 
 impl User {
+    pub const COLUMNS: usize = 15;
+    pub const SILENT: &'static str = "silent";
+    pub const HOLD: &'static str = "hold";
+
     pub fn new(chat_id: String, id: Option<i64>) -> Self {
         User {
             id,
             chat_id,
+            currency: "USD".to_string(),
+            template: "".to_string(),
+            utc_offset: 0,
+            timezone: "".to_string(),
+            quiet_start: -1,
+            quiet_end: -1,
+            quiet_mode: User::SILENT.to_string(),
+            snooze_until: 0,
+            digest_time: 540,
+            announcements: true,
+            release_seen: 0,
+            banned: false,
+            wallet_quota: 0,
             wallets: vec![],
         }
     }
+
+    // offset is the index of the first users column, it is not zero when users is joined to another table.
+    pub fn read_from_statement(statement: &Statement, offset: usize) -> Self {
+        let mut user = User::new(
+            statement.read::<String>(offset + 1).unwrap(),
+            Some(statement.read::<i64>(offset).unwrap()),
+        );
+
+        user.currency = statement.read::<String>(offset + 2).unwrap();
+        user.template = statement.read::<String>(offset + 3).unwrap();
+        user.utc_offset = statement.read::<i64>(offset + 4).unwrap();
+        user.quiet_start = statement.read::<i64>(offset + 5).unwrap();
+        user.quiet_end = statement.read::<i64>(offset + 6).unwrap();
+        user.quiet_mode = statement.read::<String>(offset + 7).unwrap();
+        user.snooze_until = statement.read::<i64>(offset + 8).unwrap();
+        user.digest_time = statement.read::<i64>(offset + 9).unwrap();
+        user.announcements = statement.read::<i64>(offset + 10).unwrap() == 1;
+        user.release_seen = statement.read::<i64>(offset + 11).unwrap();
+        user.banned = statement.read::<i64>(offset + 12).unwrap() == 1;
+        user.wallet_quota = statement.read::<i64>(offset + 13).unwrap();
+        user.timezone = statement.read::<String>(offset + 14).unwrap();
+
+        user
+    }
+
+    // minutes east of utc at the given time.
+    pub fn offset_at(&self, now: i64) -> i64 {
+        let timezone = match self.timezone.parse::<Tz>() {
+            Ok(tz) => tz,
+            Err(_) => return self.utc_offset,
+        };
+
+        match DateTime::from_timestamp(now, 0) {
+            Some(time) => timezone.offset_from_utc_datetime(&time.naive_utc()).fix().local_minus_utc() as i64 / 60,
+            None => self.utc_offset,
+        }
+    }
+
+    // minutes since local midnight.
+    pub fn local_minutes(&self, now: i64) -> i64 {
+        (now / 60 + self.offset_at(now)).rem_euclid(1440)
+    }
+
+    // quiet hours may wrap midnight, e.g. 23:00-07:00.
+    pub fn in_quiet_hours(&self, now: i64) -> bool {
+        if self.quiet_start < 0 || self.quiet_end < 0 || self.quiet_start == self.quiet_end {
+            return false;
+        }
+
+        let minutes = self.local_minutes(now);
+
+        if self.quiet_start < self.quiet_end {
+            minutes >= self.quiet_start && minutes < self.quiet_end
+        } else {
+            minutes >= self.quiet_start || minutes < self.quiet_end
+        }
+    }
+
+    pub fn is_snoozed(&self, now: i64) -> bool {
+        self.snooze_until > now
+    }
+
+    pub fn holds(&self) -> bool {
+        self.quiet_mode == User::HOLD
+    }
+
+    pub fn within_quota(&self, wallets: usize) -> bool {
+        self.wallet_quota == 0 || wallets as i64 <= self.wallet_quota
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-15 12:00 and 2024-07-15 12:00 utc.
+    const WINTER: i64 = 1705320000;
+    const SUMMER: i64 = 1721044800;
+
+    #[test]
+    fn fixed_offset() {
+        let mut user = User::new("1".to_string(), None);
+        user.utc_offset = 120;
+
+        assert_eq!(user.offset_at(WINTER), 120);
+        assert_eq!(user.offset_at(SUMMER), 120);
+        assert_eq!(user.local_minutes(WINTER), 14 * 60);
+    }
+
+    #[test]
+    fn named_timezone_follows_dst() {
+        let mut user = User::new("1".to_string(), None);
+        user.timezone = "Europe/Berlin".to_string();
+
+        assert_eq!(user.offset_at(WINTER), 60);
+        assert_eq!(user.offset_at(SUMMER), 120);
+        assert_eq!(user.local_minutes(SUMMER), 14 * 60);
+    }
+
+    #[test]
+    fn zero_quota_is_unlimited() {
+        let mut user = User::new("1".to_string(), None);
+        assert!(user.within_quota(10_000));
+
+        user.wallet_quota = 3;
+        assert!(user.within_quota(3));
+        assert!(!user.within_quota(4));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight() {
+        let mut user = User::new("1".to_string(), None);
+        user.quiet_start = 23 * 60;
+        user.quiet_end = 7 * 60;
+
+        assert!(user.in_quiet_hours(WINTER - 12 * 3600));
+        assert!(!user.in_quiet_hours(WINTER));
+    }
 }
@@ -6,15 +6,52 @@ pub struct Wallet {
     pub id: Option<i64>,
     pub address: String,
     pub user_id: i64,
+    pub label: String,
+    pub route_chat_id: i64,
+    pub delivery: String,
+    pub digest_sent_at: i64,
+    // insert time of the newest transaction already summarized.
+    pub digest_until: i64,
     pub transactions: Vec<Transaction>,
     pub user: Option<User>,
 }
 
 impl Wallet {
+    // chat the notifications of the wallet go to, the owner chat unless routed elsewhere.
+    pub fn delivery_chat(&self, owner_chat_id: i64) -> i64 {
+        if self.route_chat_id != 0 { self.route_chat_id } else { owner_chat_id }
+    }
+
+    pub fn is_instant(&self) -> bool {
+        self.delivery != Wallet::HOURLY && self.delivery != Wallet::DAILY
+    }
+
+    // end of the latest finished digest period, hourly ones close on the hour and daily ones
+    // at digest_time (minutes since local midnight) of the user.
+    pub fn digest_boundary(&self, now: i64, utc_offset: i64, digest_time: i64) -> i64 {
+        if self.delivery == Wallet::HOURLY {
+            return now - now.rem_euclid(3600);
+        }
+
+        let shift = utc_offset * 60 - digest_time * 60;
+
+        (now + shift).div_euclid(86400) * 86400 - shift
+    }
+
+    pub const COLUMNS: usize = 8;
+    pub const INSTANT: &'static str = "instant";
+    pub const HOURLY: &'static str = "hourly";
+    pub const DAILY: &'static str = "daily";
+
     pub fn new(address: String, user_id: i64, id: Option<i64>) -> Self {
         Wallet {
             address,
             user_id,
+            label: "".to_string(),
+            route_chat_id: 0,
+            delivery: Wallet::INSTANT.to_string(),
+            digest_sent_at: 0,
+            digest_until: 0,
             id,
             transactions: vec![],
             user: None,
@@ -22,10 +59,18 @@ impl Wallet {
     }
 
     pub fn read_from_statement(statement: &Statement) -> Self {
-        Wallet::new(
+        let mut wallet = Wallet::new(
             statement.read::<String>(2).unwrap(),
             statement.read::<i64>(1).unwrap(),
             Some(statement.read::<i64>(0).unwrap()),
-        )
+        );
+
+        wallet.label = statement.read::<String>(3).unwrap_or_default();
+        wallet.route_chat_id = statement.read::<i64>(4).unwrap_or_default();
+        wallet.delivery = statement.read::<String>(5).unwrap_or(Wallet::INSTANT.to_string());
+        wallet.digest_sent_at = statement.read::<i64>(6).unwrap_or_default();
+        wallet.digest_until = statement.read::<i64>(7).unwrap_or_default();
+
+        wallet
     }
 }
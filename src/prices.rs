@@ -0,0 +1,29 @@
+pub mod http_source;
+pub mod file_source;
+
+use async_trait::async_trait;
+use crate::AppConfig;
+use crate::prices::file_source::FilePriceSource;
+use crate::prices::http_source::HttpPriceSource;
+
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    // price of one unit of the token in the given fiat currency at the given unix timestamp.
+    async fn get_price(&self, symbol: &str, currency: &str, timestamp: i64) -> Option<f64>;
+}
+
+pub fn from_config(config: &AppConfig) -> Box<dyn PriceSource> {
+    match &config.price_file {
+        Some(path) => Box::new(FilePriceSource::load(path.as_str())),
+        None => Box::new(HttpPriceSource::new(config.price_api.clone())),
+    }
+}
+
+// wrapped tokens are priced as the underlying asset.
+pub fn price_symbol(symbol: &str) -> String {
+    match symbol.to_ascii_uppercase().as_str() {
+        "WETH" => "ETH".to_string(),
+        "WBTC" => "BTC".to_string(),
+        s => s.to_string(),
+    }
+}
@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::logger;
+use crate::prices::PriceSource;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub symbol: String,
+    pub currency: String,
+    pub timestamp: i64,
+    pub price: f64,
+}
+
+// offline prices loaded once from a `.json` array of price points or a `symbol,currency,timestamp,price` csv.
+pub struct FilePriceSource {
+    pub points: Vec<PricePoint>,
+}
+
+impl FilePriceSource {
+    pub fn load(path: &str) -> Self {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                panic!("Error reading price file {}: {}", path, e);
+            }
+        };
+
+        let points = if path.to_ascii_lowercase().ends_with(".json") {
+            match serde_json::from_str::<Vec<PricePoint>>(content.as_str()) {
+                Ok(p) => p,
+                Err(e) => {
+                    panic!("Error parsing price file {}: {}", path, e);
+                }
+            }
+        } else {
+            FilePriceSource::parse_csv(content.as_str())
+        };
+
+        logger!("{} price points loaded from {}", points.len(), path);
+
+        FilePriceSource {
+            points,
+        }
+    }
+
+    fn parse_csv(content: &str) -> Vec<PricePoint> {
+        content.lines()
+            .map(|l| l.split(',').map(|c| c.trim()).collect::<Vec<&str>>())
+            .filter(|c| c.len() == 4)
+            .filter_map(|c| {
+                Some(PricePoint {
+                    symbol: c[0].to_ascii_uppercase(),
+                    currency: c[1].to_ascii_uppercase(),
+                    timestamp: c[2].parse::<i64>().ok()?,
+                    price: c[3].parse::<f64>().ok()?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl PriceSource for FilePriceSource {
+    // latest point at or before the timestamp. Transfers older than the first point stay unpriced
+    // rather than borrowing a later price.
+    async fn get_price(&self, symbol: &str, currency: &str, timestamp: i64) -> Option<f64> {
+        self.points.iter()
+            .filter(|p| p.symbol.eq_ignore_ascii_case(symbol) && p.currency.eq_ignore_ascii_case(currency))
+            .filter(|p| p.timestamp <= timestamp)
+            .max_by_key(|p| p.timestamp)
+            .map(|p| p.price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> FilePriceSource {
+        FilePriceSource {
+            points: FilePriceSource::parse_csv("symbol,currency,timestamp,price\n\
+                eth,usd,100,1000.5\n\
+                ETH,USD,200,2000\n\
+                ETH,EUR,150,1800\n\
+                BTC,USD,oops,1\n\
+                BTC,USD,100\n"),
+        }
+    }
+
+    #[test]
+    fn csv_rows_are_normalized_and_bad_rows_skipped() {
+        let points = source().points;
+
+        assert_eq!(points.len(), 3);
+        assert_eq!((points[0].symbol.as_str(), points[0].currency.as_str(), points[0].timestamp), ("ETH", "USD", 100));
+    }
+
+    #[tokio::test]
+    async fn latest_price_at_or_before_the_timestamp() {
+        let source = source();
+
+        assert_eq!(source.get_price("ETH", "USD", 100).await, Some(1000.5));
+        assert_eq!(source.get_price("eth", "usd", 199).await, Some(1000.5));
+        assert_eq!(source.get_price("ETH", "USD", 5000).await, Some(2000.0));
+        assert_eq!(source.get_price("ETH", "EUR", 200).await, Some(1800.0));
+    }
+
+    #[tokio::test]
+    async fn no_price_before_the_first_point_or_for_unknown_pairs() {
+        let source = source();
+
+        assert_eq!(source.get_price("ETH", "USD", 99).await, None);
+        assert_eq!(source.get_price("ETH", "EUR", 100).await, None);
+        assert_eq!(source.get_price("BTC", "USD", 1000).await, None);
+        assert_eq!(source.get_price("ETH", "GBP", 1000).await, None);
+    }
+}
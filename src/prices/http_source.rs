@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use crate::logger;
+use crate::prices::PriceSource;
+use crate::providers::http_client;
+
+// speaks the cryptocompare `pricehistorical` api, base_url can point to a local mock.
+pub struct HttpPriceSource {
+    pub base_url: String,
+}
+
+impl HttpPriceSource {
+    pub fn new(base_url: String) -> Self {
+        HttpPriceSource {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // query parameters are encoded, symbols come from token contracts and may contain anything.
+    fn url(&self, symbol: &str, currency: &str, timestamp: i64) -> Option<Url> {
+        Url::parse_with_params(format!("{}/data/pricehistorical", self.base_url).as_str(),
+                               &[("fsym", symbol), ("tsyms", currency), ("ts", timestamp.to_string().as_str())]).ok()
+    }
+}
+
+#[async_trait]
+impl PriceSource for HttpPriceSource {
+    async fn get_price(&self, symbol: &str, currency: &str, timestamp: i64) -> Option<f64> {
+        let url = self.url(symbol, currency, timestamp)?;
+
+        let resp = match http_client().get(url).send().await {
+            Ok(r) => r.json::<serde_json::Value>().await,
+            Err(e) => Err(e),
+        };
+
+        match resp {
+            Ok(v) => v[symbol][currency].as_f64(),
+            Err(e) => {
+                logger!("-> fetching price of {} failed: {}", symbol, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_are_encoded_in_the_url() {
+        let source = HttpPriceSource::new("http://localhost:8080/".to_string());
+
+        assert_eq!(source.url("ETH", "USD", 1700000000).unwrap().as_str(),
+                   "http://localhost:8080/data/pricehistorical?fsym=ETH&tsyms=USD&ts=1700000000");
+        assert_eq!(source.url("A&B=C", "USD", 1).unwrap().query(), Some("fsym=A%26B%3DC&tsyms=USD&ts=1"));
+    }
+}
@@ -0,0 +1,20 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+pub mod etherscan;
+
+pub mod rpc;
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+// shared by the rpc calls and the http sinks so connections are reused and a hung endpoint
+// gives up instead of stalling the caller.
+pub fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(15))
+            .build()
+            .unwrap()
+    })
+}
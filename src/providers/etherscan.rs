@@ -0,0 +1,90 @@
+use crate::models::etherscan::*;
+
+pub async fn check_trx(api_token: &str, wallet: &str) -> Result<EtherScanTrx, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=txlist&address={wallet}&startblock=0&endblock=99999999&page=1&offset=1&sort=desc&apikey={api_token}",
+                      wallet = wallet, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanTrx>().await?;
+    Ok(resp)
+}
+
+pub async fn check_internal(api_token: &str, wallet: &str) -> Result<EtherScanInternal, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=txlistinternal&address={wallet}&startblock=0&endblock=99999999&page=1&offset=10&sort=desc&apikey={api_token}",
+                      wallet = wallet, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanInternal>().await?;
+    Ok(resp)
+}
+
+// a single swap produces several tokentx rows with the same hash, so more than one row is fetched here.
+pub async fn check_erc(api_token: &str, wallet: &str) -> Result<EtherScanErc, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=tokentx&address={wallet}&page=1&offset=20&startblock=0&endblock=9999999999&sort=desc&apikey={api_token}",
+                      wallet = wallet, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanErc>().await?;
+    Ok(resp)
+}
+
+pub async fn check_balance(api_token: &str, wallet: &str) -> Result<EtherScanBalance, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=balance&address={wallet}&tag=latest&apikey={api_token}",
+                      wallet = wallet, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanBalance>().await?;
+    Ok(resp)
+}
+
+pub async fn check_token_balance(api_token: &str, wallet: &str, contract: &str) -> Result<EtherScanBalance, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=tokenbalance&contractaddress={contract}&address={wallet}&tag=latest&apikey={api_token}",
+                      wallet = wallet, contract = contract, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanBalance>().await?;
+    Ok(resp)
+}
+
+// every token the wallet ever received or sent, used to discover which balances to query.
+pub async fn check_erc_history(api_token: &str, wallet: &str) -> Result<EtherScanErc, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=tokentx&address={wallet}&page=1&offset=1000&startblock=0&endblock=9999999999&sort=desc&apikey={api_token}",
+                      wallet = wallet, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanErc>().await?;
+    Ok(resp)
+}
+
+pub async fn check_block_number(api_token: &str) -> Result<EtherScanProxy, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=proxy&action=eth_blockNumber&apikey={api_token}",
+                      api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanProxy>().await?;
+    Ok(resp)
+}
+
+// one page of logs, etherscan serves at most 10000 rows (page * offset) of one query.
+pub async fn check_logs(api_token: &str, contract: &str, topic0: Option<&str>, from_block: i64, to_block: i64, page: i64, offset: i64) -> Result<EtherScanLogs, reqwest::Error> {
+    let mut url = format!("https://api.etherscan.io/api?module=logs&action=getLogs&address={contract}&fromBlock={from_block}&toBlock={to_block}&page={page}&offset={offset}&apikey={api_token}",
+                          contract = contract, from_block = from_block, to_block = to_block, page = page, offset = offset, api_token = api_token);
+
+    if let Some(topic) = topic0 {
+        url = format!("{url}&topic0={topic}", url = url, topic = topic);
+    }
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanLogs>().await?;
+    Ok(resp)
+}
+
+// latest transfer of the token, only used to learn its name, symbol and decimals.
+pub async fn check_token_info(api_token: &str, contract: &str) -> Result<EtherScanErc, reqwest::Error> {
+    let url = format!("https://api.etherscan.io/api?module=account&action=tokentx&contractaddress={contract}&page=1&offset=1&sort=desc&apikey={api_token}",
+                      contract = contract, api_token = api_token);
+
+    let resp = reqwest::get(url).await?
+        .json::<EtherScanErc>().await?;
+    Ok(resp)
+}
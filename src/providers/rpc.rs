@@ -0,0 +1,64 @@
+use serde_json::json;
+use crate::models::rpc::*;
+use crate::providers::http_client;
+
+// eth_call against the latest block, `data` is the hex encoded calldata.
+pub async fn eth_call(rpc_url: &str, to: &str, data: &str) -> Result<JsonRpcResponse, reqwest::Error> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [{ "to": to, "data": data }, "latest"],
+    });
+
+    let resp = http_client().post(rpc_url).json(&body).send().await?
+        .json::<JsonRpcResponse>().await?;
+    Ok(resp)
+}
+
+// several eth_calls in one json-rpc batch, results come back in the order of `calls` and are None
+// for calls the node answered with an error.
+pub async fn batch_eth_call(rpc_url: &str, calls: &[(String, String)]) -> Result<Vec<Option<String>>, reqwest::Error> {
+    let body = calls.iter().enumerate()
+        .map(|(i, (to, data))| json!({
+            "jsonrpc": "2.0",
+            "id": i,
+            "method": "eth_call",
+            "params": [{ "to": to, "data": data }, "latest"],
+        }))
+        .collect::<Vec<serde_json::Value>>();
+
+    let resp = http_client().post(rpc_url).json(&body).send().await?
+        .json::<Vec<JsonRpcResponse>>().await?;
+
+    Ok(in_call_order(calls.len(), resp))
+}
+
+// batch responses may come back in any order, they are matched to the calls by id.
+fn in_call_order(calls: usize, resp: Vec<JsonRpcResponse>) -> Vec<Option<String>> {
+    let mut results = vec![None; calls];
+    for r in resp {
+        if let Some(slot) = results.get_mut(r.id as usize) {
+            *slot = r.result;
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_results_follow_the_call_order() {
+        let resp = serde_json::from_str::<Vec<JsonRpcResponse>>(r#"[
+            {"jsonrpc": "2.0", "id": 2, "result": "0x02"},
+            {"jsonrpc": "2.0", "id": 0, "result": "0x00"},
+            {"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "execution reverted"}},
+            {"jsonrpc": "2.0", "id": 7, "result": "0x07"}
+        ]"#).unwrap();
+
+        assert_eq!(in_call_order(4, resp), vec![Some("0x00".to_string()), None, Some("0x02".to_string()), None]);
+    }
+}
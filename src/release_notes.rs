@@ -0,0 +1,54 @@
+// oldest first. Every user is sent the notes newer than users.release_seen once, new users start at latest().
+pub const RELEASE_NOTES: &[(i64, &str)] = &[
+    (1, r#"
+Bot has been update here is usage:
+/start: Starts the bot
+/add <wallet>: adds the wallet to tracker
+/remove <wallet>: removes wallet and all it's data from the bot and stops tracker for the wallet
+/list: shows list of wallets that have been tracked for you
+/txlist <address>: shows list of transactions for the wallet by the tracker.
+Update(s): 
+[+] fix bug in reporting 0 ETH txs.
+[+] fix bug in not reporting some ERC20 tokens txs.
+    "#),
+    (2, r#"
+Bot has been updated:
+[+] ENS names are accepted wherever an address is, e.g. /add vitalik.eth
+[+] /contact keeps an address book, known exchanges and bridges are labeled.
+[+] /route sends a wallet's notifications to another chat or channel.
+[+] /timezone, /quiet, /mute and /snooze control when you are notified.
+[+] /delivery hourly|daily summarizes a wallet in a digest.
+[+] /sink forwards notifications to Discord, Slack or a webhook, /email adds email alerts.
+[+] /announcements off stops messages like this one.
+    "#),
+];
+
+pub fn latest() -> i64 {
+    RELEASE_NOTES.last().map(|(version, _)| *version).unwrap_or(0)
+}
+
+pub fn unseen(seen: i64) -> Vec<&'static str> {
+    RELEASE_NOTES.iter()
+        .filter(|(version, _)| *version > seen)
+        .map(|(_, notes)| notes.trim())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_only_grow() {
+        assert!(RELEASE_NOTES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(latest(), RELEASE_NOTES[RELEASE_NOTES.len() - 1].0);
+    }
+
+    #[test]
+    fn only_newer_notes_are_unseen() {
+        assert_eq!(unseen(0).len(), RELEASE_NOTES.len());
+        assert_eq!(unseen(1), vec![RELEASE_NOTES[1].1.trim()]);
+        assert!(unseen(latest()).is_empty());
+        assert!(unseen(0).iter().all(|n| !n.starts_with('\n') && !n.ends_with(' ')));
+    }
+}
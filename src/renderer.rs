@@ -0,0 +1,474 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+use crate::AppConfig;
+use crate::callback_handler::{CALLBACK_DATA_LIMIT, MUTE_TOKEN, MUTE_WALLET, MUTE_WALLET_SECONDS, page_callback};
+use crate::common::{format_amount, format_timestamp, format_units, truncate_bytes};
+use crate::models::transaction::{Transaction, TransactionFilter};
+use crate::models::wallet::Wallet;
+use crate::templates;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub text: String,
+    pub parse_mode: ParseMode,
+    pub keyboard: Option<InlineKeyboardMarkup>,
+    pub silent: bool,
+}
+
+impl Notification {
+    // a plain text message escaped for the configured markup.
+    pub fn plain(text: &str, markup: Markup) -> Self {
+        Notification {
+            text: markup.escape(text),
+            parse_mode: markup.parse_mode(),
+            keyboard: None,
+            silent: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Markup {
+    Html,
+    MarkdownV2,
+}
+
+impl Markup {
+    pub fn from_config(config: &AppConfig) -> Self {
+        match config.parse_mode.as_str() {
+            "markdownv2" => Markup::MarkdownV2,
+            _ => Markup::Html,
+        }
+    }
+
+    pub fn from_parse_mode(parse_mode: ParseMode) -> Self {
+        match parse_mode {
+            ParseMode::MarkdownV2 => Markup::MarkdownV2,
+            _ => Markup::Html,
+        }
+    }
+
+    pub fn parse_mode(&self) -> ParseMode {
+        match self {
+            Markup::Html => ParseMode::Html,
+            Markup::MarkdownV2 => ParseMode::MarkdownV2,
+        }
+    }
+
+    pub fn escape(&self, text: &str) -> String {
+        match self {
+            Markup::Html => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+            Markup::MarkdownV2 => {
+                let mut escaped = String::new();
+
+                for c in text.chars() {
+                    if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+                        escaped.push('\\');
+                    }
+                    escaped.push(c);
+                }
+
+                escaped
+            }
+        }
+    }
+
+    // the visible text without formatting, for messages telegram could not parse.
+    pub fn strip(&self, text: &str) -> String {
+        let mut plain = String::new();
+
+        match self {
+            Markup::Html => {
+                let mut in_tag = false;
+
+                for c in text.chars() {
+                    match c {
+                        '<' => in_tag = true,
+                        '>' if in_tag => in_tag = false,
+                        _ if !in_tag => plain.push(c),
+                        _ => {}
+                    }
+                }
+
+                plain.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+            }
+            Markup::MarkdownV2 => {
+                let mut escaped = false;
+
+                for c in text.chars() {
+                    if escaped {
+                        plain.push(c);
+                        escaped = false;
+                    } else if c == '\\' {
+                        escaped = true;
+                    } else if !"*_~`|".contains(c) {
+                        plain.push(c);
+                    }
+                }
+
+                plain
+            }
+        }
+    }
+
+    pub fn bold(&self, text: &str) -> String {
+        match self {
+            Markup::Html => format!("<b>{}</b>", self.escape(text)),
+            Markup::MarkdownV2 => format!("*{}*", self.escape(text)),
+        }
+    }
+
+    pub fn link(&self, text: &str, url: &str) -> String {
+        match self {
+            Markup::Html => format!("<a href=\"{}\">{}</a>", url.replace('"', "&quot;"), self.escape(text)),
+            Markup::MarkdownV2 => format!("[{}]({})", self.escape(text), url.replace('\\', "\\\\").replace(')', "\\)")),
+        }
+    }
+
+    pub fn code(&self, text: &str) -> String {
+        match self {
+            Markup::Html => format!("<code>{}</code>", self.escape(text)),
+            Markup::MarkdownV2 => format!("`{}`", text.replace('\\', "\\\\").replace('`', "\\`")),
+        }
+    }
+}
+
+// 0x1234…abcd
+pub fn short_address(address: &str) -> String {
+    if address.len() < 12 {
+        return address.to_string();
+    }
+
+    format!("{}…{}", &address[..6], &address[address.len() - 4..])
+}
+
+// display names for addresses, anything unknown falls back to the shortened address.
+#[derive(Default)]
+pub struct Labels {
+    names: Vec<(String, String)>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Labels {
+            names: vec![],
+        }
+    }
+
+    pub fn insert(&mut self, address: &str, name: String) {
+        if !self.has(address) {
+            self.names.push((address.to_ascii_lowercase(), name));
+        }
+    }
+
+    pub fn has(&self, address: &str) -> bool {
+        self.names.iter().any(|(a, _)| a.eq_ignore_ascii_case(address))
+    }
+
+    pub fn get(&self, address: &str) -> String {
+        self.names.iter()
+            .find(|(a, _)| a.eq_ignore_ascii_case(address))
+            .map(|(_, n)| n.clone())
+            .unwrap_or_else(|| short_address(address))
+    }
+}
+
+// an empty template keeps the built-in layout.
+pub fn render_transaction(trx: &Transaction, wallet: Option<&Wallet>, labels: &Labels, template: &str, markup: Markup) -> Notification {
+    let token = if trx.is_swap() { trx.token_symbol() } else { trx.token.as_str() };
+    let amount = format!("{} {}", format_amount(&trx.amount, trx.token_decimal()), token);
+
+    let (arrow, direction) = match wallet {
+        _ if trx.is_swap() => ("🔄", "Swapped"),
+        Some(w) if trx.to.eq_ignore_ascii_case(&w.address) => ("⬇️", "Received"),
+        Some(w) if trx.from.eq_ignore_ascii_case(&w.address) => ("⬆️", "Sent"),
+        _ => ("🔁", "Transfer"),
+    };
+
+    if !template.is_empty() {
+        let vars = transaction_variables(trx, wallet, labels, arrow, direction);
+
+        if let Ok(text) = templates::render(template, &vars, markup) {
+            return Notification {
+                text,
+                parse_mode: markup.parse_mode(),
+                keyboard: Some(transaction_keyboard(trx, wallet)),
+                silent: false,
+            };
+        }
+    }
+
+    let headline = if trx.is_swap() {
+        format!("{} {} → {} {}", direction, amount, format_amount(&trx.received_amount, trx.received_decimal), trx.received_token_symbol())
+    } else {
+        format!("{} {}", direction, amount)
+    };
+
+    let mut text = format!("{} {}{}", arrow, markup.bold(headline.as_str()), markup.escape(trx.fiat_to_string().as_str()));
+
+    if !trx.is_swap() {
+        text = format!("{}\n{} {} {} {}", text, markup.escape("From"), markup.code(labels.get(&trx.from).as_str()),
+                       markup.escape("→"), markup.code(labels.get(&trx.to).as_str()));
+    } else if let Some(w) = wallet {
+        text = format!("{}\n{} {}", text, markup.escape("Wallet"), markup.code(labels.get(&w.address).as_str()));
+    }
+
+    Notification {
+        text,
+        parse_mode: markup.parse_mode(),
+        keyboard: Some(transaction_keyboard(trx, wallet)),
+        silent: false,
+    }
+}
+
+fn transaction_variables(trx: &Transaction, wallet: Option<&Wallet>, labels: &Labels, arrow: &str, direction: &str) -> Vec<(&'static str, String)> {
+    let wallet_address = wallet.map(|w| w.address.clone()).unwrap_or_default();
+
+    vec![
+        ("arrow", arrow.to_string()),
+        ("direction", direction.to_string()),
+        ("amount", format_amount(&trx.amount, trx.token_decimal())),
+        ("token", trx.token.clone()),
+        ("symbol", if trx.symbol.is_empty() { trx.token.clone() } else { trx.symbol.clone() }),
+        ("received_amount", format_amount(&trx.received_amount, trx.received_decimal)),
+        ("received_token", trx.received_token.clone()),
+        ("received_symbol", trx.received_token_symbol().to_string()),
+        ("from", trx.from.clone()),
+        ("to", trx.to.clone()),
+        ("from_label", labels.get(&trx.from)),
+        ("to_label", labels.get(&trx.to)),
+        ("wallet_label", labels.get(&wallet_address)),
+        ("wallet_url", format!("https://etherscan.io/address/{}", wallet_address)),
+        ("wallet", wallet_address),
+        ("tx_hash", trx.tx_hash.clone()),
+        ("tx_url", format!("https://etherscan.io/tx/{}", trx.tx_hash)),
+        ("block", trx.block_number.to_string()),
+        ("time", format_timestamp(trx.timestamp)),
+        ("fee", format_amount(&trx.fee, 18)),
+        ("fiat", trx.fiat_to_string()),
+        ("fiat_value", if trx.has_fiat_value() { format!("{:.2} {}", trx.fiat_value, trx.fiat_currency) } else { "".to_string() }),
+    ]
+}
+
+pub fn transaction_keyboard(trx: &Transaction, wallet: Option<&Wallet>) -> InlineKeyboardMarkup {
+    let mut links = vec![];
+    let mut actions = vec![];
+
+    if let Ok(url) = Url::parse(format!("https://etherscan.io/tx/{}", trx.tx_hash).as_str()) {
+        links.push(InlineKeyboardButton::url("View tx".to_string(), url));
+    }
+
+    if let Some(w) = wallet {
+        if let Ok(url) = Url::parse(format!("https://etherscan.io/address/{}", w.address).as_str()) {
+            links.push(InlineKeyboardButton::url("View wallet".to_string(), url));
+        }
+
+        if let Some(wallet_id) = w.id {
+            // long token names are cut to fit, the callback handler matches them back by prefix.
+            let data = format!("{}:{}:", MUTE_TOKEN, wallet_id);
+            let token = truncate_bytes(trx.token.as_str(), CALLBACK_DATA_LIMIT.saturating_sub(data.len()));
+
+            actions.push(InlineKeyboardButton::callback("Mute token".to_string(), format!("{}{}", data, token)));
+            actions.push(InlineKeyboardButton::callback("Mute wallet 1h".to_string(),
+                                                        format!("{}:{}:{}", MUTE_WALLET, wallet_id, MUTE_WALLET_SECONDS[0])));
+        }
+    }
+
+    InlineKeyboardMarkup::new(vec![links, actions].into_iter().filter(|r| !r.is_empty()))
+}
+
+// one page of /txlist as a single message with prev/next buttons.
+pub fn render_transaction_page(txs: &[Transaction], wallet: &Wallet, filter_id: i64, page: i64, total: i64,
+                               markup: Markup) -> Notification {
+    let pages = ((total + TransactionFilter::PAGE_SIZE - 1) / TransactionFilter::PAGE_SIZE).max(1);
+
+    let mut lines = vec![markup.bold(format!("Transactions of {} ({}/{})", short_address(&wallet.address), page, pages).as_str())];
+
+    if txs.is_empty() {
+        lines.push(markup.escape("No transactions found."));
+    }
+
+    for trx in txs {
+        let arrow = if trx.is_swap() {
+            "🔄"
+        } else if trx.to.eq_ignore_ascii_case(&wallet.address) {
+            "⬇️"
+        } else {
+            "⬆️"
+        };
+
+        let mut amount = format!("{} {}", format_amount(&trx.amount, trx.token_decimal()), trx.token);
+        if trx.is_swap() {
+            amount = format!("{} {} → {} {}", format_amount(&trx.amount, trx.token_decimal()), trx.token_symbol(),
+                             format_amount(&trx.received_amount, trx.received_decimal), trx.received_token_symbol());
+        }
+
+        let time = if trx.timestamp != 0 { format_timestamp(trx.timestamp) } else { "unknown time".to_string() };
+
+        lines.push(format!("{} {} {}{} {}", arrow, markup.escape(time.as_str()), markup.bold(amount.as_str()),
+                           markup.escape(trx.fiat_to_string().as_str()),
+                           markup.link("tx", format!("https://etherscan.io/tx/{}", trx.tx_hash).as_str())));
+    }
+
+    let mut buttons = vec![];
+
+    if page > 1 {
+        buttons.push(InlineKeyboardButton::callback("« Prev".to_string(), page_callback(wallet.id.unwrap_or(0), page - 1, filter_id)));
+    }
+
+    if page < pages {
+        buttons.push(InlineKeyboardButton::callback("Next »".to_string(), page_callback(wallet.id.unwrap_or(0), page + 1, filter_id)));
+    }
+
+    Notification {
+        text: lines.join("\n"),
+        parse_mode: markup.parse_mode(),
+        keyboard: Some(InlineKeyboardMarkup::new(vec![buttons].into_iter().filter(|r| !r.is_empty()))),
+        silent: false,
+    }
+}
+
+// one summary of a digest period: counts, net flow per token, the largest transfers and fees paid.
+pub fn render_digest(txs: &[Transaction], wallet: &Wallet, labels: &Labels, period: &str, markup: Markup) -> Notification {
+    let mut flows: Vec<(String, f64)> = vec![];
+    let mut add_flow = |token: &str, amount: f64| {
+        match flows.iter_mut().find(|(t, _)| t == token) {
+            Some((_, f)) => *f += amount,
+            None => flows.push((token.to_string(), amount)),
+        }
+    };
+
+    let (mut incoming, mut outgoing, mut swaps) = (0, 0, 0);
+    let mut fees = 0u128;
+    let mut paid: Vec<&str> = vec![];
+
+    for trx in txs {
+        let amount = format_units(&trx.amount, trx.token_decimal()).parse::<f64>().unwrap_or(0f64);
+
+        if trx.is_swap() {
+            swaps += 1;
+            add_flow(trx.token.as_str(), -amount);
+            add_flow(trx.received_token.as_str(), format_units(&trx.received_amount, trx.received_decimal).parse::<f64>().unwrap_or(0f64));
+        } else if trx.to.eq_ignore_ascii_case(&wallet.address) {
+            incoming += 1;
+            add_flow(trx.token.as_str(), amount);
+        } else {
+            outgoing += 1;
+            add_flow(trx.token.as_str(), -amount);
+        }
+
+        // the sender pays the fee, once per transaction however many transfers it made.
+        if (trx.is_swap() || trx.from.eq_ignore_ascii_case(&wallet.address)) && !paid.contains(&trx.tx_hash.as_str()) {
+            paid.push(trx.tx_hash.as_str());
+            fees += trx.fee.parse::<u128>().unwrap_or(0);
+        }
+    }
+
+    let mut lines = vec![
+        markup.bold(format!("{} digest of {}", period, labels.get(&wallet.address)).as_str()),
+        markup.escape(format!("{} transactions: {} in, {} out, {} swaps", txs.len(), incoming, outgoing, swaps).as_str()),
+    ];
+
+    lines.push(markup.bold("Net flow"));
+    for (token, flow) in flows.iter() {
+        lines.push(markup.escape(format!("{} {}", format_flow(*flow), token).as_str()));
+    }
+
+    let mut largest = txs.iter().filter(|t| t.has_fiat_value()).collect::<Vec<&Transaction>>();
+    largest.sort_by(|a, b| b.fiat_value.partial_cmp(&a.fiat_value).unwrap_or(std::cmp::Ordering::Equal));
+
+    if !largest.is_empty() {
+        lines.push(markup.bold("Largest"));
+    }
+
+    for trx in largest.iter().take(3) {
+        let amount = format!("{} {}", format_amount(&trx.amount, trx.token_decimal()), trx.token);
+
+        lines.push(format!("{}{} {}", markup.escape(amount.as_str()), markup.escape(trx.fiat_to_string().as_str()),
+                           markup.link("tx", format!("https://etherscan.io/tx/{}", trx.tx_hash).as_str())));
+    }
+
+    lines.push(markup.escape(format!("Fees paid: {} ETH", format_amount(&fees.to_string(), 18)).as_str()));
+
+    Notification {
+        text: lines.join("\n"),
+        parse_mode: markup.parse_mode(),
+        keyboard: None,
+        silent: false,
+    }
+}
+
+fn format_flow(flow: f64) -> String {
+    let text = format!("{:+.6}", flow);
+
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use teloxide::types::InlineKeyboardButtonKind;
+
+    const WALLET: &str = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+
+    fn transfer(tx_hash: &str, fee: &str) -> Transaction {
+        let mut trx = Transaction::new(WALLET.to_string(), "0x0000000000000000000000000000000000000001".to_string(),
+                                       "1000000".to_string(), tx_hash.to_string(), "USDC".to_string(), 1, 6, None, None);
+        trx.fee = fee.to_string();
+        trx
+    }
+
+    #[test]
+    fn digest_counts_the_fee_once_per_transaction() {
+        let wallet = Wallet::new(WALLET.to_string(), 1, Some(1));
+        let txs = vec![transfer("0x1", "1000000000000000"), transfer("0x1", "1000000000000000"), transfer("0x2", "1000000000000000")];
+
+        let digest = render_digest(&txs, &wallet, &Labels::new(), "Daily", Markup::Html);
+
+        assert!(digest.text.contains("Fees paid: 0.002 ETH"), "{}", digest.text);
+    }
+
+    fn page_buttons(notification: &Notification) -> Vec<String> {
+        notification.keyboard.as_ref().unwrap().inline_keyboard.iter().flatten()
+            .filter_map(|b| match &b.kind {
+                InlineKeyboardButtonKind::CallbackData(data) => Some(data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_label_of_an_address_wins() {
+        let mut labels = Labels::new();
+        labels.insert(WALLET, "Savings".to_string());
+        labels.insert(WALLET.to_ascii_uppercase().replacen("0X", "0x", 1).as_str(), "Binance 14".to_string());
+
+        assert_eq!(labels.get(WALLET.to_ascii_lowercase().as_str()), "Savings");
+        assert_eq!(labels.get("0x0000000000000000000000000000000000000001"), "0x0000…0001");
+    }
+
+    #[test]
+    fn page_buttons_keep_the_filter() {
+        let wallet = Wallet::new(WALLET.to_string(), 1, Some(5));
+
+        let first = render_transaction_page(&[transfer("0x1", "0")], &wallet, 9, 1, 25, Markup::Html);
+        assert!(first.text.contains("(1/3)"), "{}", first.text);
+        assert_eq!(page_buttons(&first), vec![page_callback(5, 2, 9)]);
+
+        let middle = render_transaction_page(&[transfer("0x1", "0")], &wallet, 9, 2, 25, Markup::Html);
+        assert_eq!(page_buttons(&middle), vec![page_callback(5, 1, 9), page_callback(5, 3, 9)]);
+
+        let only = render_transaction_page(&[], &wallet, 0, 1, 0, Markup::Html);
+        assert!(page_buttons(&only).is_empty());
+    }
+
+    #[test]
+    fn strip_drops_formatting() {
+        let html = format!("{} {}", Markup::Html.bold("a < b"), Markup::Html.link("tx", "https://etherscan.io/tx/0x1"));
+        assert_eq!(Markup::Html.strip(html.as_str()), "a < b tx");
+
+        let markdown = format!("{} {}", Markup::MarkdownV2.bold("1.5 USDC"), Markup::MarkdownV2.code("0xab"));
+        assert_eq!(Markup::MarkdownV2.strip(markdown.as_str()), "1.5 USDC 0xab");
+    }
+}
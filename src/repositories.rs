@@ -1,7 +1,22 @@
 pub mod sqlite_db;
 
 use crate::models::{user::User, wallet::Wallet};
-use crate::models::transaction::Transaction;
+use crate::models::transaction::{Transaction, TransactionFilter};
+use crate::models::balance::Balance;
+use crate::models::alert::Alert;
+use crate::models::token_watch::TokenWatch;
+use crate::models::event_watch::EventWatch;
+use crate::models::mute::Mute;
+use crate::models::ens_record::EnsRecord;
+use crate::models::contact::Contact;
+use crate::models::chat::Chat;
+use crate::models::held_notification::HeldNotification;
+use crate::models::queued_delivery::QueuedDelivery;
+use crate::models::sink_config::SinkConfig;
+use crate::models::email_address::EmailAddress;
+use crate::models::poll_status::PollStatus;
+use crate::models::stats::Stats;
+use crate::models::member::Member;
 
 pub trait DataRepository {
     fn init(&mut self, params: Vec<&str>);
@@ -11,12 +26,89 @@ pub trait DataRepository {
     fn get_user(&self, chat_id: i64) -> Option<User>;
     fn get_all_user(&self) -> Vec<User>;
     fn add_wallet(&self, user_id: i64, wallet_address: String) -> bool;
+    fn add_wallets(&self, user_id: i64, wallets: &Vec<(String, String)>) -> bool;
     fn remove_wallet(&self, user_id: i64, wallet_address: String) -> bool;
     fn get_wallet(&self, user_id: Option<i64>, wallet_address: String) -> Option<Wallet>;
     fn add_transaction(&self, transaction: Transaction) -> bool;
     fn get_transaction(&self, tx_hash: String, wallet_id: Option<i64>, token_name: Option<String>) -> Option<Transaction>;
+    fn has_transfer(&self, tx_hash: String, wallet_id: i64, log_index: i64, token_name: String) -> bool;
     fn get_all_transactions(&self, wallet_id: i64) -> Vec<Transaction>;
+    fn get_transactions(&self, wallet_id: i64, wallet_address: String, filter: &TransactionFilter, limit: i64, offset: i64) -> Vec<Transaction>;
+    fn count_transactions(&self, wallet_id: i64, wallet_address: String, filter: &TransactionFilter) -> i64;
     fn get_all_wallets_with_user(&self) -> Vec<Wallet>;
     fn get_user_wallets(&self, user_id: i64) -> Vec<Wallet>;
+    fn set_user_currency(&self, user_id: i64, currency: String) -> bool;
+    fn set_user_template(&self, user_id: i64, template: String) -> bool;
+    fn set_balances(&self, wallet_address: String, balances: &Vec<Balance>) -> bool;
+    fn get_balances(&self, wallet_address: String) -> Vec<Balance>;
+    fn add_alert(&self, alert: Alert) -> bool;
+    fn remove_alert(&self, user_id: i64, alert_id: i64) -> bool;
+    fn get_user_alerts(&self, user_id: i64) -> Vec<Alert>;
+    fn get_all_alerts_with_user(&self) -> Vec<Alert>;
+    fn set_alert_triggered(&self, alert_id: i64, triggered: bool) -> bool;
+    fn add_token_watch(&self, watch: TokenWatch) -> bool;
+    fn remove_token_watch(&self, user_id: i64, contract: String) -> bool;
+    fn get_token_watch(&self, user_id: i64, contract: String) -> Option<TokenWatch>;
+    fn get_user_token_watches(&self, user_id: i64) -> Vec<TokenWatch>;
+    fn get_all_token_watches_with_user(&self) -> Vec<TokenWatch>;
+    fn set_token_watch_block(&self, watch_id: i64, block: i64) -> bool;
+    fn add_event_watch(&self, watch: EventWatch) -> Option<i64>;
+    fn remove_event_watch(&self, user_id: i64, watch_id: i64) -> bool;
+    fn get_event_watch(&self, watch_id: i64) -> Option<EventWatch>;
+    fn get_user_event_watches(&self, user_id: i64) -> Vec<EventWatch>;
+    fn get_all_event_watches_with_user(&self) -> Vec<EventWatch>;
+    fn set_event_watch_block(&self, watch_id: i64, block: i64) -> bool;
+    fn add_mute(&self, mute: Mute) -> bool;
+    fn get_user_mutes(&self, user_id: i64) -> Vec<Mute>;
+    fn remove_wallet_mutes(&self, user_id: i64, wallet_id: i64) -> bool;
+    fn get_ens_record(&self, key: String, reverse: bool) -> Option<EnsRecord>;
+    fn set_ens_record(&self, record: EnsRecord) -> bool;
+    fn add_contact(&self, contact: Contact) -> bool;
+    fn remove_contact(&self, user_id: i64, address: String) -> bool;
+    fn get_user_contacts(&self, user_id: i64) -> Vec<Contact>;
+    fn set_chat(&self, chat: Chat) -> bool;
+    fn get_chat(&self, chat_id: i64) -> Option<Chat>;
+    fn set_wallet_route(&self, wallet_id: i64, chat_id: i64) -> bool;
+    fn set_user_timezone(&self, user_id: i64, utc_offset: i64, timezone: String) -> bool;
+    fn set_user_quiet_hours(&self, user_id: i64, start: i64, end: i64, mode: String) -> bool;
+    fn set_user_snooze(&self, user_id: i64, until: i64) -> bool;
+    fn add_held_notification(&self, held: HeldNotification) -> bool;
+    fn get_all_held_notifications(&self) -> Vec<HeldNotification>;
+    fn remove_held_notification(&self, held_id: i64) -> bool;
+    fn set_wallet_delivery(&self, wallet_id: i64, delivery: String) -> bool;
+    fn set_wallet_digest_sent(&self, wallet_id: i64, sent_at: i64, until: i64) -> bool;
+    fn get_digest_transactions(&self, wallet_id: i64, after: i64, until: i64) -> Vec<Transaction>;
+    fn set_user_digest_time(&self, user_id: i64, digest_time: i64) -> bool;
+    fn add_queued_delivery(&self, delivery: QueuedDelivery) -> bool;
+    fn get_due_deliveries(&self, now: i64) -> Vec<QueuedDelivery>;
+    fn update_queued_delivery(&self, delivery: &QueuedDelivery) -> bool;
+    fn remove_queued_delivery(&self, delivery_id: i64) -> bool;
+    fn has_queued_source(&self, prefix: String) -> bool;
+    fn add_sink(&self, sink: SinkConfig) -> bool;
+    fn remove_sink(&self, user_id: i64, sink_id: i64) -> bool;
+    fn get_user_sinks(&self, user_id: i64) -> Vec<SinkConfig>;
+    fn get_sink(&self, sink_id: i64) -> Option<SinkConfig>;
+    fn set_user_email(&self, email: EmailAddress) -> bool;
+    fn get_user_email(&self, user_id: i64) -> Option<EmailAddress>;
+    fn remove_user_email(&self, user_id: i64) -> bool;
+    fn set_chat_active(&self, chat_id: i64, active: bool) -> bool;
+    fn remove_queued_deliveries(&self, sink: String, target: String) -> bool;
+    fn set_user_announcements(&self, user_id: i64, enabled: bool) -> bool;
+    fn set_user_release_seen(&self, user_id: i64, version: i64) -> bool;
+    fn get_users(&self, limit: i64, offset: i64) -> Vec<User>;
+    fn set_user_banned(&self, user_id: i64, banned: bool) -> bool;
+    fn set_user_wallet_quota(&self, user_id: i64, quota: i64) -> bool;
+    fn get_stats(&self) -> Stats;
+    fn set_poll_success(&self, address: String, at: i64) -> bool;
+    fn add_poll_error(&self, address: String, error: String, at: i64) -> bool;
+    fn get_poll_statuses(&self) -> Vec<PollStatus>;
+    fn add_page_filter(&self, user_id: i64, filter: &TransactionFilter, created_at: i64) -> Option<i64>;
+    fn get_page_filter(&self, user_id: i64, filter_id: i64) -> Option<TransactionFilter>;
+    fn set_member(&self, member: Member) -> bool;
+    fn get_member(&self, chat_id: i64, user_id: i64) -> Option<Member>;
+    fn add_email_send(&self, chat_id: i64, address: String, sent_at: i64) -> bool;
+    fn count_email_sends(&self, chat_id: i64, address: String, since: i64) -> (i64, i64);
+    fn count_paused_addresses(&self) -> i64;
+    fn count_queued_deliveries(&self, now: i64) -> (i64, i64);
     fn drop(&mut self);
 }
@@ -1,9 +1,26 @@
-use sqlite::{Connection, State};
+use sqlite::{Connection, State, Statement};
 use crate::{AppConfig, DataRepository, logger, logger_l};
+use crate::common::{now, to_checksum_address};
 use crate::models::user::User;
 use crate::models::wallet::Wallet;
 use structopt::StructOpt;
-use crate::models::transaction::Transaction;
+use crate::models::transaction::{Transaction, TransactionFilter};
+use crate::models::balance::Balance;
+use crate::models::alert::Alert;
+use crate::models::token_watch::TokenWatch;
+use crate::models::event_watch::EventWatch;
+use crate::models::mute::Mute;
+use crate::models::ens_record::EnsRecord;
+use crate::models::contact::Contact;
+use crate::models::chat::Chat;
+use crate::models::held_notification::HeldNotification;
+use crate::models::queued_delivery::QueuedDelivery;
+use crate::models::sink_config::SinkConfig;
+use crate::models::email_address::EmailAddress;
+use crate::models::poll_status::PollStatus;
+use crate::models::stats::Stats;
+use crate::models::member::Member;
+use crate::sinks::redact;
 
 pub struct SqliteDb {
     connection: Option<Connection>,
@@ -25,6 +42,130 @@ impl SqliteDb {
         db.load();
         db
     }
+
+    // one-off data migrations, in order. Each runs once per database, the number of applied ones
+    // is kept in `pragma user_version`. Only append to this list.
+    const MIGRATIONS: [fn(&Connection); 5] = [
+        SqliteDb::checksum_wallet_addresses,
+        SqliteDb::digest_watermarks,
+        SqliteDb::release_notes_seen,
+        SqliteDb::unique_poll_statuses,
+        SqliteDb::transaction_statuses,
+    ];
+
+    fn user_version(connection: &Connection) -> usize {
+        let mut statement = connection.prepare(r#"pragma user_version;"#).unwrap();
+        statement.next().unwrap();
+
+        statement.read::<i64>(0).unwrap() as usize
+    }
+
+    fn migrate(connection: &Connection) {
+        if SqliteDb::user_version(connection) >= SqliteDb::MIGRATIONS.len() {
+            return;
+        }
+
+        // connections opened at the same time wait here, then find the migrations already applied.
+        connection.set_busy_timeout(5000).unwrap_or_default();
+        connection.execute(r#"begin immediate;"#).unwrap();
+
+        for (i, migration) in SqliteDb::MIGRATIONS.iter().enumerate().skip(SqliteDb::user_version(connection)) {
+            logger!("-> running migration {}...", i + 1);
+
+            migration(connection);
+            connection.execute(format!("pragma user_version = {};", i + 1)).unwrap();
+        }
+
+        connection.execute(r#"commit;"#).unwrap();
+    }
+
+    // wallets added before checksum normalization may be stored in lower or upper case.
+    fn checksum_wallet_addresses(connection: &Connection) {
+        let mut legacy = vec![];
+
+        let mut statement = connection.prepare(r#"select id, address from wallets where address = lower(address) or substr(address, 3) = upper(substr(address, 3));"#).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            legacy.push((statement.read::<i64>(0).unwrap(), statement.read::<String>(1).unwrap()));
+        }
+
+        for (id, address) in legacy {
+            let mut statement = connection.prepare(r#"update wallets set address = :address where id = :id;"#).unwrap();
+
+            statement.bind_by_name(":address", to_checksum_address(address.as_str()).as_str()).unwrap();
+            statement.bind_by_name(":id", id).unwrap();
+
+            statement.next().unwrap();
+        }
+    }
+
+    // digests used to be cut by block time, they continue from the last period sent.
+    fn digest_watermarks(connection: &Connection) {
+        connection.execute(r#"update wallets set digest_until = digest_sent_at;"#).unwrap();
+    }
+
+    // users from before release notes were tracked already got the first changelog.
+    fn release_notes_seen(connection: &Connection) {
+        connection.execute(r#"update users set release_seen = 1 where release_seen < 1;"#).unwrap();
+    }
+
+    // concurrent workers could insert the same address twice in different cases. The most recently
+    // polled row is kept, addresses are stored lowercased from now on.
+    fn unique_poll_statuses(connection: &Connection) {
+        connection.execute(r#"delete from poll_status where id <> (select q.id from poll_status q where lower(q.address) = lower(poll_status.address) order by q.last_success_at desc, q.id desc limit 1);"#).unwrap();
+        connection.execute(r#"update poll_status set address = lower(address);"#).unwrap();
+        connection.execute(r#"create unique index if not exists poll_status_address_uindex on poll_status (address);"#).unwrap();
+    }
+
+    // the first releases stored the status as text, for eth transfers it held isError so "true" meant failed.
+    fn transaction_statuses(connection: &Connection) {
+        connection.execute(r#"update transactions set status = case when status = 'true' then 0 else 1 end where token = 'ETH' and status in ('true', 'false');"#).unwrap();
+        connection.execute(r#"update transactions set status = case when status = 'true' then 1 else 0 end where token <> 'ETH' and status in ('true', 'false');"#).unwrap();
+    }
+
+    fn transaction_filter_clause(filter: &TransactionFilter) -> String {
+        let mut clause = r#"wallet_id = :wallet_id"#.to_string();
+
+        if filter.token.is_some() {
+            clause.push_str(r#" and (lower(token) = lower(:token) or lower(symbol) = lower(:token) or lower(received_token) = lower(:token))"#);
+        }
+
+        match filter.incoming {
+            Some(true) => clause.push_str(r#" and lower("to") = lower(:address)"#),
+            Some(false) => clause.push_str(r#" and lower("from") = lower(:address)"#),
+            None => {}
+        }
+
+        if filter.from.is_some() {
+            clause.push_str(r#" and timestamp >= :from_time"#);
+        }
+
+        if filter.to.is_some() {
+            clause.push_str(r#" and timestamp <= :to_time"#);
+        }
+
+        clause
+    }
+
+    fn bind_transaction_filter(statement: &mut Statement, wallet_id: i64, wallet_address: &str, filter: &TransactionFilter) {
+        statement.bind_by_name(":wallet_id", wallet_id).unwrap();
+
+        if let Some(token) = &filter.token {
+            statement.bind_by_name(":token", token.as_str()).unwrap();
+        }
+
+        if filter.incoming.is_some() {
+            statement.bind_by_name(":address", wallet_address).unwrap();
+        }
+
+        if let Some(from) = filter.from {
+            statement.bind_by_name(":from_time", from).unwrap();
+        }
+
+        if let Some(to) = filter.to {
+            statement.bind_by_name(":to_time", to).unwrap();
+        }
+    }
 }
 
 impl DataRepository for SqliteDb {
@@ -57,17 +198,138 @@ impl DataRepository for SqliteDb {
             panic!("Error configuring users table: {}", e);
         }
 
+        connection.execute(r#"alter table users add currency varchar default 'USD';"#).unwrap_or_default();
+        connection.execute(r#"alter table users add template varchar default '';"#).unwrap_or_default();
+        connection.execute(r#"alter table users add utc_offset integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add quiet_start integer default -1;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add quiet_end integer default -1;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add quiet_mode varchar default 'silent';"#).unwrap_or_default();
+        connection.execute(r#"alter table users add snooze_until integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add digest_time integer default 540;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add announcements integer default 1;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add release_seen integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add banned integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add wallet_quota integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table users add timezone varchar default '';"#).unwrap_or_default();
+
         let result = connection.execute(r#"create table if not exists wallets ("id" integer not null constraint wallets_pk primary key autoincrement, "user_id" integer not null constraint wallets_users_id_fk references users (id) on update cascade on delete cascade, "address" varchar not null);"#);
         if let Err(e) = result {
             panic!("Error configuring wallets table: {}", e);
         }
 
+        connection.execute(r#"alter table wallets add label varchar default '';"#).unwrap_or_default();
+        connection.execute(r#"alter table wallets add route_chat_id integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table wallets add delivery varchar default 'instant';"#).unwrap_or_default();
+        connection.execute(r#"alter table wallets add digest_sent_at integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table wallets add digest_until integer default 0;"#).unwrap_or_default();
+
         let result = connection.execute(r#"create table if not exists transactions( "id" integer constraint transactions_pk primary key autoincrement, "from" varchar not null, "wallet_id" integer not null constraint transactions_wallets_id_fk references transactions(id) on update cascade on delete cascade, "to" varchar not null, "amount" varchar not null, "tx_hash" varchar not null, "status" bool default TRUE, "token" varchar not null);"#);
         if let Err(e) = result {
             panic!("Error on configuring transactions table: {}", e);
         }
 
         connection.execute(r#"alter table transactions add decimal integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add kind varchar default 'transfer';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add received_token varchar default '';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add received_amount varchar default '0';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add received_decimal integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add block_number integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add timestamp integer default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add symbol varchar default '';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add fiat_value real default 0;"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add fiat_currency varchar default '';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add fee varchar default '0';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add received_symbol varchar default '';"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add log_index integer;"#).unwrap_or_default();
+        connection.execute(r#"alter table transactions add created_at integer default 0;"#).unwrap_or_default();
+
+        let result = connection.execute(r#"create table if not exists balances ("id" integer not null constraint balances_pk primary key autoincrement, "address" varchar not null, "contract" varchar not null, "token" varchar not null, "decimal" integer default 0, "amount" varchar not null, "updated_at" integer not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring balances table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists alerts ("id" integer not null constraint alerts_pk primary key autoincrement, "user_id" integer not null constraint alerts_users_id_fk references users (id) on update cascade on delete cascade, "address" varchar not null, "token" varchar not null, "direction" varchar not null, "threshold" real not null, "triggered" integer default 0);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring alerts table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists token_watches ("id" integer not null constraint token_watches_pk primary key autoincrement, "user_id" integer not null constraint token_watches_users_id_fk references users (id) on update cascade on delete cascade, "contract" varchar not null, "threshold" real not null, "last_block" integer default 0);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring token_watches table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists event_watches ("id" integer not null constraint event_watches_pk primary key autoincrement, "user_id" integer not null constraint event_watches_users_id_fk references users (id) on update cascade on delete cascade, "contract" varchar not null, "signature" varchar not null, "last_block" integer default 0);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring event_watches table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists mutes ("id" integer not null constraint mutes_pk primary key autoincrement, "user_id" integer not null constraint mutes_users_id_fk references users (id) on update cascade on delete cascade, "wallet_id" integer not null, "token" varchar default '', "until" integer default 0);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring mutes table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists ens_records ("id" integer not null constraint ens_records_pk primary key autoincrement, "name" varchar not null, "address" varchar not null, "reverse" integer default 0, "updated_at" integer not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring ens_records table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists contacts ("id" integer not null constraint contacts_pk primary key autoincrement, "user_id" integer not null constraint contacts_users_id_fk references users (id) on update cascade on delete cascade, "address" varchar not null, "name" varchar not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring contacts table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists chats ("id" integer not null constraint chats_pk primary key autoincrement, "chat_id" integer not null, "kind" varchar not null, "title" varchar default '', "added_by" integer default 0);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring chats table: {}", e);
+        }
+
+        connection.execute(r#"alter table chats add active integer default 1;"#).unwrap_or_default();
+
+        let result = connection.execute(r#"create table if not exists held_notifications ("id" integer not null constraint held_notifications_pk primary key autoincrement, "user_id" integer not null constraint held_notifications_users_id_fk references users (id) on update cascade on delete cascade, "wallet_id" integer not null, "chat_id" integer not null, "text" varchar not null, "created_at" integer not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring held_notifications table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists delivery_queue ("id" integer not null constraint delivery_queue_pk primary key autoincrement, "sink" varchar not null, "target" varchar not null, "payload" varchar not null, "attempts" integer default 0, "next_attempt_at" integer default 0, "last_error" varchar default '');"#);
+        if let Err(e) = result {
+            panic!("Error on configuring delivery_queue table: {}", e);
+        }
+
+        connection.execute(r#"alter table delivery_queue add source varchar default '';"#).unwrap_or_default();
+
+        let result = connection.execute(r#"create table if not exists sinks ("id" integer not null constraint sinks_pk primary key autoincrement, "user_id" integer not null, "wallet_id" integer default 0, "kind" varchar not null, "target" varchar not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring sinks table: {}", e);
+        }
+
+        connection.execute(r#"alter table sinks add secret varchar default '';"#).unwrap_or_default();
+
+        let result = connection.execute(r#"create table if not exists emails ("id" integer not null constraint emails_pk primary key autoincrement, "user_id" integer not null constraint emails_user_id_uindex unique, "address" varchar not null, "verified" integer default 0, "code" varchar default '', "code_expires_at" integer default 0, "attempts" integer default 0);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring emails table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists poll_status ("id" integer not null constraint poll_status_pk primary key autoincrement, "address" varchar not null, "last_success_at" integer default 0, "last_error_at" integer default 0, "errors" integer default 0, "last_error" varchar default '');"#);
+        if let Err(e) = result {
+            panic!("Error on configuring poll_status table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists page_filters ("id" integer not null constraint page_filters_pk primary key autoincrement, "user_id" integer not null constraint page_filters_users_id_fk references users (id) on update cascade on delete cascade, "filter" varchar not null, "created_at" integer not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring page_filters table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists members ("id" integer not null constraint members_pk primary key autoincrement, "chat_id" integer not null, "user_id" integer not null, "role" varchar not null, "updated_at" integer not null, constraint members_chat_user_uindex unique (chat_id, user_id));"#);
+        if let Err(e) = result {
+            panic!("Error on configuring members table: {}", e);
+        }
+
+        let result = connection.execute(r#"create table if not exists email_sends ("id" integer not null constraint email_sends_pk primary key autoincrement, "chat_id" integer not null, "address" varchar not null, "sent_at" integer not null);"#);
+        if let Err(e) = result {
+            panic!("Error on configuring email_sends table: {}", e);
+        }
+
+        SqliteDb::migrate(connection);
     }
 
     fn connected(&self) -> bool {
@@ -113,10 +375,7 @@ impl DataRepository for SqliteDb {
             None
         } else {
             logger!("-> user with chat id {} found", chat_id);
-            Some(User::new(
-                statement.read::<String>(1).unwrap(),
-                Some(statement.read::<i64>(0).unwrap()),
-            ))
+            Some(User::read_from_statement(&statement, 0))
         };
     }
 
@@ -134,10 +393,7 @@ impl DataRepository for SqliteDb {
         let mut users = vec![];
 
         while let State::Row = statement.next().unwrap() {
-            users.push(User::new(
-                statement.read::<String>(1).unwrap(),
-                Some(statement.read::<i64>(0).unwrap()),
-            ))
+            users.push(User::read_from_statement(&statement, 0))
         }
 
         logger!("{} user retrieved", users.len());
@@ -166,6 +422,36 @@ impl DataRepository for SqliteDb {
         true
     }
 
+    // (address, label) pairs inserted in one sqlite transaction.
+    fn add_wallets(&self, user_id: i64, wallets: &Vec<(String, String)>) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding {} wallets for user {}...", wallets.len(), user_id);
+
+        connection.execute("begin;").unwrap();
+
+        for (address, label) in wallets {
+            let mut statement = connection
+                .prepare(r#"insert into wallets (user_id, address, label) values (:user_id,:address,:label);"#).unwrap();
+
+            statement.bind_by_name(":user_id", user_id).unwrap();
+            statement.bind_by_name(":address", address.as_str()).unwrap();
+            statement.bind_by_name(":label", label.as_str()).unwrap();
+
+            statement.next().unwrap();
+        }
+
+        connection.execute("commit;").unwrap();
+
+        logger!("-> wallets added successfully");
+
+        true
+    }
+
     fn remove_wallet(&self, user_id: i64, wallet_address: String) -> bool {
         if !self.connected() {
             panic!("Connection error.");
@@ -175,9 +461,17 @@ impl DataRepository for SqliteDb {
 
         logger!("-> remove wallet {} for user {}...", wallet_address, user_id);
 
-        let mut statement = connection.prepare(r#"delete from wallets where address = :wallet_address;"#).unwrap();
+        let mut statement = connection.prepare(r#"delete from sinks where user_id = :user_id and wallet_id in (select id from wallets where lower(address) = lower(:wallet_address) and user_id = :user_id);"#).unwrap();
+
+        statement.bind_by_name(":wallet_address", wallet_address.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        let mut statement = connection.prepare(r#"delete from wallets where lower(address) = lower(:wallet_address) and user_id = :user_id;"#).unwrap();
 
         statement.bind_by_name(":wallet_address", wallet_address.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
 
         statement.next().unwrap();
 
@@ -197,7 +491,7 @@ impl DataRepository for SqliteDb {
             logger!("-> retrieving wallet with user {} and wallet {}...", u, wallet_address);
 
             let mut statement = connection
-                .prepare(r#"select * from wallets where address = :address and user_id = :user_id;"#).unwrap();
+                .prepare(r#"select * from wallets where lower(address) = lower(:address) and user_id = :user_id;"#).unwrap();
 
             statement.bind_by_name(":user_id", u).unwrap();
 
@@ -205,7 +499,7 @@ impl DataRepository for SqliteDb {
         } else {
             logger!("-> retrieving wallet with wallet {}...", wallet_address);
 
-            connection.prepare(r#"select * from wallets where address = :address;"#).unwrap()
+            connection.prepare(r#"select * from wallets where lower(address) = lower(:address);"#).unwrap()
         };
 
         statement.bind_by_name(":address", wallet_address.as_str()).unwrap();
@@ -239,15 +533,29 @@ impl DataRepository for SqliteDb {
 
         logger!("-> adding transaction for wallet {}...", transaction.wallet_id);
 
-        let mut statement = connection.prepare(r#"insert into transactions ("from", wallet_id, "to", amount, tx_hash, status, token) values (:from, :wallet_id, :to, :amount, :tx_hash, :status, :token);"#).unwrap();
+        let mut statement = connection.prepare(r#"insert into transactions ("from", wallet_id, "to", amount, tx_hash, status, token, decimal, kind, received_token, received_amount, received_decimal, block_number, timestamp, symbol, fiat_value, fiat_currency, fee, received_symbol, log_index, created_at) values (:from, :wallet_id, :to, :amount, :tx_hash, :status, :token, :decimal, :kind, :received_token, :received_amount, :received_decimal, :block_number, :timestamp, :symbol, :fiat_value, :fiat_currency, :fee, :received_symbol, :log_index, :created_at);"#).unwrap();
 
         statement.bind_by_name(":from", transaction.from.as_str()).unwrap();
         statement.bind_by_name(":wallet_id", transaction.wallet_id).unwrap();
         statement.bind_by_name(":to", transaction.to.as_str()).unwrap();
         statement.bind_by_name(":amount", transaction.amount.as_str()).unwrap();
         statement.bind_by_name(":tx_hash", transaction.tx_hash.as_str()).unwrap();
-        statement.bind_by_name(":status", transaction.status.to_string().as_str()).unwrap();
+        statement.bind_by_name(":status", transaction.status as i64).unwrap();
         statement.bind_by_name(":token", transaction.token.as_str()).unwrap();
+        statement.bind_by_name(":decimal", transaction.decimal).unwrap();
+        statement.bind_by_name(":kind", transaction.kind.as_str()).unwrap();
+        statement.bind_by_name(":received_token", transaction.received_token.as_str()).unwrap();
+        statement.bind_by_name(":received_amount", transaction.received_amount.as_str()).unwrap();
+        statement.bind_by_name(":received_decimal", transaction.received_decimal).unwrap();
+        statement.bind_by_name(":block_number", transaction.block_number).unwrap();
+        statement.bind_by_name(":timestamp", transaction.timestamp).unwrap();
+        statement.bind_by_name(":symbol", transaction.symbol.as_str()).unwrap();
+        statement.bind_by_name(":fiat_value", transaction.fiat_value).unwrap();
+        statement.bind_by_name(":fiat_currency", transaction.fiat_currency.as_str()).unwrap();
+        statement.bind_by_name(":fee", transaction.fee.as_str()).unwrap();
+        statement.bind_by_name(":received_symbol", transaction.received_symbol.as_str()).unwrap();
+        statement.bind_by_name(":log_index", transaction.log_index).unwrap();
+        statement.bind_by_name(":created_at", now()).unwrap();
 
         statement.next().unwrap();
 
@@ -268,13 +576,13 @@ impl DataRepository for SqliteDb {
 
             let mut statement = if let Some(token) = token_name {
                 let mut statement = connection
-                    .prepare(r#"select * from transactions where tx_hash = :tx_hash and wallet_id = :wallet_id and token = :token;"#).unwrap();
+                    .prepare(r#"select * from transactions where lower(tx_hash) = lower(:tx_hash) and wallet_id = :wallet_id and token = :token;"#).unwrap();
 
                 statement.bind_by_name(":token", token.as_str()).unwrap();
 
                 statement
             } else {
-                connection.prepare(r#"select * from transactions where tx_hash = :tx_hash and wallet_id = :wallet_id;"#).unwrap()
+                connection.prepare(r#"select * from transactions where lower(tx_hash) = lower(:tx_hash) and wallet_id = :wallet_id;"#).unwrap()
             };
 
             statement.bind_by_name(":wallet_id", u).unwrap();
@@ -285,13 +593,13 @@ impl DataRepository for SqliteDb {
 
             if let Some(token) = token_name {
                 let mut statement = connection
-                    .prepare(r#"select * from transactions where tx_hash = :tx_hash and token = :token;"#).unwrap();
+                    .prepare(r#"select * from transactions where lower(tx_hash) = lower(:tx_hash) and token = :token;"#).unwrap();
 
                 statement.bind_by_name(":token", token.as_str()).unwrap();
 
                 statement
             } else {
-                connection.prepare(r#"select * from transactions where tx_hash = :tx_hash;"#).unwrap()
+                connection.prepare(r#"select * from transactions where lower(tx_hash) = lower(:tx_hash);"#).unwrap()
             }
         };
 
@@ -317,6 +625,27 @@ impl DataRepository for SqliteDb {
         };
     }
 
+    // rows stored before log indexes were saved are matched by token instead.
+    fn has_transfer(&self, tx_hash: String, wallet_id: i64, log_index: i64, token_name: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving transfer {} #{} for wallet {}...", tx_hash, log_index, wallet_id);
+
+        let mut statement = connection
+            .prepare(r#"select id from transactions where lower(tx_hash) = lower(:tx_hash) and wallet_id = :wallet_id and (log_index = :log_index or (log_index is null and token = :token));"#).unwrap();
+
+        statement.bind_by_name(":tx_hash", tx_hash.as_str()).unwrap();
+        statement.bind_by_name(":wallet_id", wallet_id).unwrap();
+        statement.bind_by_name(":log_index", log_index).unwrap();
+        statement.bind_by_name(":token", token_name.as_str()).unwrap();
+
+        statement.next().unwrap() == State::Row
+    }
+
     fn get_all_transactions(&self, wallet_id: i64) -> Vec<Transaction> {
         let mut res = vec![];
 
@@ -341,6 +670,51 @@ impl DataRepository for SqliteDb {
         res
     }
 
+    fn get_transactions(&self, wallet_id: i64, wallet_address: String, filter: &TransactionFilter, limit: i64, offset: i64) -> Vec<Transaction> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving {} transactions of wallet {} from offset {}...", limit, wallet_id, offset);
+
+        let query = format!(r#"select * from transactions where {} order by timestamp desc, id desc limit :limit offset :offset;"#,
+                            SqliteDb::transaction_filter_clause(filter));
+        let mut statement = connection.prepare(query).unwrap();
+
+        SqliteDb::bind_transaction_filter(&mut statement, wallet_id, wallet_address.as_str(), filter);
+        statement.bind_by_name(":limit", limit).unwrap();
+        statement.bind_by_name(":offset", offset).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(Transaction::read_from_statement(&statement));
+        }
+
+        logger!("-> {} transactions retrieved.", res.len());
+
+        res
+    }
+
+    fn count_transactions(&self, wallet_id: i64, wallet_address: String, filter: &TransactionFilter) -> i64 {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let query = format!(r#"select count(*) from transactions where {};"#, SqliteDb::transaction_filter_clause(filter));
+        let mut statement = connection.prepare(query).unwrap();
+
+        SqliteDb::bind_transaction_filter(&mut statement, wallet_id, wallet_address.as_str(), filter);
+
+        statement.next().unwrap();
+
+        statement.read::<i64>(0).unwrap()
+    }
+
     fn get_all_wallets_with_user(&self) -> Vec<Wallet> {
         let mut res = vec![];
 
@@ -352,15 +726,12 @@ impl DataRepository for SqliteDb {
 
         logger!("-> retrieving all wallets from database...");
 
-        let mut statement = connection.prepare(r#"select * from wallets inner join users on users.id = wallets.user_id;"#).unwrap();
+        let mut statement = connection.prepare(r#"select wallets.*, users.* from wallets inner join users on users.id = wallets.user_id;"#).unwrap();
 
         while let State::Row = statement.next().unwrap() {
             let mut wallet = Wallet::read_from_statement(&statement);
 
-            wallet.user = Some(User::new(
-                statement.read::<String>(4).unwrap(),
-                Some(statement.read::<i64>(3).unwrap()),
-            ));
+            wallet.user = Some(User::read_from_statement(&statement, Wallet::COLUMNS));
 
             res.push(wallet);
         }
@@ -396,11 +767,1647 @@ impl DataRepository for SqliteDb {
         res
     }
 
-    fn drop(&mut self) {
-        if self.connection.is_none() {
-            return;
+    fn set_user_currency(&self, user_id: i64, currency: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
         }
 
-        self.connection = None;
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting currency {} for user {}...", currency, user_id);
+
+        let mut statement = connection.prepare(r#"update users set currency = :currency where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":currency", currency.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> currency set successfully");
+
+        true
+    }
+
+    fn set_user_template(&self, user_id: i64, template: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting notification template for user {}...", user_id);
+
+        let mut statement = connection.prepare(r#"update users set template = :template where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":template", template.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> template set successfully");
+
+        true
+    }
+
+    fn set_balances(&self, wallet_address: String, balances: &Vec<Balance>) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> caching {} balances for wallet {}...", balances.len(), wallet_address);
+
+        let mut statement = connection.prepare(r#"delete from balances where lower(address) = lower(:address);"#).unwrap();
+
+        statement.bind_by_name(":address", wallet_address.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        for balance in balances {
+            let mut statement = connection.prepare(r#"insert into balances (address, contract, token, decimal, amount, updated_at) values (:address, :contract, :token, :decimal, :amount, :updated_at);"#).unwrap();
+
+            statement.bind_by_name(":address", wallet_address.as_str()).unwrap();
+            statement.bind_by_name(":contract", balance.contract.as_str()).unwrap();
+            statement.bind_by_name(":token", balance.token.as_str()).unwrap();
+            statement.bind_by_name(":decimal", balance.decimal).unwrap();
+            statement.bind_by_name(":amount", balance.amount.as_str()).unwrap();
+            statement.bind_by_name(":updated_at", balance.updated_at).unwrap();
+
+            statement.next().unwrap();
+        }
+
+        logger!("-> balances cached successfully");
+
+        true
+    }
+
+    fn get_balances(&self, wallet_address: String) -> Vec<Balance> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving cached balances for wallet {}...", wallet_address);
+
+        let mut statement = connection.prepare(r#"select * from balances where lower(address) = lower(:address);"#).unwrap();
+
+        statement.bind_by_name(":address", wallet_address.as_str()).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(Balance::read_from_statement(&statement));
+        }
+
+        logger!("-> {} balances retrieved.", res.len());
+
+        res
+    }
+
+    fn add_alert(&self, alert: Alert) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding alert for user {}...", alert.user_id);
+
+        let mut statement = connection.prepare(r#"insert into alerts (user_id, address, token, direction, threshold, triggered) values (:user_id, :address, :token, :direction, :threshold, 0);"#).unwrap();
+
+        statement.bind_by_name(":user_id", alert.user_id).unwrap();
+        statement.bind_by_name(":address", alert.address.as_str()).unwrap();
+        statement.bind_by_name(":token", alert.token.as_str()).unwrap();
+        statement.bind_by_name(":direction", alert.direction.as_str()).unwrap();
+        statement.bind_by_name(":threshold", alert.threshold).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> alert added successfully");
+
+        true
+    }
+
+    fn remove_alert(&self, user_id: i64, alert_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> removing alert {} for user {}...", alert_id, user_id);
+
+        let mut statement = connection.prepare(r#"delete from alerts where id = :id and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":id", alert_id).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> alert removed successfully.");
+
+        true
+    }
+
+    fn get_user_alerts(&self, user_id: i64) -> Vec<Alert> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving alerts for user {} from database...", user_id);
+
+        let mut statement = connection.prepare(r#"select * from alerts where user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(Alert::read_from_statement(&statement));
+        }
+
+        logger!("-> {} alerts retrieved.", res.len());
+
+        res
+    }
+
+    fn get_all_alerts_with_user(&self) -> Vec<Alert> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving all alerts from database...");
+
+        let mut statement = connection.prepare(r#"select alerts.*, users.* from alerts inner join users on users.id = alerts.user_id;"#).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            let mut alert = Alert::read_from_statement(&statement);
+
+            alert.user = Some(User::read_from_statement(&statement, Alert::COLUMNS));
+
+            res.push(alert);
+        }
+
+        logger!("-> {} alerts retrieved.", res.len());
+
+        res
+    }
+
+    fn set_alert_triggered(&self, alert_id: i64, triggered: bool) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"update alerts set triggered = :triggered where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":triggered", triggered as i64).unwrap();
+        statement.bind_by_name(":id", alert_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn add_token_watch(&self, watch: TokenWatch) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding token watch {} for user {}...", watch.contract, watch.user_id);
+
+        let mut statement = connection.prepare(r#"insert into token_watches (user_id, contract, threshold, last_block) values (:user_id, :contract, :threshold, 0);"#).unwrap();
+
+        statement.bind_by_name(":user_id", watch.user_id).unwrap();
+        statement.bind_by_name(":contract", watch.contract.as_str()).unwrap();
+        statement.bind_by_name(":threshold", watch.threshold).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> token watch added successfully");
+
+        true
+    }
+
+    fn remove_token_watch(&self, user_id: i64, contract: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> removing token watch {} for user {}...", contract, user_id);
+
+        let mut statement = connection.prepare(r#"delete from token_watches where lower(contract) = lower(:contract) and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":contract", contract.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> token watch removed successfully.");
+
+        true
+    }
+
+    fn get_token_watch(&self, user_id: i64, contract: String) -> Option<TokenWatch> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving token watch {} for user {}...", contract, user_id);
+
+        let mut statement = connection.prepare(r#"select * from token_watches where lower(contract) = lower(:contract) and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":contract", contract.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        let state = statement.next().unwrap();
+        return if state == State::Done {
+            logger!("-> token watch {} notfound for user {}", contract, user_id);
+            None
+        } else {
+            logger!("-> token watch {} found for user {}", contract, user_id);
+            Some(TokenWatch::read_from_statement(&statement))
+        };
+    }
+
+    fn get_user_token_watches(&self, user_id: i64) -> Vec<TokenWatch> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving token watches for user {} from database...", user_id);
+
+        let mut statement = connection.prepare(r#"select * from token_watches where user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(TokenWatch::read_from_statement(&statement));
+        }
+
+        logger!("-> {} token watches retrieved.", res.len());
+
+        res
+    }
+
+    fn get_all_token_watches_with_user(&self) -> Vec<TokenWatch> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving all token watches from database...");
+
+        let mut statement = connection.prepare(r#"select token_watches.*, users.* from token_watches inner join users on users.id = token_watches.user_id;"#).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            let mut watch = TokenWatch::read_from_statement(&statement);
+
+            watch.user = Some(User::read_from_statement(&statement, TokenWatch::COLUMNS));
+
+            res.push(watch);
+        }
+
+        logger!("-> {} token watches retrieved.", res.len());
+
+        res
+    }
+
+    fn set_token_watch_block(&self, watch_id: i64, block: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"update token_watches set last_block = :last_block where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":last_block", block).unwrap();
+        statement.bind_by_name(":id", watch_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn add_event_watch(&self, watch: EventWatch) -> Option<i64> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding event watch {} on {} for user {}...", watch.signature, watch.contract, watch.user_id);
+
+        let mut statement = connection.prepare(r#"insert into event_watches (user_id, contract, signature, last_block) values (:user_id, :contract, :signature, 0);"#).unwrap();
+
+        statement.bind_by_name(":user_id", watch.user_id).unwrap();
+        statement.bind_by_name(":contract", watch.contract.as_str()).unwrap();
+        statement.bind_by_name(":signature", watch.signature.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        let mut statement = connection.prepare(r#"select last_insert_rowid();"#).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            logger!("-> event watch added successfully");
+            return Some(statement.read::<i64>(0).unwrap());
+        }
+
+        None
+    }
+
+    fn remove_event_watch(&self, user_id: i64, watch_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> removing event watch {} for user {}...", watch_id, user_id);
+
+        let mut statement = connection.prepare(r#"delete from event_watches where id = :id and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":id", watch_id).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> event watch removed successfully.");
+
+        true
+    }
+
+    fn get_event_watch(&self, watch_id: i64) -> Option<EventWatch> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from event_watches where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":id", watch_id).unwrap();
+
+        let state = statement.next().unwrap();
+        return if state == State::Done {
+            logger!("-> event watch {} notfound", watch_id);
+            None
+        } else {
+            Some(EventWatch::read_from_statement(&statement))
+        };
+    }
+
+    fn get_user_event_watches(&self, user_id: i64) -> Vec<EventWatch> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving event watches for user {} from database...", user_id);
+
+        let mut statement = connection.prepare(r#"select * from event_watches where user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(EventWatch::read_from_statement(&statement));
+        }
+
+        logger!("-> {} event watches retrieved.", res.len());
+
+        res
+    }
+
+    fn get_all_event_watches_with_user(&self) -> Vec<EventWatch> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving all event watches from database...");
+
+        let mut statement = connection.prepare(r#"select event_watches.*, users.* from event_watches inner join users on users.id = event_watches.user_id;"#).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            let mut watch = EventWatch::read_from_statement(&statement);
+
+            watch.user = Some(User::read_from_statement(&statement, EventWatch::COLUMNS));
+
+            res.push(watch);
+        }
+
+        logger!("-> {} event watches retrieved.", res.len());
+
+        res
+    }
+
+    fn set_event_watch_block(&self, watch_id: i64, block: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"update event_watches set last_block = :last_block where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":last_block", block).unwrap();
+        statement.bind_by_name(":id", watch_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn add_mute(&self, mute: Mute) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> muting wallet {} for user {}...", mute.wallet_id, mute.user_id);
+
+        let mut statement = connection.prepare(r#"insert into mutes (user_id, wallet_id, token, until) values (:user_id, :wallet_id, :token, :until);"#).unwrap();
+
+        statement.bind_by_name(":user_id", mute.user_id).unwrap();
+        statement.bind_by_name(":wallet_id", mute.wallet_id).unwrap();
+        statement.bind_by_name(":token", mute.token.as_str()).unwrap();
+        statement.bind_by_name(":until", mute.until).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> mute added successfully");
+
+        true
+    }
+
+    fn get_user_mutes(&self, user_id: i64) -> Vec<Mute> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from mutes where user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(Mute::read_from_statement(&statement));
+        }
+
+        res
+    }
+
+    fn remove_wallet_mutes(&self, user_id: i64, wallet_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> unmuting wallet {} for user {}...", wallet_id, user_id);
+
+        let mut statement = connection.prepare(r#"delete from mutes where user_id = :user_id and wallet_id = :wallet_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+        statement.bind_by_name(":wallet_id", wallet_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> wallet unmuted successfully.");
+
+        true
+    }
+
+    // forward records are keyed by name, reverse ones by address.
+    fn get_ens_record(&self, key: String, reverse: bool) -> Option<EnsRecord> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving ens record for {}...", key);
+
+        let mut statement = if reverse {
+            connection.prepare(r#"select * from ens_records where lower(address) = lower(:key) and reverse = 1;"#).unwrap()
+        } else {
+            connection.prepare(r#"select * from ens_records where lower(name) = lower(:key) and reverse = 0;"#).unwrap()
+        };
+
+        statement.bind_by_name(":key", key.as_str()).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            Some(EnsRecord::read_from_statement(&statement))
+        } else {
+            None
+        }
+    }
+
+    fn set_ens_record(&self, record: EnsRecord) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> caching ens record {} -> {}...", record.name, record.address);
+
+        let mut statement = if record.reverse {
+            connection.prepare(r#"delete from ens_records where lower(address) = lower(:key) and reverse = 1;"#).unwrap()
+        } else {
+            connection.prepare(r#"delete from ens_records where lower(name) = lower(:key) and reverse = 0;"#).unwrap()
+        };
+
+        statement.bind_by_name(":key", if record.reverse { record.address.as_str() } else { record.name.as_str() }).unwrap();
+
+        statement.next().unwrap();
+
+        let mut statement = connection.prepare(r#"insert into ens_records (name, address, reverse, updated_at) values (:name, :address, :reverse, :updated_at);"#).unwrap();
+
+        statement.bind_by_name(":name", record.name.as_str()).unwrap();
+        statement.bind_by_name(":address", record.address.as_str()).unwrap();
+        statement.bind_by_name(":reverse", record.reverse as i64).unwrap();
+        statement.bind_by_name(":updated_at", record.updated_at).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> ens record cached successfully");
+
+        true
+    }
+
+    // an existing contact with the same address is renamed.
+    fn add_contact(&self, contact: Contact) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding contact for user {}...", contact.user_id);
+
+        self.remove_contact(contact.user_id, contact.address.clone());
+
+        let mut statement = connection.prepare(r#"insert into contacts (user_id, address, name) values (:user_id, :address, :name);"#).unwrap();
+
+        statement.bind_by_name(":user_id", contact.user_id).unwrap();
+        statement.bind_by_name(":address", contact.address.as_str()).unwrap();
+        statement.bind_by_name(":name", contact.name.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> contact added successfully");
+
+        true
+    }
+
+    fn remove_contact(&self, user_id: i64, address: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> removing contact {} for user {}...", address, user_id);
+
+        let mut statement = connection.prepare(r#"delete from contacts where lower(address) = lower(:address) and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":address", address.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> contact removed successfully.");
+
+        true
+    }
+
+    fn get_user_contacts(&self, user_id: i64) -> Vec<Contact> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving contacts for user {} from database...", user_id);
+
+        let mut statement = connection.prepare(r#"select * from contacts where user_id = :user_id order by name;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(Contact::read_from_statement(&statement));
+        }
+
+        logger!("-> {} contacts retrieved.", res.len());
+
+        res
+    }
+
+    // keeps the first added_by, only the kind and title of a known chat are refreshed.
+    fn set_chat(&self, chat: Chat) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> saving chat {}...", chat.chat_id);
+
+        let mut statement = if self.get_chat(chat.chat_id).is_some() {
+            connection.prepare(r#"update chats set kind = :kind, title = :title, active = 1 where chat_id = :chat_id;"#).unwrap()
+        } else {
+            let mut statement = connection.prepare(r#"insert into chats (chat_id, kind, title, added_by) values (:chat_id, :kind, :title, :added_by);"#).unwrap();
+
+            statement.bind_by_name(":added_by", chat.added_by).unwrap();
+
+            statement
+        };
+
+        statement.bind_by_name(":chat_id", chat.chat_id).unwrap();
+        statement.bind_by_name(":kind", chat.kind.as_str()).unwrap();
+        statement.bind_by_name(":title", chat.title.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> chat saved successfully");
+
+        true
+    }
+
+    fn get_chat(&self, chat_id: i64) -> Option<Chat> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from chats where chat_id = :chat_id;"#).unwrap();
+
+        statement.bind_by_name(":chat_id", chat_id).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            Some(Chat::read_from_statement(&statement))
+        } else {
+            None
+        }
+    }
+
+    // chat_id 0 delivers to the owner chat again.
+    fn set_wallet_route(&self, wallet_id: i64, chat_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> routing wallet {} to chat {}...", wallet_id, chat_id);
+
+        let mut statement = connection.prepare(r#"update wallets set route_chat_id = :chat_id where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":chat_id", chat_id).unwrap();
+        statement.bind_by_name(":id", wallet_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> wallet routed successfully");
+
+        true
+    }
+
+    // timezone is an IANA name or empty when only a fixed offset was given.
+    fn set_user_timezone(&self, user_id: i64, utc_offset: i64, timezone: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting timezone for user {}...", user_id);
+
+        let mut statement = connection.prepare(r#"update users set utc_offset = :utc_offset, timezone = :timezone where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":utc_offset", utc_offset).unwrap();
+        statement.bind_by_name(":timezone", timezone.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> timezone set successfully");
+
+        true
+    }
+
+    // start and end are minutes since local midnight, -1 turns quiet hours off.
+    fn set_user_quiet_hours(&self, user_id: i64, start: i64, end: i64, mode: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting quiet hours for user {}...", user_id);
+
+        let mut statement = connection.prepare(r#"update users set quiet_start = :start, quiet_end = :end, quiet_mode = :mode where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":start", start).unwrap();
+        statement.bind_by_name(":end", end).unwrap();
+        statement.bind_by_name(":mode", mode.as_str()).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> quiet hours set successfully");
+
+        true
+    }
+
+    fn set_user_snooze(&self, user_id: i64, until: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> snoozing user {} until {}...", user_id, until);
+
+        let mut statement = connection.prepare(r#"update users set snooze_until = :until where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":until", until).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> snooze set successfully");
+
+        true
+    }
+
+    fn add_held_notification(&self, held: HeldNotification) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> holding notification for user {}...", held.user_id);
+
+        let mut statement = connection.prepare(r#"insert into held_notifications (user_id, wallet_id, chat_id, text, created_at) values (:user_id, :wallet_id, :chat_id, :text, :created_at);"#).unwrap();
+
+        statement.bind_by_name(":user_id", held.user_id).unwrap();
+        statement.bind_by_name(":wallet_id", held.wallet_id).unwrap();
+        statement.bind_by_name(":chat_id", held.chat_id).unwrap();
+        statement.bind_by_name(":text", held.text.as_str()).unwrap();
+        statement.bind_by_name(":created_at", held.created_at).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> notification held successfully");
+
+        true
+    }
+
+    fn get_all_held_notifications(&self) -> Vec<HeldNotification> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from held_notifications order by created_at, id;"#).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(HeldNotification::read_from_statement(&statement));
+        }
+
+        res
+    }
+
+    fn remove_held_notification(&self, held_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"delete from held_notifications where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":id", held_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    // digests start from the moment the mode is chosen.
+    fn set_wallet_delivery(&self, wallet_id: i64, delivery: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting delivery of wallet {} to {}...", wallet_id, delivery);
+
+        let mut statement = connection.prepare(r#"update wallets set delivery = :delivery, digest_sent_at = :now, digest_until = :now where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":delivery", delivery.as_str()).unwrap();
+        statement.bind_by_name(":now", now()).unwrap();
+        statement.bind_by_name(":id", wallet_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> delivery set successfully");
+
+        true
+    }
+
+    // until is the insert time of the newest transaction the digest covered.
+    fn set_wallet_digest_sent(&self, wallet_id: i64, sent_at: i64, until: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"update wallets set digest_sent_at = :sent_at, digest_until = :until where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":sent_at", sent_at).unwrap();
+        statement.bind_by_name(":until", until).unwrap();
+        statement.bind_by_name(":id", wallet_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    // transactions stored in (after, until], by insert time so late polls still reach a digest.
+    fn get_digest_transactions(&self, wallet_id: i64, after: i64, until: i64) -> Vec<Transaction> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving digest transactions of wallet {}...", wallet_id);
+
+        let mut statement = connection.prepare(r#"select * from transactions where wallet_id = :wallet_id and created_at > :after and created_at <= :until order by id;"#).unwrap();
+
+        statement.bind_by_name(":wallet_id", wallet_id).unwrap();
+        statement.bind_by_name(":after", after).unwrap();
+        statement.bind_by_name(":until", until).unwrap();
+
+        let mut txs = vec![];
+
+        while let State::Row = statement.next().unwrap() {
+            txs.push(Transaction::read_from_statement(&statement));
+        }
+
+        logger!("-> {} digest transactions retrieved", txs.len());
+
+        txs
+    }
+
+    fn set_user_digest_time(&self, user_id: i64, digest_time: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting digest time for user {}...", user_id);
+
+        let mut statement = connection.prepare(r#"update users set digest_time = :digest_time where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":digest_time", digest_time).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> digest time set successfully");
+
+        true
+    }
+
+    fn add_queued_delivery(&self, delivery: QueuedDelivery) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> queueing {} delivery to {}...", delivery.sink, redact(delivery.target.as_str()));
+
+        let mut statement = connection.prepare(r#"insert into delivery_queue (sink, target, payload, attempts, next_attempt_at, last_error, source) values (:sink, :target, :payload, :attempts, :next_attempt_at, :last_error, :source);"#).unwrap();
+
+        statement.bind_by_name(":sink", delivery.sink.as_str()).unwrap();
+        statement.bind_by_name(":target", delivery.target.as_str()).unwrap();
+        statement.bind_by_name(":payload", delivery.payload.as_str()).unwrap();
+        statement.bind_by_name(":attempts", delivery.attempts).unwrap();
+        statement.bind_by_name(":next_attempt_at", delivery.next_attempt_at).unwrap();
+        statement.bind_by_name(":last_error", delivery.last_error.as_str()).unwrap();
+        statement.bind_by_name(":source", delivery.source.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> delivery queued successfully");
+
+        true
+    }
+
+    fn get_due_deliveries(&self, now: i64) -> Vec<QueuedDelivery> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from delivery_queue where next_attempt_at <= :now order by next_attempt_at, id;"#).unwrap();
+
+        statement.bind_by_name(":now", now).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(QueuedDelivery::read_from_statement(&statement));
+        }
+
+        res
+    }
+
+    fn update_queued_delivery(&self, delivery: &QueuedDelivery) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"update delivery_queue set attempts = :attempts, next_attempt_at = :next_attempt_at, last_error = :last_error where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":attempts", delivery.attempts).unwrap();
+        statement.bind_by_name(":next_attempt_at", delivery.next_attempt_at).unwrap();
+        statement.bind_by_name(":last_error", delivery.last_error.as_str()).unwrap();
+        statement.bind_by_name(":id", delivery.id.unwrap()).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    // whether a delivery whose source starts with the prefix is still queued.
+    fn has_queued_source(&self, prefix: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select count(*) from delivery_queue where substr(source, 1, length(:prefix)) = :prefix;"#).unwrap();
+
+        statement.bind_by_name(":prefix", prefix.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        statement.read::<i64>(0).unwrap() > 0
+    }
+
+    fn remove_queued_delivery(&self, delivery_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"delete from delivery_queue where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":id", delivery_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn add_sink(&self, sink: SinkConfig) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding {} sink for user {}...", sink.kind, sink.user_id);
+
+        let mut statement = connection.prepare(r#"insert into sinks (user_id, wallet_id, kind, target, secret) values (:user_id, :wallet_id, :kind, :target, :secret);"#).unwrap();
+
+        statement.bind_by_name(":user_id", sink.user_id).unwrap();
+        statement.bind_by_name(":wallet_id", sink.wallet_id).unwrap();
+        statement.bind_by_name(":kind", sink.kind.as_str()).unwrap();
+        statement.bind_by_name(":target", sink.target.as_str()).unwrap();
+        statement.bind_by_name(":secret", sink.secret.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> sink added successfully.");
+
+        true
+    }
+
+    fn remove_sink(&self, user_id: i64, sink_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> removing sink {} for user {}...", sink_id, user_id);
+
+        let mut statement = connection.prepare(r#"delete from sinks where id = :id and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":id", sink_id).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> sink removed successfully.");
+
+        true
+    }
+
+    fn get_user_sinks(&self, user_id: i64) -> Vec<SinkConfig> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from sinks where user_id = :user_id order by id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(SinkConfig::read_from_statement(&statement));
+        }
+
+        res
+    }
+
+    fn get_sink(&self, sink_id: i64) -> Option<SinkConfig> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from sinks where id = :id;"#).unwrap();
+
+        statement.bind_by_name(":id", sink_id).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            Some(SinkConfig::read_from_statement(&statement))
+        } else {
+            None
+        }
+    }
+
+    fn set_user_email(&self, email: EmailAddress) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting email of user {}...", email.user_id);
+
+        self.remove_user_email(email.user_id);
+
+        let mut statement = connection.prepare(r#"insert into emails (user_id, address, verified, code, code_expires_at, attempts) values (:user_id, :address, :verified, :code, :code_expires_at, :attempts);"#).unwrap();
+
+        statement.bind_by_name(":user_id", email.user_id).unwrap();
+        statement.bind_by_name(":address", email.address.as_str()).unwrap();
+        statement.bind_by_name(":verified", email.verified as i64).unwrap();
+        statement.bind_by_name(":code", email.code.as_str()).unwrap();
+        statement.bind_by_name(":code_expires_at", email.code_expires_at).unwrap();
+        statement.bind_by_name(":attempts", email.attempts).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> email set successfully");
+
+        true
+    }
+
+    fn get_user_email(&self, user_id: i64) -> Option<EmailAddress> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from emails where user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            Some(EmailAddress::read_from_statement(&statement))
+        } else {
+            None
+        }
+    }
+
+    fn remove_user_email(&self, user_id: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"delete from emails where user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn set_chat_active(&self, chat_id: i64, active: bool) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> marking chat {} as {}...", chat_id, if active { "active" } else { "inactive" });
+
+        // chats of users from before the chats table have no row yet.
+        if self.get_chat(chat_id).is_none() {
+            let kind = if chat_id > 0 { Chat::PRIVATE } else { Chat::GROUP };
+            self.set_chat(Chat::new(chat_id, kind.to_string(), "".to_string(), 0, None));
+        }
+
+        let mut statement = connection.prepare(r#"update chats set active = :active where chat_id = :chat_id;"#).unwrap();
+
+        statement.bind_by_name(":active", active as i64).unwrap();
+        statement.bind_by_name(":chat_id", chat_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn remove_queued_deliveries(&self, sink: String, target: String) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> dropping queued {} deliveries to {}...", sink, redact(target.as_str()));
+
+        let mut statement = connection.prepare(r#"delete from delivery_queue where sink = :sink and target = :target;"#).unwrap();
+
+        statement.bind_by_name(":sink", sink.as_str()).unwrap();
+        statement.bind_by_name(":target", target.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn set_user_announcements(&self, user_id: i64, enabled: bool) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> turning announcements {} for user {}...", if enabled { "on" } else { "off" }, user_id);
+
+        let mut statement = connection.prepare(r#"update users set announcements = :announcements where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":announcements", enabled as i64).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn set_user_release_seen(&self, user_id: i64, version: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"update users set release_seen = :version where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":version", version).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn get_users(&self, limit: i64, offset: i64) -> Vec<User> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from users order by id limit :limit offset :offset;"#).unwrap();
+
+        statement.bind_by_name(":limit", limit).unwrap();
+        statement.bind_by_name(":offset", offset).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(User::read_from_statement(&statement, 0));
+        }
+
+        res
+    }
+
+    fn set_user_banned(&self, user_id: i64, banned: bool) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> {} user {}...", if banned { "banning" } else { "unbanning" }, user_id);
+
+        let mut statement = connection.prepare(r#"update users set banned = :banned where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":banned", banned as i64).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn set_user_wallet_quota(&self, user_id: i64, quota: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> setting wallet quota of user {} to {}...", user_id, quota);
+
+        let mut statement = connection.prepare(r#"update users set wallet_quota = :quota where id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":quota", quota).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn get_stats(&self) -> Stats {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select (select count(*) from users), (select count(*) from users where banned = 1), (select count(*) from wallets), (select count(distinct lower(address)) from wallets), (select count(*) from transactions), (select ifnull(sum(errors), 0) from poll_status);"#).unwrap();
+
+        statement.next().unwrap();
+
+        Stats {
+            users: statement.read::<i64>(0).unwrap(),
+            banned_users: statement.read::<i64>(1).unwrap(),
+            wallets: statement.read::<i64>(2).unwrap(),
+            addresses: statement.read::<i64>(3).unwrap(),
+            transactions: statement.read::<i64>(4).unwrap(),
+            poll_errors: statement.read::<i64>(5).unwrap(),
+        }
+    }
+
+    fn set_poll_success(&self, address: String, at: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"insert into poll_status (address, last_success_at) values (lower(:address), :at) on conflict(address) do update set last_success_at = excluded.last_success_at;"#).unwrap();
+
+        statement.bind_by_name(":at", at).unwrap();
+        statement.bind_by_name(":address", address.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn add_poll_error(&self, address: String, error: String, at: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> polling {} failed: {}", address, error);
+
+        let mut statement = connection.prepare(r#"insert into poll_status (address, last_error_at, errors, last_error) values (lower(:address), :at, 1, :error) on conflict(address) do update set last_error_at = excluded.last_error_at, errors = errors + 1, last_error = excluded.last_error;"#).unwrap();
+
+        statement.bind_by_name(":at", at).unwrap();
+        statement.bind_by_name(":error", error.as_str()).unwrap();
+        statement.bind_by_name(":address", address.as_str()).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    fn get_poll_statuses(&self) -> Vec<PollStatus> {
+        let mut res = vec![];
+
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        // addresses only banned users track are not polled, see count_paused_addresses.
+        let mut statement = connection.prepare(r#"select * from poll_status where address in (select lower(w.address) from wallets w inner join users u on u.id = w.user_id where u.banned = 0) order by last_success_at;"#).unwrap();
+
+        while let State::Row = statement.next().unwrap() {
+            res.push(PollStatus::read_from_statement(&statement));
+        }
+
+        res
+    }
+
+    // filters of /txlist pages are kept a week, the prev/next buttons of older pages stop filtering.
+    fn add_page_filter(&self, user_id: i64, filter: &TransactionFilter, created_at: i64) -> Option<i64> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> adding page filter for user {}...", user_id);
+
+        let mut statement = connection.prepare(r#"delete from page_filters where created_at < :expired;"#).unwrap();
+
+        statement.bind_by_name(":expired", created_at - 7 * 86400).unwrap();
+
+        statement.next().unwrap();
+
+        let mut statement = connection.prepare(r#"insert into page_filters (user_id, filter, created_at) values (:user_id, :filter, :created_at);"#).unwrap();
+
+        statement.bind_by_name(":user_id", user_id).unwrap();
+        statement.bind_by_name(":filter", serde_json::to_string(filter).unwrap().as_str()).unwrap();
+        statement.bind_by_name(":created_at", created_at).unwrap();
+
+        statement.next().unwrap();
+
+        let mut statement = connection.prepare(r#"select last_insert_rowid();"#).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            logger!("-> page filter added successfully");
+            return Some(statement.read::<i64>(0).unwrap());
+        }
+
+        None
+    }
+
+    fn get_page_filter(&self, user_id: i64, filter_id: i64) -> Option<TransactionFilter> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> retrieving page filter {} of user {}...", filter_id, user_id);
+
+        let mut statement = connection.prepare(r#"select filter from page_filters where id = :id and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":id", filter_id).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            return serde_json::from_str::<TransactionFilter>(statement.read::<String>(0).unwrap().as_str()).ok();
+        }
+
+        None
+    }
+
+    fn set_member(&self, member: Member) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        logger!("-> saving member {} of chat {}...", member.user_id, member.chat_id);
+
+        let mut statement = connection.prepare(r#"insert into members (chat_id, user_id, role, updated_at) values (:chat_id, :user_id, :role, :updated_at) on conflict (chat_id, user_id) do update set role = excluded.role, updated_at = excluded.updated_at;"#).unwrap();
+
+        statement.bind_by_name(":chat_id", member.chat_id).unwrap();
+        statement.bind_by_name(":user_id", member.user_id).unwrap();
+        statement.bind_by_name(":role", member.role.as_str()).unwrap();
+        statement.bind_by_name(":updated_at", member.updated_at).unwrap();
+
+        statement.next().unwrap();
+
+        logger!("-> member saved successfully");
+
+        true
+    }
+
+    fn get_member(&self, chat_id: i64, user_id: i64) -> Option<Member> {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select * from members where chat_id = :chat_id and user_id = :user_id;"#).unwrap();
+
+        statement.bind_by_name(":chat_id", chat_id).unwrap();
+        statement.bind_by_name(":user_id", user_id).unwrap();
+
+        if let State::Row = statement.next().unwrap() {
+            return Some(Member::read_from_statement(&statement));
+        }
+
+        None
+    }
+
+    // verification emails sent, kept for a day to rate limit /email per chat and per address.
+    fn add_email_send(&self, chat_id: i64, address: String, sent_at: i64) -> bool {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"delete from email_sends where sent_at <= :expired;"#).unwrap();
+
+        statement.bind_by_name(":expired", sent_at - EmailAddress::SEND_WINDOW).unwrap();
+
+        statement.next().unwrap();
+
+        let mut statement = connection.prepare(r#"insert into email_sends (chat_id, address, sent_at) values (:chat_id, lower(:address), :sent_at);"#).unwrap();
+
+        statement.bind_by_name(":chat_id", chat_id).unwrap();
+        statement.bind_by_name(":address", address.as_str()).unwrap();
+        statement.bind_by_name(":sent_at", sent_at).unwrap();
+
+        statement.next().unwrap();
+
+        true
+    }
+
+    // sends since the given time by the chat and to the address.
+    fn count_email_sends(&self, chat_id: i64, address: String, since: i64) -> (i64, i64) {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select (select count(*) from email_sends where chat_id = :chat_id and sent_at > :since), (select count(*) from email_sends where address = lower(:address) and sent_at > :since);"#).unwrap();
+
+        statement.bind_by_name(":chat_id", chat_id).unwrap();
+        statement.bind_by_name(":address", address.as_str()).unwrap();
+        statement.bind_by_name(":since", since).unwrap();
+
+        statement.next().unwrap();
+
+        (statement.read::<i64>(0).unwrap(), statement.read::<i64>(1).unwrap())
+    }
+
+    // distinct addresses tracked by banned users only.
+    fn count_paused_addresses(&self) -> i64 {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select count(distinct lower(address)) from wallets where lower(address) not in (select lower(w.address) from wallets w inner join users u on u.id = w.user_id where u.banned = 0);"#).unwrap();
+
+        statement.next().unwrap();
+
+        statement.read::<i64>(0).unwrap()
+    }
+
+    // all queued deliveries and the ones due at now.
+    fn count_queued_deliveries(&self, now: i64) -> (i64, i64) {
+        if !self.connected() {
+            panic!("Connection error.");
+        }
+
+        let connection = self.connection.as_ref().unwrap();
+
+        let mut statement = connection.prepare(r#"select count(*), ifnull(sum(next_attempt_at <= :now), 0) from delivery_queue;"#).unwrap();
+
+        statement.bind_by_name(":now", now).unwrap();
+
+        statement.next().unwrap();
+
+        (statement.read::<i64>(0).unwrap(), statement.read::<i64>(1).unwrap())
+    }
+
+    fn drop(&mut self) {
+        if self.connection.is_none() {
+            return;
+        }
+
+        self.connection = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_text_statuses_are_migrated() {
+        let mut db = SqliteDb::new();
+        db.init(vec![":memory:"]);
+
+        // the transactions table as the first release created and filled it.
+        db.connection.as_ref().unwrap().execute(r#"create table transactions( "id" integer constraint transactions_pk primary key autoincrement, "from" varchar not null, "wallet_id" integer not null constraint transactions_wallets_id_fk references transactions(id) on update cascade on delete cascade, "to" varchar not null, "amount" varchar not null, "tx_hash" varchar not null, "status" bool default TRUE, "token" varchar not null);
+            insert into transactions ("from", wallet_id, "to", amount, tx_hash, status, token) values
+                ('0x1', 1, '0x2', '1', '0xfailed', 'true', 'ETH'),
+                ('0x1', 1, '0x2', '1', '0xsent', 'false', 'ETH'),
+                ('0x1', 1, '0x2', '1', '0xtoken', 'true', 'Tether USD');"#).unwrap();
+
+        db.load();
+
+        let statuses = db.get_all_transactions(1).into_iter()
+            .map(|t| (t.tx_hash, t.status))
+            .collect::<Vec<(String, bool)>>();
+
+        assert_eq!(statuses, vec![("0xfailed".to_string(), false), ("0xsent".to_string(), true), ("0xtoken".to_string(), true)]);
+        assert_eq!(SqliteDb::user_version(db.connection.as_ref().unwrap()), SqliteDb::MIGRATIONS.len());
+    }
+
+    #[test]
+    fn existing_users_have_seen_the_first_release_notes() {
+        let mut db = SqliteDb::new();
+        db.init(vec![":memory:"]);
+
+        db.connection.as_ref().unwrap().execute(r#"create table users("id" integer not null constraint users_pk primary key autoincrement, "chat_id" varchar not null);
+            insert into users (chat_id) values ('100'), ('200');"#).unwrap();
+
+        db.load();
+        db.add_user(300);
+
+        let seen = db.get_all_user().into_iter().map(|u| (u.chat_id, u.release_seen)).collect::<Vec<(String, i64)>>();
+        assert_eq!(seen, vec![("100".to_string(), 1), ("200".to_string(), 1), ("300".to_string(), 0)]);
+
+        // loading again does not run the migration a second time.
+        db.load();
+        assert_eq!(db.get_user(300).unwrap().release_seen, 0);
     }
 }
@@ -0,0 +1,413 @@
+pub mod telegram;
+pub mod webhook;
+pub mod discord;
+pub mod slack;
+pub mod email;
+
+use std::net::IpAddr;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+use teloxide::{prelude::*, types::ChatId};
+use crate::{AppConfig, DataRepository, logger};
+use crate::common::{format_timestamp, format_units, now};
+use crate::models::queued_delivery::QueuedDelivery;
+use crate::models::sink_config::SinkConfig;
+use crate::models::transaction::Transaction;
+use crate::providers;
+use crate::renderer::Notification;
+use crate::sinks::discord::DiscordSink;
+use crate::sinks::email::{EmailSink, SmtpSettings};
+use crate::sinks::slack::SlackSink;
+use crate::sinks::telegram::TelegramSink;
+use crate::sinks::webhook::{SIGNATURE_HEADER, WebhookSink};
+
+pub const CHAIN: &str = "ethereum";
+
+// what happened, independent of how a sink formats it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationEvent {
+    pub chain: String,
+    pub wallet: String,
+    pub direction: String,
+    pub tx_hash: String,
+    pub block: i64,
+    pub timestamp: i64,
+    pub from: String,
+    pub to: String,
+    pub token: String,
+    pub symbol: String,
+    pub amount: String,
+    pub received_token: String,
+    #[serde(default)]
+    pub received_symbol: String,
+    pub received_amount: String,
+    pub fiat_value: Option<f64>,
+    pub fiat_currency: String,
+}
+
+impl NotificationEvent {
+    // wallet is empty for token watch alerts, which are not about a tracked wallet.
+    pub fn from_transaction(trx: &Transaction, wallet: &str) -> Self {
+        let direction = if trx.is_swap() {
+            "swap"
+        } else if !wallet.is_empty() && trx.to.eq_ignore_ascii_case(wallet) {
+            "in"
+        } else if !wallet.is_empty() && trx.from.eq_ignore_ascii_case(wallet) {
+            "out"
+        } else {
+            "transfer"
+        };
+
+        NotificationEvent {
+            chain: CHAIN.to_string(),
+            wallet: wallet.to_string(),
+            direction: direction.to_string(),
+            tx_hash: trx.tx_hash.clone(),
+            block: trx.block_number,
+            timestamp: trx.timestamp,
+            from: trx.from.clone(),
+            to: trx.to.clone(),
+            token: trx.token.clone(),
+            symbol: trx.symbol.clone(),
+            amount: format_units(&trx.amount, trx.token_decimal()),
+            received_token: trx.received_token.clone(),
+            received_symbol: trx.received_symbol.clone(),
+            received_amount: if trx.is_swap() { format_units(&trx.received_amount, trx.received_decimal) } else { "".to_string() },
+            fiat_value: if trx.has_fiat_value() { Some(trx.fiat_value) } else { None },
+            fiat_currency: trx.fiat_currency.clone(),
+        }
+    }
+}
+
+// how a failed delivery is handled by background_outbox_worker.
+#[derive(Debug)]
+pub enum SinkError {
+    // rate limited, the number of seconds to wait before the next attempt.
+    RetryAfter(i64),
+    // network errors and server side failures, retried with backoff.
+    Transient(String),
+    // the bot was blocked or removed from the chat, nothing more is sent there.
+    Blocked(String),
+    // the request itself is wrong, retrying would fail the same way.
+    Rejected(String),
+}
+
+impl std::fmt::Display for SinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SinkError::RetryAfter(secs) => write!(f, "retry after {} seconds", secs),
+            SinkError::Transient(e) | SinkError::Blocked(e) | SinkError::Rejected(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    // where the payload goes: a chat id, a url...
+    fn target(&self) -> String;
+
+    // the serialized message, stored as is in the outbox so a retry sends the same content.
+    fn payload(&self, event: &NotificationEvent, notification: &Notification) -> String;
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError>;
+}
+
+// a sink added with /sink. It is queued by id so its url and secret stay out of the outbox, and
+// the url is checked to still point to a public host before every delivery.
+pub struct ConfiguredSink {
+    id: i64,
+    inner: Box<dyn NotificationSink>,
+}
+
+impl ConfiguredSink {
+    pub const PREFIX: &'static str = "sink:";
+
+    pub fn from_config(config: &SinkConfig) -> Option<Self> {
+        let inner: Box<dyn NotificationSink> = match config.kind.as_str() {
+            webhook::KIND => Box::new(WebhookSink::new(config.target.clone(), config.secret.clone())),
+            discord::KIND => Box::new(DiscordSink::new(config.target.clone())),
+            slack::KIND => Box::new(SlackSink::new(config.target.clone())),
+            _ => return None,
+        };
+
+        Some(ConfiguredSink {
+            id: config.id?,
+            inner,
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationSink for ConfiguredSink {
+    fn kind(&self) -> &'static str {
+        self.inner.kind()
+    }
+
+    fn target(&self) -> String {
+        format!("{}{}", ConfiguredSink::PREFIX, self.id)
+    }
+
+    fn payload(&self, event: &NotificationEvent, notification: &Notification) -> String {
+        self.inner.payload(event, notification)
+    }
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError> {
+        ensure_public(self.inner.target().as_str()).await?;
+
+        self.inner.deliver(payload).await
+    }
+}
+
+// rebuilds a sink for a queued delivery, None when it cannot be delivered anymore.
+pub fn from_parts<R>(bot: &AutoSend<Bot>, repo: &R, kind: &str, target: &str) -> Option<Box<dyn NotificationSink>>
+    where R: DataRepository {
+    if let Some(id) = target.strip_prefix(ConfiguredSink::PREFIX) {
+        let config = repo.get_sink(id.parse::<i64>().ok()?)?;
+
+        return Some(Box::new(ConfiguredSink::from_config(&config)?));
+    }
+
+    let config = AppConfig::from_args();
+
+    match kind {
+        telegram::KIND => Some(Box::new(TelegramSink::new(bot.clone(), ChatId(target.parse::<i64>().ok()?)))),
+        webhook::KIND => Some(Box::new(WebhookSink::new(target.to_string(), config.webhook_secret.unwrap_or_default()))),
+        discord::KIND => Some(Box::new(DiscordSink::new(target.to_string()))),
+        slack::KIND => Some(Box::new(SlackSink::new(target.to_string()))),
+        email::KIND => Some(Box::new(EmailSink::new(SmtpSettings::from_config(&config)?, target.to_string()))),
+        _ => None,
+    }
+}
+
+// sinks outside telegram that receive every event regardless of quiet hours or digests.
+pub fn external_sinks(config: &AppConfig) -> Vec<Box<dyn NotificationSink>> {
+    let mut sinks: Vec<Box<dyn NotificationSink>> = vec![];
+
+    if let Some(url) = &config.webhook_url {
+        sinks.push(Box::new(WebhookSink::new(url.clone(), config.webhook_secret.clone().unwrap_or_default())));
+    }
+
+    sinks
+}
+
+// the sinks a user configured for all wallets plus the ones for this wallet.
+pub fn user_sinks<R>(repo: &R, user_id: i64, wallet_id: i64) -> Vec<Box<dyn NotificationSink>>
+    where R: DataRepository {
+    repo.get_user_sinks(user_id).iter()
+        .filter(|c| c.covers(wallet_id))
+        .filter_map(|c| ConfiguredSink::from_config(c).map(|s| Box::new(s) as Box<dyn NotificationSink>))
+        .collect()
+}
+
+// the verified email of a user, large-transfer alerts are copied there.
+pub fn email_sinks<R>(config: &AppConfig, repo: &R, user_id: i64) -> Vec<Box<dyn NotificationSink>>
+    where R: DataRepository {
+    match (SmtpSettings::from_config(config), repo.get_user_email(user_id)) {
+        (Some(settings), Some(email)) if email.verified => vec![Box::new(EmailSink::new(settings, email.address))],
+        _ => vec![],
+    }
+}
+
+pub fn is_configurable(kind: &str) -> bool {
+    SinkConfig::KINDS.contains(&kind)
+}
+
+// urls carry secrets in their path or query, logs and listings only show where they point to.
+pub fn redact(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(u) => format!("{}://{}/…", u.scheme(), u.host_str().unwrap_or("")),
+        Err(_) => url.to_string(),
+    }
+}
+
+// user sinks must not reach the host of the bot or its network: no loopback, private, link-local,
+// shared (cgnat), multicast or reserved addresses.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+
+            !(v4.is_loopback() || v4.is_private() || v4.is_link_local() || v4.is_unspecified() || v4.is_broadcast()
+                || v4.is_documentation() || v4.is_multicast()
+                || a == 0 || a >= 240 || (a == 100 && (64..128).contains(&b)) || (a == 198 && (18..20).contains(&b))
+                || (a == 192 && b == 0 && c == 0))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+
+            let first = v6.segments()[0];
+
+            !(v6.is_loopback() || v6.is_unspecified() || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80 || (first == 0x2001 && v6.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+// https only, and every address the host resolves to has to be public.
+pub async fn ensure_public(url: &str) -> Result<(), SinkError> {
+    let url = reqwest::Url::parse(url).map_err(|e| SinkError::Rejected(e.to_string()))?;
+
+    if url.scheme() != "https" {
+        return Err(SinkError::Rejected("only https urls are allowed".to_string()));
+    }
+
+    let host = url.host_str().unwrap_or("").trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let addresses = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) if host.is_empty() => vec![],
+        Err(_) => tokio::net::lookup_host((host, port)).await
+            .map_err(|e| SinkError::Transient(e.to_string()))?
+            .map(|a| a.ip())
+            .collect(),
+    };
+
+    if addresses.is_empty() || !addresses.iter().all(|ip| is_public_ip(*ip)) {
+        return Err(SinkError::Rejected(format!("{} is not a public host", redact(url.as_str()))));
+    }
+
+    Ok(())
+}
+
+// "Received 1.5 USDC", shared by the sinks that format the event themselves.
+pub fn event_headline(event: &NotificationEvent) -> String {
+    let amount = format!("{} {}", event.amount, if event.symbol.is_empty() { &event.token } else { &event.symbol });
+
+    match event.direction.as_str() {
+        "in" => format!("Received {}", amount),
+        "out" => format!("Sent {}", amount),
+        "swap" => format!("Swapped {} → {} {}", amount, event.received_amount,
+                         if event.received_symbol.is_empty() { &event.received_token } else { &event.received_symbol }),
+        _ => format!("Transfer {}", amount),
+    }
+}
+
+pub fn event_fields(event: &NotificationEvent) -> Vec<(&'static str, String)> {
+    let mut fields = vec![];
+
+    if !event.wallet.is_empty() {
+        fields.push(("Wallet", event.wallet.clone()));
+    }
+
+    fields.push(("From", event.from.clone()));
+    fields.push(("To", event.to.clone()));
+
+    if let Some(value) = event.fiat_value {
+        fields.push(("Value", format!("{:.2} {}", value, event.fiat_currency)));
+    }
+
+    fields.push(("Block", event.block.to_string()));
+
+    if event.timestamp != 0 {
+        fields.push(("Time", format_timestamp(event.timestamp)));
+    }
+
+    fields
+}
+
+// 429 honours Retry-After, other non 2xx answers are retried with backoff.
+pub async fn post_json(url: &str, payload: &str, signature: Option<String>) -> Result<(), SinkError> {
+    let mut request = providers::http_client().post(url)
+        .header("Content-Type", "application/json")
+        .body(payload.to_string());
+
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+
+    let resp = request.send().await.map_err(|e| SinkError::Transient(e.without_url().to_string()))?;
+
+    if resp.status().is_success() {
+        return Ok(());
+    }
+
+    if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let secs = resp.headers().get("Retry-After")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(60f64);
+
+        return Err(SinkError::RetryAfter(secs.ceil() as i64));
+    }
+
+    Err(SinkError::Transient(format!("status {}", resp.status())))
+}
+
+pub const MAX_ATTEMPTS: i64 = 10;
+
+// 1, 2, 4... minutes up to an hour.
+pub fn backoff(attempts: i64) -> i64 {
+    (60 * 2i64.pow(attempts.clamp(1, 7) as u32 - 1)).min(3600)
+}
+
+// notifications are written to the outbox and sent by background_outbox_worker, so a failing
+// sink never takes the worker that produced the notification down with it.
+pub fn enqueue<R>(sinks: &[Box<dyn NotificationSink>], event: &NotificationEvent, notification: &Notification, repo: &mut R)
+    where R: DataRepository {
+    for sink in sinks {
+        queue(repo, sink.kind(), sink.target(), sink.payload(event, notification), "");
+    }
+}
+
+// false when nothing was queued, see QueuedDelivery::source for the source.
+pub fn queue<R>(repo: &mut R, kind: &str, target: String, payload: String, source: &str) -> bool where R: DataRepository {
+    // chats that blocked the bot get nothing until they send /start again.
+    if kind == telegram::KIND {
+        if let Some(chat) = target.parse::<i64>().ok().and_then(|id| repo.get_chat(id)) {
+            if !chat.active {
+                logger!("-> chat {} is inactive, notification dropped.", target);
+                return false;
+            }
+        }
+    }
+
+    let mut delivery = QueuedDelivery::new(kind.to_string(), target, payload, 0, now(), "".to_string(), None);
+    delivery.source = source.to_string();
+
+    repo.add_queued_delivery(delivery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse::<IpAddr>().unwrap())
+    }
+
+    #[test]
+    fn private_addresses_are_not_public() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+                   "255.255.255.255", "224.0.0.1", "198.18.0.1", "::1", "::", "fc00::1", "fd12:3456::1", "fe80::1",
+                   "::ffff:127.0.0.1", "::ffff:10.0.0.1"] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn public_addresses() {
+        for ip in ["1.1.1.1", "8.8.8.8", "100.128.0.1", "172.32.0.1", "2606:4700:4700::1111", "::ffff:1.1.1.1"] {
+            assert!(public(ip), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn sink_urls_must_be_public_https() {
+        assert!(matches!(ensure_public("http://1.1.1.1/hook").await, Err(SinkError::Rejected(_))));
+        assert!(matches!(ensure_public("https://127.0.0.1/hook").await, Err(SinkError::Rejected(_))));
+        assert!(matches!(ensure_public("https://[::1]:8443/hook").await, Err(SinkError::Rejected(_))));
+        assert!(ensure_public("https://1.1.1.1/hook").await.is_ok());
+    }
+
+    #[test]
+    fn redact_keeps_the_host() {
+        assert_eq!(redact("https://discord.com/api/webhooks/1/token"), "https://discord.com/…");
+        assert_eq!(redact("-1001234"), "-1001234");
+    }
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use serde_json::json;
+use crate::renderer::Notification;
+use crate::sinks::{event_fields, event_headline, NotificationEvent, NotificationSink, SinkError, post_json};
+
+pub const KIND: &str = "discord";
+
+// discord webhook with one embed per event.
+pub struct DiscordSink {
+    url: String,
+}
+
+impl DiscordSink {
+    pub fn new(url: String) -> Self {
+        DiscordSink {
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for DiscordSink {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn target(&self) -> String {
+        self.url.clone()
+    }
+
+    fn payload(&self, event: &NotificationEvent, _notification: &Notification) -> String {
+        let color = match event.direction.as_str() {
+            "in" => 0x2ecc71,
+            "out" => 0xe74c3c,
+            _ => 0x3498db,
+        };
+
+        let fields = event_fields(event).into_iter()
+            .map(|(name, value)| json!({ "name": name, "value": value, "inline": true }))
+            .collect::<Vec<serde_json::Value>>();
+
+        json!({
+            "embeds": [{
+                "title": event_headline(event),
+                "url": format!("https://etherscan.io/tx/{}", event.tx_hash),
+                "color": color,
+                "fields": fields,
+            }]
+        }).to_string()
+    }
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError> {
+        post_json(self.url.as_str(), payload, None).await
+    }
+}
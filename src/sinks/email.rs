@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message as Email, Tokio1Executor};
+use lettre::message::MultiPart;
+use lettre::transport::smtp::authentication::Credentials;
+use serde::{Deserialize, Serialize};
+use crate::AppConfig;
+use crate::renderer::{Markup, Notification};
+use crate::sinks::{event_fields, event_headline, NotificationEvent, NotificationSink, SinkError};
+
+pub const KIND: &str = "email";
+
+#[derive(Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub tls: String,
+    pub from: String,
+}
+
+impl SmtpSettings {
+    // None when no smtp host is configured, email is disabled then.
+    pub fn from_config(config: &AppConfig) -> Option<Self> {
+        Some(SmtpSettings {
+            host: config.smtp_host.clone()?,
+            port: config.smtp_port.unwrap_or(default_port(config.smtp_tls.as_str())),
+            username: config.smtp_username.clone(),
+            password: config.smtp_password.clone(),
+            tls: config.smtp_tls.clone(),
+            from: config.smtp_from.clone(),
+        })
+    }
+}
+
+pub fn default_port(tls: &str) -> u16 {
+    match tls {
+        "tls" => 465,
+        "none" => 25,
+        _ => 587,
+    }
+}
+
+// what is stored in the retry queue, both bodies are rendered up front.
+#[derive(Serialize, Deserialize)]
+struct EmailPayload {
+    subject: String,
+    text: String,
+    html: String,
+}
+
+pub struct EmailSink {
+    settings: SmtpSettings,
+    to: String,
+}
+
+impl EmailSink {
+    pub fn new(settings: SmtpSettings, to: String) -> Self {
+        EmailSink {
+            settings,
+            to,
+        }
+    }
+}
+
+// multipart/alternative, clients without html support show the plain text part.
+pub async fn send_email(settings: &SmtpSettings, to: &str, subject: &str, text: String, html: String) -> Result<(), String> {
+    let email = Email::builder()
+        .from(settings.from.parse().map_err(|e| format!("invalid from address: {}", e))?)
+        .to(to.parse().map_err(|e| format!("invalid address: {}", e))?)
+        .subject(subject)
+        .multipart(MultiPart::alternative_plain_html(text, html))
+        .map_err(|e| e.to_string())?;
+
+    let mut transport = match settings.tls.as_str() {
+        "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(settings.host.as_str()),
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(settings.host.as_str()).map_err(|e| e.to_string())?,
+        _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(settings.host.as_str()).map_err(|e| e.to_string())?,
+    }.port(settings.port);
+
+    if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport.build().send(email).await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[async_trait]
+impl NotificationSink for EmailSink {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn target(&self) -> String {
+        self.to.clone()
+    }
+
+    fn payload(&self, event: &NotificationEvent, _notification: &Notification) -> String {
+        let headline = event_headline(event);
+        let fields = event_fields(event);
+        let url = format!("https://etherscan.io/tx/{}", event.tx_hash);
+
+        let text = format!("{}\n\n{}\n\n{}\n",
+                           headline,
+                           fields.iter().map(|(name, value)| format!("{}: {}", name, value)).collect::<Vec<String>>().join("\n"),
+                           url);
+
+        let markup = Markup::Html;
+        let rows = fields.iter()
+            .map(|(name, value)| format!("<tr><td>{}</td><td>{}</td></tr>", markup.bold(name), markup.escape(value)))
+            .collect::<String>();
+        let html = format!("<html><body><h3>{}</h3><table>{}</table><p>{}</p></body></html>",
+                           markup.escape(headline.as_str()), rows, markup.link("View on Etherscan", url.as_str()));
+
+        serde_json::to_string(&EmailPayload {
+            subject: headline,
+            text,
+            html,
+        }).unwrap()
+    }
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError> {
+        let payload = serde_json::from_str::<EmailPayload>(payload).map_err(|e| SinkError::Rejected(e.to_string()))?;
+
+        send_email(&self.settings, self.to.as_str(), payload.subject.as_str(), payload.text, payload.html).await
+            .map_err(SinkError::Transient)
+    }
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use serde_json::json;
+use crate::renderer::Notification;
+use crate::sinks::{event_fields, event_headline, NotificationEvent, NotificationSink, SinkError, post_json};
+
+pub const KIND: &str = "slack";
+
+// slack incoming webhook using block kit, `text` is the fallback for notifications.
+pub struct SlackSink {
+    url: String,
+}
+
+impl SlackSink {
+    pub fn new(url: String) -> Self {
+        SlackSink {
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for SlackSink {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn target(&self) -> String {
+        self.url.clone()
+    }
+
+    fn payload(&self, event: &NotificationEvent, _notification: &Notification) -> String {
+        let headline = event_headline(event);
+
+        let fields = event_fields(event).into_iter()
+            .map(|(name, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", name, value) }))
+            .collect::<Vec<serde_json::Value>>();
+
+        json!({
+            "text": headline,
+            "blocks": [
+                { "type": "header", "text": { "type": "plain_text", "text": headline } },
+                { "type": "section", "fields": fields },
+                { "type": "section", "text": { "type": "mrkdwn", "text": format!("<https://etherscan.io/tx/{}|View on Etherscan>", event.tx_hash) } },
+            ]
+        }).to_string()
+    }
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError> {
+        post_json(self.url.as_str(), payload, None).await
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use teloxide::{ApiError, RequestError, prelude::*, types::ChatId};
+use crate::DataRepository;
+use crate::common::{MESSAGE_LIMIT, send_notification, split_message};
+use crate::renderer::{Markup, Notification};
+use crate::sinks::{NotificationEvent, NotificationSink, SinkError, queue};
+
+pub const KIND: &str = "telegram";
+
+pub struct TelegramSink {
+    bot: AutoSend<Bot>,
+    chat_id: ChatId,
+}
+
+impl TelegramSink {
+    pub fn new(bot: AutoSend<Bot>, chat_id: ChatId) -> Self {
+        TelegramSink {
+            bot,
+            chat_id,
+        }
+    }
+
+    // mistakes of the bot itself are repaired instead of losing the message: markup telegram could
+    // not parse is sent as plain text and invalid buttons are left out.
+    async fn send_repaired(&self, mut notification: Notification) -> Result<(), RequestError> {
+        let mut stripped = false;
+
+        loop {
+            match send_notification(&self.bot, self.chat_id, &notification).await {
+                Ok(_) => return Ok(()),
+                Err(RequestError::Api(ApiError::ButtonDataInvalid)) if notification.keyboard.is_some() => {
+                    notification.keyboard = None;
+                }
+                Err(RequestError::Api(ApiError::CantParseEntities(_))) if !stripped => {
+                    let markup = Markup::from_parse_mode(notification.parse_mode);
+
+                    notification.text = markup.escape(markup.strip(notification.text.as_str()).as_str());
+                    stripped = true;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// for notifications that are not about a single event: digests, alerts, decoded logs...
+pub fn queue_notification<R>(repo: &mut R, chat_id: ChatId, notification: &Notification, source: &str) -> bool where R: DataRepository {
+    queue(repo, KIND, chat_id.0.to_string(), serde_json::to_string(notification).unwrap(), source)
+}
+
+pub fn to_sink_error(error: RequestError) -> SinkError {
+    match error {
+        RequestError::RetryAfter(secs) => SinkError::RetryAfter(secs as i64),
+        RequestError::Api(ApiError::BotBlocked | ApiError::BotKicked | ApiError::BotKickedFromSupergroup
+                          | ApiError::UserDeactivated | ApiError::ChatNotFound | ApiError::CantInitiateConversation) => {
+            SinkError::Blocked(error.to_string())
+        }
+        RequestError::Api(_) | RequestError::MigrateToChatId(_) => SinkError::Rejected(error.to_string()),
+        _ => SinkError::Transient(error.to_string()),
+    }
+}
+
+#[async_trait]
+impl NotificationSink for TelegramSink {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn target(&self) -> String {
+        self.chat_id.0.to_string()
+    }
+
+    fn payload(&self, _event: &NotificationEvent, notification: &Notification) -> String {
+        serde_json::to_string(notification).unwrap()
+    }
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError> {
+        let notification = serde_json::from_str::<Notification>(payload).map_err(|e| SinkError::Rejected(e.to_string()))?;
+
+        match self.send_repaired(notification.clone()).await {
+            // split at line breaks, the buttons go with the last part.
+            Err(RequestError::Api(ApiError::MessageIsTooLong)) => {
+                let parts = split_message(notification.text.as_str(), MESSAGE_LIMIT);
+                let last = parts.len() - 1;
+
+                for (i, text) in parts.into_iter().enumerate() {
+                    let part = Notification {
+                        text,
+                        keyboard: if i == last { notification.keyboard.clone() } else { None },
+                        ..notification.clone()
+                    };
+
+                    self.send_repaired(part).await.map_err(to_sink_error)?;
+                }
+
+                Ok(())
+            }
+            result => result.map_err(to_sink_error),
+        }
+    }
+}
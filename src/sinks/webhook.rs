@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use crypto::{hmac::Hmac, mac::Mac, sha2::Sha256};
+use rand::{rngs::OsRng, RngCore};
+use crate::renderer::Notification;
+use crate::sinks::{NotificationEvent, NotificationSink, SinkError, post_json};
+
+pub const KIND: &str = "webhook";
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+// POSTs the event as json, signed with hmac-sha256 of the body. Unsigned events are never sent.
+pub struct WebhookSink {
+    url: String,
+    secret: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, secret: String) -> Self {
+        WebhookSink {
+            url,
+            secret,
+        }
+    }
+}
+
+// `sha256=<hex>` like github webhooks, receivers recompute it over the raw body.
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::new(Sha256::new(), secret.as_bytes());
+    mac.input(body.as_bytes());
+
+    let code = mac.result().code().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+    format!("sha256={}", code)
+}
+
+// key of a user webhook, 32 random bytes as hex.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn target(&self) -> String {
+        self.url.clone()
+    }
+
+    fn payload(&self, event: &NotificationEvent, _notification: &Notification) -> String {
+        serde_json::to_string(event).unwrap()
+    }
+
+    async fn deliver(&self, payload: &str) -> Result<(), SinkError> {
+        if self.secret.is_empty() {
+            return Err(SinkError::Rejected("webhook secret is not configured".to_string()));
+        }
+
+        post_json(self.url.as_str(), payload, Some(sign(self.secret.as_str(), payload))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn sign_matches_rfc_4231() {
+        assert_eq!(sign("Jefe", "what do ya want for nothing?"),
+                   "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[tokio::test]
+    async fn unsigned_delivery_is_rejected() {
+        let sink = WebhookSink::new("http://127.0.0.1:9/".to_string(), "".to_string());
+
+        assert!(matches!(sink.deliver("{}").await, Err(SinkError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    async fn delivery_sends_the_signature_header() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 1024];
+
+            // headers and the 16 byte body.
+            while !String::from_utf8_lossy(&request).ends_with(r#"{"event":"test"}"#) {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n").await.unwrap();
+
+            String::from_utf8_lossy(&request).to_lowercase()
+        });
+
+        let sink = WebhookSink::new(url, "secret".to_string());
+        sink.deliver(r#"{"event":"test"}"#).await.unwrap();
+
+        let request = server.await.unwrap();
+        let expected = format!("{}: {}", SIGNATURE_HEADER, sign("secret", r#"{"event":"test"}"#)).to_lowercase();
+
+        assert!(request.contains(expected.as_str()), "{}", request);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::common::{days_from_civil, format_timestamp, format_units};
+use crate::export::csv_line;
+use crate::models::transaction::Transaction;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+    Hifo,
+}
+
+impl LotMethod {
+    pub fn parse(method: &str) -> Option<Self> {
+        match method.to_ascii_lowercase().as_str() {
+            "fifo" => Some(LotMethod::Fifo),
+            "lifo" => Some(LotMethod::Lifo),
+            "hifo" => Some(LotMethod::Hifo),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LotMethod::Fifo => "FIFO",
+            LotMethod::Lifo => "LIFO",
+            LotMethod::Hifo => "HIFO",
+        }
+    }
+}
+
+struct Lot {
+    asset: String,
+    amount: f64,
+    unit_cost: f64,
+    acquired_at: i64,
+    priced: bool,
+}
+
+pub struct TaxEvent {
+    pub date: i64,
+    pub kind: &'static str,
+    pub asset: String,
+    pub amount: f64,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+    pub acquired_at: Option<i64>,
+    pub currency: String,
+    pub tx_hash: String,
+    // false when the transaction, or the one the consumed lot came from, had no fiat price.
+    pub priced: bool,
+}
+
+pub struct TaxReport {
+    pub year: i64,
+    pub method: LotMethod,
+    pub currency: String,
+    pub events: Vec<TaxEvent>,
+}
+
+impl TaxReport {
+    pub fn unpriced(&self) -> usize {
+        self.events.iter().filter(|e| !e.priced).count()
+    }
+
+    // realised gain per asset, in order of first disposal.
+    pub fn gains(&self) -> Vec<(String, f64)> {
+        let mut gains: Vec<(String, f64)> = vec![];
+
+        for event in self.events.iter().filter(|e| e.kind == "Disposal") {
+            let gain = event.proceeds - event.cost_basis;
+
+            match gains.iter_mut().find(|(a, _)| *a == event.asset) {
+                Some((_, g)) => *g += gain,
+                None => gains.push((event.asset.clone(), gain)),
+            }
+        }
+
+        gains
+    }
+
+    // one row per acquisition and per consumed lot, close to the form 8949 layout most tax tools import.
+    pub fn to_csv(&self) -> Vec<u8> {
+        let mut csv = vec![csv_line(&["Date", "Type", "Asset", "Amount", "Proceeds", "Cost Basis", "Gain", "Currency",
+                                      "Date Acquired", "Method", "Tx Hash", "Note"])];
+
+        for event in self.events.iter() {
+            let (proceeds, gain) = if event.kind == "Disposal" {
+                (format!("{:.2}", event.proceeds), format!("{:.2}", event.proceeds - event.cost_basis))
+            } else {
+                ("".to_string(), "".to_string())
+            };
+
+            csv.push(csv_line(&[
+                format_timestamp(event.date).as_str(), event.kind, event.asset.as_str(), event.amount.to_string().as_str(),
+                proceeds.as_str(), format!("{:.2}", event.cost_basis).as_str(), gain.as_str(), event.currency.as_str(),
+                event.acquired_at.map(format_timestamp).unwrap_or_default().as_str(), self.method.name(), event.tx_hash.as_str(),
+                if event.priced { "" } else { "missing price" },
+            ]));
+        }
+
+        csv.join("\n").into_bytes()
+    }
+}
+
+fn asset_of(trx: &Transaction) -> String {
+    if trx.symbol.is_empty() { trx.token.clone() } else { trx.symbol.clone() }
+}
+
+// txs are (wallet address, transaction) pairs of every wallet of the user; transfers between
+// own_addresses are internal moves and neither acquire nor dispose anything. Failed transactions
+// are skipped and rows without a fiat price count as zero but are flagged. Err lists the currencies
+// when the transactions were priced in more than one.
+pub fn build_report(mut txs: Vec<(String, Transaction)>, own_addresses: &[String], year: i64, method: LotMethod,
+                    currency: &str) -> Result<TaxReport, Vec<String>> {
+    let year_start = days_from_civil(year, 1, 1) * 86400;
+    let year_end = days_from_civil(year + 1, 1, 1) * 86400;
+    let is_own = |address: &str| own_addresses.iter().any(|a| a.eq_ignore_ascii_case(address));
+
+    txs.retain(|(_, t)| t.status && t.timestamp < year_end);
+    txs.sort_by_key(|(_, t)| t.timestamp);
+
+    let mut currencies: Vec<String> = vec![];
+    for (_, trx) in txs.iter().filter(|(_, t)| t.has_fiat_value()) {
+        if !currencies.contains(&trx.fiat_currency) {
+            currencies.push(trx.fiat_currency.clone());
+        }
+    }
+
+    if currencies.len() > 1 {
+        return Err(currencies);
+    }
+
+    let currency = currencies.pop().unwrap_or(currency.to_string());
+
+    let mut lots: Vec<Lot> = vec![];
+    let mut events = vec![];
+
+    for (wallet, trx) in txs.iter() {
+        let in_year = trx.timestamp >= year_start;
+        let priced = trx.has_fiat_value();
+
+        if !trx.is_swap() && is_own(&trx.from) && is_own(&trx.to) {
+            continue;
+        }
+
+        let amount = format_units(&trx.amount, trx.token_decimal()).parse::<f64>().unwrap_or(0f64);
+
+        let (disposed, acquired) = if trx.is_swap() {
+            let received = format_units(&trx.received_amount, trx.received_decimal).parse::<f64>().unwrap_or(0f64);
+            (Some((asset_of(trx), amount)), Some((trx.received_token_symbol().to_string(), received)))
+        } else if trx.to.eq_ignore_ascii_case(wallet) {
+            (None, Some((asset_of(trx), amount)))
+        } else {
+            (Some((asset_of(trx), amount)), None)
+        };
+
+        if let Some((asset, mut remaining)) = disposed {
+            let unit_proceeds = if amount > 0f64 { trx.fiat_value / amount } else { 0f64 };
+
+            while remaining > 0f64 {
+                let lot_index = pick_lot(&lots, asset.as_str(), method);
+
+                // without a known acquisition the remainder has a zero cost basis.
+                let (used, unit_cost, acquired_at, lot_priced) = match lot_index {
+                    Some(i) => {
+                        let lot = &mut lots[i];
+                        let used = lot.amount.min(remaining);
+                        lot.amount -= used;
+                        (used, lot.unit_cost, Some(lot.acquired_at), lot.priced)
+                    }
+                    None => (remaining, 0f64, None, true),
+                };
+
+                remaining -= used;
+                lots.retain(|l| l.amount > 0f64);
+
+                if in_year {
+                    events.push(TaxEvent {
+                        date: trx.timestamp,
+                        kind: "Disposal",
+                        asset: asset.clone(),
+                        amount: used,
+                        proceeds: used * unit_proceeds,
+                        cost_basis: used * unit_cost,
+                        acquired_at,
+                        currency: currency.clone(),
+                        tx_hash: trx.tx_hash.clone(),
+                        priced: priced && lot_priced,
+                    });
+                }
+            }
+        }
+
+        if let Some((asset, received)) = acquired {
+            if received > 0f64 {
+                lots.push(Lot {
+                    asset: asset.clone(),
+                    amount: received,
+                    unit_cost: trx.fiat_value / received,
+                    acquired_at: trx.timestamp,
+                    priced,
+                });
+
+                if in_year {
+                    events.push(TaxEvent {
+                        date: trx.timestamp,
+                        kind: "Acquisition",
+                        asset,
+                        amount: received,
+                        proceeds: 0f64,
+                        cost_basis: trx.fiat_value,
+                        acquired_at: None,
+                        currency: currency.clone(),
+                        tx_hash: trx.tx_hash.clone(),
+                        priced,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(TaxReport {
+        year,
+        method,
+        currency,
+        events,
+    })
+}
+
+fn pick_lot(lots: &[Lot], asset: &str, method: LotMethod) -> Option<usize> {
+    let candidates = lots.iter().enumerate().filter(|(_, l)| l.asset == asset && l.amount > 0f64);
+
+    match method {
+        LotMethod::Fifo => candidates.min_by_key(|(_, l)| l.acquired_at).map(|(i, _)| i),
+        LotMethod::Lifo => candidates.max_by_key(|(_, l)| l.acquired_at).map(|(i, _)| i),
+        LotMethod::Hifo => candidates
+            .max_by(|(_, a), (_, b)| a.unit_cost.partial_cmp(&b.unit_cost).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0xaaaa";
+    const OTHER: &str = "0xbbbb";
+
+    fn day(n: i64) -> i64 {
+        days_from_civil(2023, 1, 1) * 86400 + n * 86400
+    }
+
+    // amounts are whole ETH, fiat_value is the total value in USD.
+    fn trx(from: &str, to: &str, eth: u64, fiat_value: f64, timestamp: i64) -> (String, Transaction) {
+        let mut trx = Transaction::new(from.to_string(), to.to_string(), format!("{}000000000000000000", eth),
+                                       format!("0x{}", timestamp), "ETH".to_string(), 1, 18, None, Some(true));
+        trx.timestamp = timestamp;
+        trx.fiat_value = fiat_value;
+        trx.fiat_currency = "USD".to_string();
+
+        (WALLET.to_string(), trx)
+    }
+
+    fn report(txs: Vec<(String, Transaction)>, method: LotMethod) -> TaxReport {
+        build_report(txs, &[WALLET.to_string()], 2023, method, "USD").ok().unwrap()
+    }
+
+    #[test]
+    fn fifo_uses_the_oldest_lot() {
+        let report = report(vec![
+            trx(OTHER, WALLET, 1, 1000f64, day(0)),
+            trx(OTHER, WALLET, 1, 2000f64, day(1)),
+            trx(WALLET, OTHER, 1, 3000f64, day(2)),
+        ], LotMethod::Fifo);
+
+        let disposal = report.events.iter().find(|e| e.kind == "Disposal").unwrap();
+        assert_eq!(disposal.cost_basis, 1000f64);
+        assert_eq!(disposal.proceeds, 3000f64);
+        assert_eq!(report.gains(), vec![("ETH".to_string(), 2000f64)]);
+    }
+
+    #[test]
+    fn lifo_and_hifo_pick_other_lots() {
+        let txs = || vec![
+            trx(OTHER, WALLET, 1, 3000f64, day(0)),
+            trx(OTHER, WALLET, 1, 1000f64, day(1)),
+            trx(WALLET, OTHER, 1, 2000f64, day(2)),
+        ];
+
+        assert_eq!(report(txs(), LotMethod::Lifo).gains(), vec![("ETH".to_string(), 1000f64)]);
+        assert_eq!(report(txs(), LotMethod::Hifo).gains(), vec![("ETH".to_string(), -1000f64)]);
+    }
+
+    #[test]
+    fn disposal_spanning_lots_is_split() {
+        let report = report(vec![
+            trx(OTHER, WALLET, 1, 1000f64, day(0)),
+            trx(OTHER, WALLET, 1, 2000f64, day(1)),
+            trx(WALLET, OTHER, 2, 5000f64, day(2)),
+        ], LotMethod::Fifo);
+
+        let disposals = report.events.iter().filter(|e| e.kind == "Disposal").collect::<Vec<&TaxEvent>>();
+        assert_eq!(disposals.len(), 2);
+        assert_eq!(disposals[0].cost_basis + disposals[1].cost_basis, 3000f64);
+        assert_eq!(report.gains(), vec![("ETH".to_string(), 2000f64)]);
+    }
+
+    #[test]
+    fn earlier_years_only_build_lots() {
+        let mut old = trx(OTHER, WALLET, 1, 500f64, 0);
+        old.1.timestamp = days_from_civil(2022, 6, 1) * 86400;
+
+        let report = report(vec![old, trx(WALLET, OTHER, 1, 2000f64, day(3))], LotMethod::Fifo);
+
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.events[0].acquired_at, Some(days_from_civil(2022, 6, 1) * 86400));
+        assert_eq!(report.gains(), vec![("ETH".to_string(), 1500f64)]);
+    }
+
+    #[test]
+    fn failed_and_internal_transactions_are_skipped() {
+        let mut failed = trx(OTHER, WALLET, 1, 1000f64, day(0));
+        failed.1.status = false;
+
+        let report = build_report(vec![failed, trx(WALLET, "0xcccc", 1, 1000f64, day(1))],
+                                  &[WALLET.to_string(), "0xcccc".to_string()], 2023, LotMethod::Fifo, "USD").ok().unwrap();
+
+        assert!(report.events.is_empty());
+    }
+
+    #[test]
+    fn missing_prices_are_flagged() {
+        let mut unpriced = trx(OTHER, WALLET, 1, 0f64, day(0));
+        unpriced.1.fiat_currency = "".to_string();
+
+        let report = report(vec![unpriced, trx(WALLET, OTHER, 1, 2000f64, day(1))], LotMethod::Fifo);
+
+        assert_eq!(report.unpriced(), 2);
+        assert_eq!(report.currency, "USD");
+    }
+
+    #[test]
+    fn mixed_currencies_are_rejected() {
+        let mut euro = trx(WALLET, OTHER, 1, 900f64, day(1));
+        euro.1.fiat_currency = "EUR".to_string();
+
+        let result = build_report(vec![trx(OTHER, WALLET, 1, 1000f64, day(0)), euro], &[WALLET.to_string()], 2023,
+                                  LotMethod::Fifo, "USD");
+
+        assert_eq!(result.err(), Some(vec!["USD".to_string(), "EUR".to_string()]));
+    }
+}
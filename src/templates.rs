@@ -0,0 +1,134 @@
+use crate::renderer::Markup;
+
+pub const PRESETS: [(&str, &str); 4] = [
+    ("default", ""),
+    ("compact", "{arrow} {amount} {token} {from_label} → {to_label}"),
+    ("detailed", "{arrow} {direction} {amount} {token}{fiat}\nFrom: {from_label}\nTo: {to_label}\nBlock: {block} at {time}\nFee: {fee} ETH\n{tx_url}"),
+    ("oneline", "{direction} {amount} {token} | {tx_url}"),
+];
+
+pub const VARIABLES: [&str; 22] = [
+    "arrow", "direction", "amount", "token", "symbol", "received_amount", "received_token", "received_symbol",
+    "from", "to", "from_label", "to_label", "wallet", "wallet_label", "tx_hash", "tx_url",
+    "wallet_url", "block", "time", "fee", "fiat", "fiat_value",
+];
+
+pub const MAX_LENGTH: usize = 1000;
+
+pub fn preset(name: &str) -> Option<&'static str> {
+    PRESETS.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, t)| *t)
+}
+
+// splits the template into literal text and variable names, Err names the first problem found.
+fn parse(template: &str) -> Result<Vec<(bool, &str)>, String> {
+    let mut parts = vec![];
+    let mut rest = template;
+
+    while let Some(open) = rest.find(|c: char| c == '{' || c == '}') {
+        if rest[open..].starts_with('}') {
+            return Err("unexpected }".to_string());
+        }
+
+        let close = match rest[open..].find('}') {
+            Some(c) => open + c,
+            None => return Err("unclosed {".to_string()),
+        };
+
+        let name = &rest[open + 1..close];
+
+        if !VARIABLES.contains(&name) {
+            return Err(format!("unknown variable {{{}}}", name));
+        }
+
+        parts.push((false, &rest[..open]));
+        parts.push((true, name));
+        rest = &rest[close + 1..];
+    }
+
+    parts.push((false, rest));
+
+    Ok(parts)
+}
+
+pub fn validate(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("template is empty".to_string());
+    }
+
+    if template.chars().count() > MAX_LENGTH {
+        return Err(format!("template is longer than {} characters", MAX_LENGTH));
+    }
+
+    parse(template).map(|_| ())
+}
+
+// literal text and values are both escaped, so a template can not inject markup.
+pub fn render(template: &str, vars: &[(&str, String)], markup: Markup) -> Result<String, String> {
+    let mut text = String::new();
+
+    for (is_variable, part) in parse(template)? {
+        if !is_variable {
+            text.push_str(markup.escape(part).as_str());
+            continue;
+        }
+
+        let value = vars.iter()
+            .find(|(n, _)| *n == part)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or("");
+
+        text.push_str(markup.escape(value).as_str());
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid() {
+        for (name, template) in PRESETS.iter().filter(|(_, t)| !t.is_empty()) {
+            assert_eq!(validate(template), Ok(()), "{}", name);
+        }
+
+        assert_eq!(preset("Compact"), Some(PRESETS[1].1));
+        assert_eq!(preset("fancy"), None);
+    }
+
+    #[test]
+    fn unknown_variables_and_unbalanced_braces_are_rejected() {
+        assert_eq!(validate("{amount} {price}"), Err("unknown variable {price}".to_string()));
+        assert_eq!(validate("{}"), Err("unknown variable {}".to_string()));
+        assert_eq!(validate("{amount"), Err("unclosed {".to_string()));
+        assert_eq!(validate("amount}"), Err("unexpected }".to_string()));
+        assert_eq!(validate("  \n"), Err("template is empty".to_string()));
+    }
+
+    #[test]
+    fn length_is_counted_in_characters() {
+        assert_eq!(validate("€".repeat(MAX_LENGTH).as_str()), Ok(()));
+        assert!(validate("€".repeat(MAX_LENGTH + 1).as_str()).is_err());
+    }
+
+    #[test]
+    fn literal_text_and_values_are_escaped() {
+        let vars = [("token", "<b>USDC</b>".to_string())];
+
+        assert_eq!(render("a < b: {token}", &vars, Markup::Html), Ok("a &lt; b: &lt;b&gt;USDC&lt;/b&gt;".to_string()));
+        assert_eq!(render("1.5 {token}!", &vars, Markup::MarkdownV2), Ok("1\\.5 <b\\>USDC</b\\>\\!".to_string()));
+    }
+
+    #[test]
+    fn every_variable_is_rendered() {
+        let template = VARIABLES.iter().map(|v| format!("{{{}}}", v)).collect::<Vec<String>>().join("|");
+        let vars = VARIABLES.iter().map(|v| (*v, v.to_uppercase())).collect::<Vec<(&str, String)>>();
+
+        assert_eq!(render(template.as_str(), &vars, Markup::Html),
+                   Ok(VARIABLES.iter().map(|v| v.to_uppercase()).collect::<Vec<String>>().join("|")));
+        assert_eq!(render("{fiat}{amount}", &[], Markup::Html), Ok("".to_string()));
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+use teloxide::{prelude::*, types::ChatId, RequestError};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{self, Duration, Instant};
+
+// telegram allows about 30 messages per second overall, 1 per second in a private chat
+// and 20 per minute in a group.
+const GLOBAL_RATE: f64 = 30.0;
+const PRIVATE_RATE: f64 = 1.0;
+const GROUP_RATE: f64 = 20.0 / 60.0;
+
+// buckets of idle chats are dropped once there are this many.
+const MAX_IDLE_BUCKETS: usize = 1000;
+
+type Permit = (i64, oneshot::Sender<()>);
+
+static SCHEDULER: OnceLock<mpsc::UnboundedSender<Permit>> = OnceLock::new();
+
+struct Bucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(capacity: f64, rate: f64) -> Self {
+        Bucket {
+            capacity,
+            rate,
+            tokens: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    fn for_chat(chat_id: i64) -> Self {
+        if chat_id < 0 { Bucket::new(1.0, GROUP_RATE) } else { Bucket::new(1.0, PRIVATE_RATE) }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        self.tokens = (self.tokens + now.duration_since(self.updated).as_secs_f64() * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    fn ready(&self) -> bool {
+        self.tokens >= 1.0
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= self.capacity
+    }
+
+    fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+
+    fn ready_at(&self) -> Instant {
+        self.updated + Duration::from_secs_f64(((1.0 - self.tokens) / self.rate).max(0.0))
+    }
+}
+
+// waits for a slot to send to the chat. Chats with pending messages are served round robin,
+// so one chat listing a hundred wallets does not hold back everyone else.
+pub async fn acquire(chat_id: ChatId) {
+    let scheduler = SCHEDULER.get_or_init(|| {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(schedule(receiver));
+
+        sender
+    });
+
+    let (sender, receiver) = oneshot::channel();
+
+    if scheduler.send((chat_id.0, sender)).is_ok() {
+        receiver.await.ok();
+    }
+}
+
+pub async fn send_message<T>(bot: &AutoSend<Bot>, chat_id: ChatId, text: T) -> Result<Message, RequestError>
+    where T: Into<String> {
+    acquire(chat_id).await;
+
+    bot.send_message(chat_id, text).await
+}
+
+async fn schedule(mut receiver: mpsc::UnboundedReceiver<Permit>) {
+    let mut global = Bucket::new(GLOBAL_RATE, GLOBAL_RATE);
+    let mut buckets: HashMap<i64, Bucket> = HashMap::new();
+    let mut waiting: HashMap<i64, VecDeque<oneshot::Sender<()>>> = HashMap::new();
+    // chats with waiting senders in the order they are served.
+    let mut turns: VecDeque<i64> = VecDeque::new();
+
+    loop {
+        let now = Instant::now();
+        let mut wake_at: Option<Instant> = None;
+
+        global.refill(now);
+
+        for _ in 0..turns.len() {
+            if !global.ready() {
+                wake_at = Some(global.ready_at());
+                break;
+            }
+
+            let chat_id = turns.pop_front().unwrap();
+            let bucket = buckets.entry(chat_id).or_insert_with(|| Bucket::for_chat(chat_id));
+            bucket.refill(now);
+
+            if !bucket.ready() {
+                wake_at = Some(wake_at.map_or(bucket.ready_at(), |w| w.min(bucket.ready_at())));
+                turns.push_back(chat_id);
+                continue;
+            }
+
+            let queue = waiting.get_mut(&chat_id).unwrap();
+
+            // a sender whose caller went away does not use up a slot.
+            if let Some(sender) = queue.pop_front() {
+                if sender.send(()).is_ok() {
+                    bucket.take();
+                    global.take();
+                }
+            }
+
+            if queue.is_empty() {
+                waiting.remove(&chat_id);
+            } else {
+                turns.push_back(chat_id);
+            }
+        }
+
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            buckets.retain(|chat_id, bucket| {
+                bucket.refill(now);
+                waiting.contains_key(chat_id) || !bucket.is_full()
+            });
+        }
+
+        let permit = match wake_at {
+            Some(at) if !turns.is_empty() => {
+                tokio::select! {
+                    permit = receiver.recv() => permit,
+                    _ = time::sleep_until(at) => continue,
+                }
+            }
+            _ if !turns.is_empty() => continue,
+            _ => receiver.recv().await,
+        };
+
+        let (chat_id, sender) = match permit {
+            Some(p) => p,
+            None => break,
+        };
+
+        if !waiting.contains_key(&chat_id) {
+            turns.push_back(chat_id);
+        }
+
+        waiting.entry(chat_id).or_default().push_back(sender);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_starts_full_and_empties() {
+        let mut bucket = Bucket::new(2.0, 1.0);
+
+        assert!(bucket.ready() && bucket.is_full());
+        bucket.take();
+        bucket.take();
+        assert!(!bucket.ready());
+    }
+
+    #[test]
+    fn bucket_refills_at_its_rate_up_to_capacity() {
+        let mut bucket = Bucket::new(1.0, GROUP_RATE);
+        let start = bucket.updated;
+
+        bucket.take();
+        assert_eq!(bucket.ready_at(), start + Duration::from_secs(3));
+
+        bucket.refill(start + Duration::from_secs(2));
+        assert!(!bucket.ready());
+
+        bucket.refill(start + Duration::from_secs(60));
+        assert!(bucket.ready() && bucket.is_full());
+        assert!(bucket.tokens <= bucket.capacity);
+    }
+
+    #[test]
+    fn groups_are_slower_than_private_chats() {
+        assert_eq!(Bucket::for_chat(42).rate, PRIVATE_RATE);
+        assert_eq!(Bucket::for_chat(-100042).rate, GROUP_RATE);
+    }
+
+    #[tokio::test]
+    async fn a_waiting_chat_does_not_block_others() {
+        acquire(ChatId(-100777)).await;
+
+        // the group has to wait three seconds for its next slot.
+        let waiting = tokio::spawn(acquire(ChatId(-100777)));
+        time::sleep(Duration::from_millis(50)).await;
+
+        let start = Instant::now();
+        acquire(ChatId(778)).await;
+
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert!(!waiting.is_finished());
+    }
+}